@@ -1,5 +1,40 @@
 use std::borrow::Cow;
 
+/// A known download mirror of the GameBanana files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mirror {
+    /// Key used in the mirror preferences (e.g., "jade")
+    pub id: &'static str,
+    /// Human-readable location of the server
+    pub location: &'static str,
+    /// Base URL of the server
+    pub base_url: &'static str,
+}
+
+/// All mirrors supported by [`get_all_mirror_urls`].
+pub const MIRRORS: [Mirror; 4] = [
+    Mirror {
+        id: "gb",
+        location: "Default GameBanana Server",
+        base_url: "https://gamebanana.com/",
+    },
+    Mirror {
+        id: "jade",
+        location: "Germany",
+        base_url: "https://celestemodupdater.0x0a.de/banana-mirror/",
+    },
+    Mirror {
+        id: "wegfan",
+        location: "China",
+        base_url: "https://celeste.weg.fan/",
+    },
+    Mirror {
+        id: "otobot",
+        location: "North America",
+        base_url: "https://banana-mirror-mods.celestemods.com/",
+    },
+];
+
 /// Gets all mirror URLs based on the given preferences.
 ///
 /// The following code is my Rust implementation, ported from the original C# code. I extend my respect to the original author(s) for their work.
@@ -81,6 +116,16 @@ mod tests {
         assert_eq!(extract_gamebanana_id("https://example.com/file"), 0);
     }
 
+    #[test]
+    fn test_every_mirror_is_supported() {
+        let url = "https://gamebanana.com/dl/12345";
+        for mirror in MIRRORS {
+            let urls = get_all_mirror_urls(url, mirror.id);
+            assert_eq!(urls.len(), 1, "mirror '{}' is not supported", mirror.id);
+            assert!(urls[0].starts_with(mirror.base_url));
+        }
+    }
+
     #[test]
     fn test_mirror_urls() {
         let url = "https://gamebanana.com/dl/12345";
@@ -0,0 +1,73 @@
+//! Phase timings printed by the hidden `--bench` flag.
+//!
+//! Helps to tell whether scanning, parsing, the network or hashing dominates on a given setup.
+use std::{
+    sync::{
+        Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static PHASES: Mutex<Vec<(&'static str, Duration)>> = Mutex::new(Vec::new());
+
+/// Starts collecting phase timings.
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// Records the time elapsed since `started` for the given phase.
+///
+/// Does nothing unless `--bench` was given. A phase recorded several times is summed up.
+pub fn record(phase: &'static str, started: Instant) {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    let elapsed = started.elapsed();
+    tracing::debug!("[bench] {}: {:?}", phase, elapsed);
+
+    let Ok(mut phases) = PHASES.lock() else {
+        return;
+    };
+    match phases.iter_mut().find(|(name, _)| *name == phase) {
+        Some((_, total)) => *total += elapsed,
+        None => phases.push((phase, elapsed)),
+    }
+}
+
+/// Prints the breakdown of every recorded phase to stderr.
+pub fn print_summary(total: Duration) {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    let Ok(phases) = PHASES.lock() else {
+        return;
+    };
+    eprintln!();
+    eprintln!("⏱️  Benchmark:");
+    for (phase, elapsed) in phases.iter() {
+        eprintln!("  {:<24} {:>10.3}s", phase, elapsed.as_secs_f64());
+    }
+    eprintln!("  {:<24} {:>10.3}s", "total", total.as_secs_f64());
+}
+
+#[cfg(test)]
+mod tests_bench {
+    use super::*;
+
+    #[test]
+    fn test_record_sums_repeated_phases() {
+        enable();
+        let started = Instant::now() - Duration::from_millis(10);
+        record("test phase", started);
+        record("test phase", started);
+
+        let phases = PHASES.lock().unwrap();
+        let (_, total) = phases
+            .iter()
+            .find(|(name, _)| *name == "test phase")
+            .unwrap();
+        assert!(*total >= Duration::from_millis(20));
+    }
+}
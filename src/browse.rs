@@ -0,0 +1,266 @@
+//! Interactive browser of the mod registry for the `browse` command, behind the `browse` feature.
+//!
+//! The state of the browser and its rendering don't touch the terminal, so they can be tested
+//! without one.
+use std::{collections::BTreeSet, io};
+
+use console::{Key, Term, style};
+
+use crate::mod_registry::{RemoteModInfo, RemoteModRegistry};
+
+/// Lines of the screen used by everything but the list of mods.
+const RESERVED_LINES: usize = 14;
+
+/// Keys shown at the bottom of the screen.
+const HELP: &str = "↑/↓ move · type to search · Tab select · Enter install · Esc quit";
+
+/// What to do after a key press.
+#[derive(Debug, PartialEq, Eq)]
+enum Action {
+    /// Keep browsing.
+    Continue,
+    /// Install the selected mods.
+    Confirm,
+    /// Leave without installing anything.
+    Quit,
+}
+
+/// State of the browser.
+struct Browser<'a> {
+    /// Every mod of the registry, sorted by name
+    mods: Vec<(&'a str, &'a RemoteModInfo)>,
+    /// The search query typed so far
+    query: String,
+    /// Indices into `mods` of the mods matching the query
+    matches: Vec<usize>,
+    /// Position of the cursor in `matches`
+    cursor: usize,
+    /// Names of the mods selected for installation
+    selected: BTreeSet<&'a str>,
+}
+
+impl<'a> Browser<'a> {
+    fn new(mod_registry: &'a RemoteModRegistry) -> Self {
+        let mut mods: Vec<_> = mod_registry
+            .iter()
+            .filter(|(_, remote_mod)| remote_mod.is_mod_submission())
+            .map(|(name, remote_mod)| (name.as_str(), remote_mod))
+            .collect();
+        mods.sort_by_key(|(name, _)| name.to_lowercase());
+        let mut browser = Self {
+            mods,
+            query: String::new(),
+            matches: Vec::new(),
+            cursor: 0,
+            selected: BTreeSet::new(),
+        };
+        browser.update_matches();
+        browser
+    }
+
+    /// Filters the mods by the query, case-insensitively, keeping the cursor in range.
+    fn update_matches(&mut self) {
+        let query = self.query.to_lowercase();
+        self.matches = self
+            .mods
+            .iter()
+            .enumerate()
+            .filter(|(_, (name, _))| name.to_lowercase().contains(&query))
+            .map(|(i, _)| i)
+            .collect();
+        self.cursor = self.cursor.min(self.matches.len().saturating_sub(1));
+    }
+
+    /// Returns the mod under the cursor, if any mod matches the query.
+    fn current(&self) -> Option<(&'a str, &'a RemoteModInfo)> {
+        self.matches.get(self.cursor).map(|&i| self.mods[i])
+    }
+
+    fn handle_key(&mut self, key: Key) -> Action {
+        match key {
+            Key::ArrowUp => self.cursor = self.cursor.saturating_sub(1),
+            Key::ArrowDown if self.cursor + 1 < self.matches.len() => self.cursor += 1,
+            Key::Tab => {
+                if let Some((name, _)) = self.current()
+                    && !self.selected.remove(name)
+                {
+                    self.selected.insert(name);
+                }
+            }
+            Key::Enter => return Action::Confirm,
+            Key::Escape | Key::CtrlC => return Action::Quit,
+            Key::Backspace => {
+                self.query.pop();
+                self.update_matches();
+            }
+            Key::Char(c) if !c.is_control() => {
+                self.query.push(c);
+                self.update_matches();
+            }
+            _ => {}
+        }
+        Action::Continue
+    }
+
+    /// Renders the screen for a terminal of the given height.
+    fn render(&self, height: usize) -> Vec<String> {
+        let mut lines = vec![
+            format!("Search: {}_", self.query),
+            format!(
+                "{} mods found, {} selected",
+                self.matches.len(),
+                self.selected.len()
+            ),
+            String::new(),
+        ];
+
+        let list_height = height.saturating_sub(RESERVED_LINES).max(3);
+        let start = (self.cursor + 1).saturating_sub(list_height);
+        for (position, &i) in self
+            .matches
+            .iter()
+            .enumerate()
+            .skip(start)
+            .take(list_height)
+        {
+            let (name, remote_mod) = self.mods[i];
+            let pointer = if position == self.cursor { ">" } else { " " };
+            let mark = if self.selected.contains(name) {
+                "[x]"
+            } else {
+                "[ ]"
+            };
+            lines.push(format!("{pointer} {mark} {name} ({})", remote_mod.version));
+        }
+
+        lines.push(String::new());
+        if let Some((name, remote_mod)) = self.current() {
+            lines.extend(remote_mod.describe(name));
+        }
+        lines.push(String::new());
+        lines.push(HELP.to_string());
+        lines
+    }
+}
+
+/// Lets the user search the registry and select mods, and returns the names of the selected
+/// mods, sorted. Nothing is returned if the user quits.
+///
+/// # Errors
+///
+/// Returns an error if stdout is not a terminal or the terminal can't be controlled.
+pub fn run(mod_registry: &RemoteModRegistry) -> io::Result<Vec<String>> {
+    let term = Term::stdout();
+    if !term.is_term() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotConnected,
+            "browse needs an interactive terminal",
+        ));
+    }
+
+    let mut browser = Browser::new(mod_registry);
+    term.hide_cursor()?;
+    let action = (|| {
+        loop {
+            let (height, width) = term.size();
+            term.clear_screen()?;
+            for line in browser.render(height as usize) {
+                let line = console::truncate_str(&line, width as usize, "…");
+                if line.starts_with('>') {
+                    term.write_line(&style(line).bold().to_string())?;
+                } else {
+                    term.write_line(&line)?;
+                }
+            }
+            match browser.handle_key(term.read_key()?) {
+                Action::Continue => {}
+                action => return io::Result::Ok(action),
+            }
+        }
+    })();
+    term.clear_screen()?;
+    term.show_cursor()?;
+
+    Ok(match action? {
+        Action::Confirm => browser.selected.into_iter().map(str::to_string).collect(),
+        _ => Vec::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests_browse {
+    use super::*;
+
+    fn registry(names: &[&str]) -> RemoteModRegistry {
+        names
+            .iter()
+            .map(|name| {
+                let remote_mod = RemoteModInfo {
+                    version: "1.0.0".to_string(),
+                    ..Default::default()
+                };
+                (name.to_string(), remote_mod)
+            })
+            .collect()
+    }
+
+    fn type_text(browser: &mut Browser, text: &str) {
+        for c in text.chars() {
+            browser.handle_key(Key::Char(c));
+        }
+    }
+
+    #[test]
+    fn test_search_select_and_confirm() {
+        let registry = registry(&["FrostHelper", "SpeedrunTool", "CommunalHelper"]);
+        let mut browser = Browser::new(&registry);
+        assert_eq!(browser.matches.len(), 3);
+
+        type_text(&mut browser, "helper");
+        assert_eq!(
+            browser.current().map(|(name, _)| name),
+            Some("CommunalHelper")
+        );
+        browser.handle_key(Key::ArrowDown);
+        browser.handle_key(Key::Tab);
+        browser.handle_key(Key::ArrowDown); // Stays on the last match.
+        assert_eq!(browser.current().map(|(name, _)| name), Some("FrostHelper"));
+
+        for _ in 0.."helper".len() {
+            browser.handle_key(Key::Backspace);
+        }
+        assert_eq!(browser.matches.len(), 3);
+        assert_eq!(browser.handle_key(Key::Enter), Action::Confirm);
+        assert_eq!(browser.selected, BTreeSet::from(["FrostHelper"]));
+    }
+
+    #[test]
+    fn test_tab_toggles_selection() {
+        let registry = registry(&["FrostHelper"]);
+        let mut browser = Browser::new(&registry);
+        browser.handle_key(Key::Tab);
+        assert_eq!(browser.selected.len(), 1);
+        browser.handle_key(Key::Tab);
+        assert!(browser.selected.is_empty());
+        assert_eq!(browser.handle_key(Key::Escape), Action::Quit);
+    }
+
+    #[test]
+    fn test_render_keeps_cursor_visible() {
+        let names: Vec<String> = (0..30).map(|i| format!("Mod{i:02}")).collect();
+        let names: Vec<&str> = names.iter().map(String::as_str).collect();
+        let registry = registry(&names);
+        let mut browser = Browser::new(&registry);
+        for _ in 0..20 {
+            browser.handle_key(Key::ArrowDown);
+        }
+
+        let lines = browser.render(RESERVED_LINES + 5);
+        let list: Vec<&String> = lines
+            .iter()
+            .filter(|line| line.contains("(1.0.0)"))
+            .collect();
+        assert_eq!(list.len(), 5);
+        assert_eq!(list.last().unwrap().as_str(), "> [ ] Mod20 (1.0.0)");
+    }
+}
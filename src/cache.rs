@@ -0,0 +1,287 @@
+//! On-disk cache of the mod registry and the dependency graph, enabled by `--cache`.
+//!
+//! Each remote file is cached on its own, so a fresh copy of one is reused even if fetching
+//! the other one failed. Every cached file is written to the directory returned by
+//! [`cache_directory`].
+use std::{
+    env,
+    ffi::OsString,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{Mutex, OnceLock},
+    time::{Duration, SystemTime},
+};
+
+use serde::de::DeserializeOwned;
+
+use crate::{
+    constant::{CACHE_DIRECTORY_NAME, CACHE_DIRECTORY_PATH, CACHE_TTL_SECS},
+    fileutil,
+};
+
+static DIRECTORY: OnceLock<PathBuf> = OnceLock::new();
+static HITS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Returns the path to the directory where the cached files are stored.
+///
+/// The `--cache-dir` option takes precedence over `XDG_CACHE_HOME` (on Linux), which takes
+/// precedence over `~/.cache/everest-mod-cli`. If none of them is available, it returns None.
+pub fn cache_directory(cache_dir: Option<&Path>) -> Option<PathBuf> {
+    let xdg_cache_home = if cfg!(target_os = "linux") {
+        env::var_os("XDG_CACHE_HOME")
+    } else {
+        None
+    };
+    resolve_directory(cache_dir, xdg_cache_home, env::home_dir())
+}
+
+fn resolve_directory(
+    cache_dir: Option<&Path>,
+    xdg_cache_home: Option<OsString>,
+    home_dir: Option<PathBuf>,
+) -> Option<PathBuf> {
+    if let Some(directory) = cache_dir {
+        return Some(directory.to_path_buf());
+    }
+    // The XDG spec says that relative paths are invalid and must be ignored.
+    match xdg_cache_home.map(PathBuf::from) {
+        Some(xdg_cache_home) if xdg_cache_home.is_absolute() => {
+            Some(xdg_cache_home.join(CACHE_DIRECTORY_NAME))
+        }
+        _ => home_dir.map(|home_path| home_path.join(CACHE_DIRECTORY_PATH)),
+    }
+}
+
+/// Deletes the cached files, returning how many were deleted.
+///
+/// Subdirectories, i.e. the store of `--dedup-store` which the mods directories link to, are
+/// kept. The directory itself is removed once it is empty.
+///
+/// # Errors
+/// Returns an error if the directory exists but a file could not be read or deleted.
+pub fn clear(directory: &Path) -> io::Result<usize> {
+    let entries = match fs::read_dir(directory) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(0),
+        Err(err) => return Err(err),
+    };
+    let mut count = 0;
+    for entry in entries {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            continue;
+        }
+        fs::remove_file(entry.path())?;
+        count += 1;
+    }
+    // NOTE: This fails if the directory isn't empty, which is expected.
+    let _ = fs::remove_dir(directory);
+    tracing::info!(
+        "Deleted {} cached files from '{}'",
+        count,
+        directory.display()
+    );
+    Ok(count)
+}
+
+/// Starts caching the remote files in the given directory.
+pub fn enable(directory: PathBuf) {
+    tracing::info!(
+        "Caching remote files in '{}'",
+        fileutil::replace_home_dir_with_tilde(&directory)
+    );
+    let _ = DIRECTORY.set(directory);
+}
+
+/// Checks if caching is enabled.
+pub fn is_enabled() -> bool {
+    DIRECTORY.get().is_some()
+}
+
+/// Returns the parsed cached contents of the URL if caching is enabled and the entry is fresh.
+///
+/// A cached file that can't be parsed, e.g. after an interrupted write or a disk error, is
+/// deleted with a warning so that the caller fetches it again.
+pub fn read<T: DeserializeOwned>(url: &str) -> Option<T> {
+    let data = read_entry(entry_name(url), Duration::from_secs(CACHE_TTL_SECS))?;
+    tracing::info!("'{}' -> Served from cache", url);
+    if let Ok(mut hits) = HITS.lock() {
+        hits.push(url.to_string());
+    }
+    Some(data)
+}
+
+/// Stores the contents of the URL if caching is enabled.
+///
+/// Failures are only logged, since the cache is an optimization.
+pub fn write(url: &str, bytes: &[u8]) {
+    write_entry(entry_name(url), bytes);
+}
+
+/// Returns the parsed cache entry of the given name if caching is enabled and the entry is
+/// younger than `ttl`. A corrupted entry is deleted like in [`read`].
+pub fn read_entry<T: DeserializeOwned>(name: &str, ttl: Duration) -> Option<T> {
+    read_parsed_from(DIRECTORY.get()?, name, ttl)
+}
+
+/// Stores the cache entry of the given name if caching is enabled.
+///
+/// Failures are only logged, since the cache is an optimization.
+pub fn write_entry(name: &str, bytes: &[u8]) {
+    let Some(directory) = DIRECTORY.get() else {
+        return;
+    };
+    if let Err(err) = write_to(directory, name, bytes) {
+        tracing::warn!("Failed to cache '{}': {}", name, err);
+    }
+}
+
+/// Checks if the URL was served from the cache during this run.
+pub fn served_from_cache(url: &str) -> bool {
+    HITS.lock()
+        .is_ok_and(|hits| hits.iter().any(|hit| hit == url))
+}
+
+/// Returns the name of the cache entry of the URL, which is its last path segment.
+fn entry_name(url: &str) -> &str {
+    url.rsplit('/').next().unwrap_or(url)
+}
+
+fn read_from(directory: &Path, name: &str, ttl: Duration) -> Option<Vec<u8>> {
+    let path = directory.join(name);
+    let modified = fs::metadata(&path).and_then(|meta| meta.modified()).ok()?;
+    let age = SystemTime::now()
+        .duration_since(modified)
+        .unwrap_or_default();
+    if age > ttl {
+        tracing::debug!("Cache entry '{}' is stale ({:?} old)", path.display(), age);
+        return None;
+    }
+    fs::read(&path).ok()
+}
+
+fn read_parsed_from<T: DeserializeOwned>(directory: &Path, name: &str, ttl: Duration) -> Option<T> {
+    let bytes = read_from(directory, name, ttl)?;
+    match serde_yaml_ng::from_slice(&bytes) {
+        Ok(data) => Some(data),
+        Err(err) => {
+            let path = directory.join(name);
+            tracing::warn!(
+                "Deleting the corrupted cache entry '{}': {}",
+                path.display(),
+                err
+            );
+            if let Err(err) = fs::remove_file(&path) {
+                tracing::warn!("Failed to delete '{}': {}", path.display(), err);
+            }
+            None
+        }
+    }
+}
+
+fn write_to(directory: &Path, name: &str, bytes: &[u8]) -> std::io::Result<()> {
+    fs::create_dir_all(directory)?;
+    fileutil::write_atomic(&directory.join(name), bytes)
+}
+
+#[cfg(test)]
+mod tests_cache {
+    use super::*;
+
+    const NAME: &str = "everest_update.yaml";
+
+    #[test]
+    fn test_entry_name() {
+        assert_eq!(
+            entry_name("https://maddie480.ovh/celeste/everest_update.yaml"),
+            NAME
+        );
+    }
+
+    #[test]
+    fn test_resolve_directory_precedence() {
+        let flag = Path::new("/portable/cache");
+        let xdg = || Some(OsString::from("/xdg/cache"));
+        let home = || Some(PathBuf::from("/home/madeline"));
+
+        assert_eq!(
+            resolve_directory(Some(flag), xdg(), home()),
+            Some(flag.to_path_buf())
+        );
+        assert_eq!(
+            resolve_directory(None, xdg(), home()),
+            Some(PathBuf::from("/xdg/cache/everest-mod-cli"))
+        );
+        assert_eq!(
+            resolve_directory(None, Some(OsString::from("relative")), home()),
+            Some(PathBuf::from("/home/madeline/.cache/everest-mod-cli"))
+        );
+        assert_eq!(resolve_directory(None, None, None), None);
+    }
+
+    #[test]
+    fn test_clear_keeps_store() -> std::io::Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let directory = temp_dir.path().join("cache");
+        assert_eq!(clear(&directory)?, 0);
+
+        write_to(&directory, NAME, b"registry")?;
+        write_to(&directory, "mod_dependency_graph.yaml", b"graph")?;
+        assert_eq!(clear(&directory)?, 2);
+        assert!(!directory.exists());
+
+        // The store of `--dedup-store` is kept, since mods directories link to it.
+        write_to(&directory.join("store"), "1f3c5a7e9b2d4c60.zip", b"archive")?;
+        write_to(&directory, NAME, b"registry")?;
+        assert_eq!(clear(&directory)?, 1);
+        assert!(
+            directory
+                .join("store")
+                .join("1f3c5a7e9b2d4c60.zip")
+                .exists()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_then_read_fresh_entry() -> std::io::Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let directory = temp_dir.path().join("cache");
+        write_to(&directory, NAME, b"registry")?;
+
+        assert!(directory.join("everest_update.yaml").exists());
+        assert_eq!(
+            read_from(&directory, NAME, Duration::from_secs(60)).as_deref(),
+            Some(b"registry".as_slice())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_corrupted_entry_is_deleted() -> std::io::Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        write_to(temp_dir.path(), NAME, b"\xff\xfe\x00truncated")?;
+
+        let parsed: Option<std::collections::HashMap<String, String>> =
+            read_parsed_from(temp_dir.path(), NAME, Duration::from_secs(60));
+        assert!(parsed.is_none());
+        assert!(!temp_dir.path().join("everest_update.yaml").exists());
+
+        write_to(temp_dir.path(), NAME, b"SpeedrunTool: 3.24.0\n")?;
+        let parsed: Option<std::collections::HashMap<String, String>> =
+            read_parsed_from(temp_dir.path(), NAME, Duration::from_secs(60));
+        assert_eq!(parsed.unwrap()["SpeedrunTool"], "3.24.0");
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_skips_stale_or_missing_entry() -> std::io::Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        assert!(read_from(temp_dir.path(), NAME, Duration::from_secs(60)).is_none());
+
+        write_to(temp_dir.path(), NAME, b"registry")?;
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(read_from(temp_dir.path(), NAME, Duration::from_millis(10)).is_none());
+        Ok(())
+    }
+}
@@ -1,6 +1,16 @@
 use std::{num::ParseIntError, path::PathBuf};
 
-use clap::{Args, Parser, Subcommand};
+use clap::{Args, CommandFactory, Parser, Subcommand, ValueEnum};
+
+use crate::{
+    constant::{
+        DEFAULT_CONFIRM_DEPS_OVER, DEFAULT_MAX_CONNECTIONS_PER_HOST,
+        DEFAULT_POOL_IDLE_TIMEOUT_SECS, DEFAULT_POOL_MAX_IDLE_PER_HOST, DEFAULT_STALL_TIMEOUT_SECS,
+        LOG_FILE_STEM,
+    },
+    fileutil::ChecksumAlgorithm,
+    timeutil::UtcDateTime,
+};
 
 /// The main CLI structure for the Everest Mod CLI application
 #[derive(Debug, Parser)]
@@ -10,6 +20,30 @@ pub struct Cli {
     #[arg(short = 'd', long = "mods-dir", value_name = "DIR")]
     pub mods_directory: Option<PathBuf>,
 
+    /// Use the settings of the named profile of the config file
+    #[arg(
+        long,
+        value_name = "NAME",
+        long_help = "Use the settings of the named profile of the config file
+        (~/.config/everest-mod-cli/config.toml, or inside XDG_CONFIG_HOME on Linux).
+        A profile may set the mods directory, the mirror priority, the jobs and a proxy.
+        The command-line options take precedence over the profile."
+    )]
+    pub profile: Option<String>,
+
+    /// Also scan the subdirectories of the mods directory for installed mods
+    #[arg(
+        long,
+        long_help = "Also scan the subdirectories of the mods directory for installed mods.
+        Useful when mods are organized in category folders or symlinked from a library.
+        Note that Everest itself doesn't load mods from subdirectories."
+    )]
+    pub recursive: bool,
+
+    /// Warn about files in the mods directory that are neither mod archives nor Everest's lists
+    #[arg(long)]
+    pub warn_unknown: bool,
+
     /// Priority of the mirror list separated by commas
     #[arg(
         short = 'm',
@@ -27,18 +61,293 @@ pub struct Cli {
         automatically fall back to the next server in the priority list to
         retry the download. You can also restrict the fallback servers by
         providing a comma-separated list (e.g., \"otobot,jade\"), which will
-        limit the retries to only those specified servers.",
-        default_value = "otobot,gb,jade,wegfan"
+        limit the retries to only those specified servers.
+
+        Defaults to the mirror priority of --profile, or \"otobot,gb,jade,wegfan\"."
+    )]
+    pub mirror_preferences: Option<String>,
+
+    /// How the order of the mirrors is decided
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = MirrorStrategy::Priority,
+        long_help = "How the order of the mirrors is decided.
+        `priority` tries them in the order of --mirror-priority. `fastest` pings them once
+        before downloading and tries the fastest first, among the mirrors of --mirror-priority."
+    )]
+    pub mirror_strategy: MirrorStrategy,
+
+    /// Exclude files matching the gitignore-style pattern from the installed mod scan
+    #[arg(
+        long = "ignore",
+        value_name = "PATTERN",
+        long_help = "Exclude files matching the gitignore-style pattern from the installed mod scan.
+        Can be given multiple times. Patterns without a slash match the file name,
+        patterns with a slash match the path relative to the mods directory
+        (e.g., \"*-backup.zip\", \"Cache/\")."
+    )]
+    pub ignore_patterns: Vec<String>,
+
+    /// Maximum number of mods downloaded at once [default: 6]
+    #[arg(
+        short = 'j',
+        long = "jobs",
+        value_name = "N",
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..),
+        long_help = "Maximum number of mods downloaded at once.
+        Defaults to the jobs of --profile, or 6."
+    )]
+    pub jobs: Option<usize>,
+
+    /// Maximum number of connections opened to a single mirror at once
+    #[arg(
+        long = "per-host",
+        value_name = "N",
+        default_value_t = DEFAULT_MAX_CONNECTIONS_PER_HOST,
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..),
+        long_help = "Maximum number of connections opened to a single mirror at once.
+        Some mirrors throttle or ban clients opening too many parallel connections,
+        so this limit applies even when --jobs is higher."
+    )]
+    pub max_connections_per_host: usize,
+
+    /// Program used to transfer the mod files
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = Downloader::Builtin,
+        long_help = "Program used to transfer the mod files.
+        'external' uses aria2c for multi-connection downloads if it is found on PATH, and falls back to
+        the built-in downloader otherwise. Checksums are verified in both cases."
     )]
-    pub mirror_preferences: String,
+    pub downloader: Downloader,
 
-    /// Verbose mode: Write verbose logs to the file
+    /// Maximum number of idle connections kept alive per host
+    #[arg(
+        long = "pool-max-idle",
+        value_name = "N",
+        default_value_t = DEFAULT_POOL_MAX_IDLE_PER_HOST,
+        long_help = "Maximum number of idle connections kept alive per host.
+        Reusing connections avoids repeating the TLS handshake for every file
+        when many small mods are downloaded from the same mirror."
+    )]
+    pub pool_max_idle_per_host: usize,
+
+    /// Seconds an idle connection is kept alive for reuse
+    #[arg(
+        long = "pool-idle-timeout",
+        value_name = "SECS",
+        default_value_t = DEFAULT_POOL_IDLE_TIMEOUT_SECS
+    )]
+    pub pool_idle_timeout: u64,
+
+    /// Seconds without receiving any data after which a download moves on to the next mirror
+    #[arg(
+        long = "stall-timeout",
+        value_name = "SECS",
+        default_value_t = DEFAULT_STALL_TIMEOUT_SECS,
+        value_parser = clap::value_parser!(u64).range(1..),
+        long_help = "Seconds without receiving any data after which a download moves on to the next mirror.
+        Unlike a total timeout, this doesn't limit how long a large mod may take to download."
+    )]
+    pub stall_timeout: u64,
+
+    /// Abort a batch of downloads after N downloads failed in a row [default: never]
+    #[arg(
+        long,
+        value_name = "N",
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..),
+        long_help = "Abort a batch of downloads after N downloads failed in a row.
+        The remaining downloads are skipped, since the network or the mirrors are most likely down.
+        By default, every download is attempted."
+    )]
+    pub fail_fast_threshold: Option<usize>,
+
+    /// Download large files in N concurrent byte ranges
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 1,
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..=16),
+        long_help = "Download files larger than 8 MiB in N concurrent byte ranges with the built-in downloader.
+        The ranges are concatenated and the whole file is verified against the registry checksum.
+        Mirrors that don't accept range requests are downloaded as a single stream."
+    )]
+    pub segments: usize,
+
+    /// Write a JSON report summarizing the run to the given file
+    #[arg(long = "report", value_name = "FILE")]
+    pub report: Option<PathBuf>,
+
+    /// Quiet mode: Do not show progress bars and spinners
     #[arg(short, long)]
-    pub verbose: bool,
+    pub quiet: bool,
+
+    /// Print plain status lines instead of progress bars and spinners
+    #[arg(
+        long,
+        long_help = "Print plain status lines instead of progress bars and spinners, suitable for log capture.
+        This is the default when stdout is not a terminal."
+    )]
+    pub no_progress: bool,
+
+    /// Re-read and verify every newly installed file against the registry after install or update
+    #[arg(long)]
+    pub verify_after: bool,
+
+    /// Install downloaded files even when their checksum doesn't match the registry
+    #[arg(
+        long,
+        conflicts_with = "verify_after",
+        long_help = "Install downloaded files even when their checksum doesn't match the registry.
+        The other mirrors are tried first, and a mismatching file is installed only if none matches.
+        The checksums are still computed, and every mismatch is logged and reported with a warning.
+        Only use this as a last resort against persistent spurious checksum failures."
+    )]
+    pub no_verify: bool,
+
+    /// Allow downloading mods from URLs that aren't https
+    #[arg(
+        long,
+        long_help = "Allow downloading mods from URLs that aren't https.
+        By default, a mirror URL using plain HTTP is skipped with a warning, since the file could
+        be tampered with in transit. The checksums are verified either way."
+    )]
+    pub allow_insecure_download: bool,
+
+    /// Verify the detached signatures of the downloads against the key of --trusted-key
+    #[arg(
+        long,
+        requires = "trusted_key",
+        long_help = "Verify the detached OpenPGP signatures of the downloads against the key of
+        --trusted-key, with gpg. The signature of a mod is looked up at its download URL followed
+        by `.sig`. Mods without a signature are installed as usual; a mod whose signature is
+        invalid is downloaded from the next mirror."
+    )]
+    pub verify_signature: bool,
+
+    /// Public key the signatures must be made with, for --verify-signature
+    #[arg(long, value_name = "FILE", requires = "verify_signature")]
+    pub trusted_key: Option<PathBuf>,
+
+    /// Use a short progress bar showing only the percentage and the rate
+    #[arg(
+        long,
+        long_help = "Use a short progress bar showing only the percentage and the rate.
+        This is the default on terminals narrower than 100 columns."
+    )]
+    pub compact: bool,
+
+    /// Show the overall download progress in the terminal title
+    #[arg(
+        long,
+        long_help = "Show the overall download progress in the terminal title, e.g.
+        \"everest-mod-cli: 4/12 mods\", which is visible while the terminal is in the background.
+        Only used when stdout is a terminal. The previous title is restored afterwards on
+        terminals that support it, and cleared on the others."
+    )]
+    pub title_progress: bool,
+
+    /// Reuse the mod registry and the dependency graph fetched in the last few minutes
+    #[arg(
+        long,
+        long_help = "Reuse the mod registry and the dependency graph fetched in the last few minutes.
+        Each of them is cached on its own in the cache directory (see --cache-dir), so when one
+        fetch fails, only that one is fetched again on the next run."
+    )]
+    pub cache: bool,
+
+    /// Directory where cached files are stored [default: ~/.cache/everest-mod-cli]
+    #[arg(
+        long,
+        value_name = "DIR",
+        long_help = "Directory where cached files are stored.
+        Without this option, $XDG_CACHE_HOME/everest-mod-cli is used on Linux if XDG_CACHE_HOME is set,
+        and ~/.cache/everest-mod-cli otherwise."
+    )]
+    pub cache_dir: Option<PathBuf>,
+
+    /// Download each mod once into a store in the cache directory and link it into the mods directory
+    #[arg(
+        long,
+        long_help = "Download each mod once into a store in the cache directory and link it into the mods directory.
+        The store is shared by every mods directory, so profiles using the same mods don't duplicate them on disk.
+        Where symlinks can't be created, the archives are copied instead."
+    )]
+    pub dedup_store: bool,
+
+    /// Modify the mods even if the game is running
+    #[arg(
+        long,
+        long_help = "Modify the mods even if the game is running.
+        Without this option, install and update --install check for a running Celeste process first,
+        and ask whether to continue if one is found."
+    )]
+    pub force: bool,
+
+    /// List every mod archive that could not be read, with the reason
+    #[arg(
+        long,
+        conflicts_with = "strict",
+        long_help = "List every mod archive that could not be read, with the reason, and continue with the others.
+        Without this option, unreadable archives are skipped and only counted, with the details in the log file."
+    )]
+    pub keep_going: bool,
+
+    /// Fail if any mod archive could not be read
+    #[arg(
+        long,
+        long_help = "Fail if any mod archive could not be read, listing them with the reason.
+        Without this option, unreadable archives are skipped."
+    )]
+    pub strict: bool,
+
+    /// Answer yes to every confirmation prompt
+    #[arg(short = 'y', long)]
+    pub yes: bool,
+
+    /// Append the details of downloads failing checksum verification to this file
+    #[arg(
+        long,
+        value_name = "FILE",
+        long_help = "Append the details of downloads failing checksum verification to this file.
+        It lists the mod, its URL, the expected size and checksums, and for each mirror tried the
+        computed checksum and the downloaded size, ready to be attached to a bug report."
+    )]
+    pub checksum_mismatch_report: Option<PathBuf>,
+
+    /// Print how long each phase of the command took
+    #[arg(long, hide = true)]
+    pub bench: bool,
+
+    /// Verbose mode: Write verbose logs to the file and show them on stderr (-v, -vv, -vvv)
+    #[arg(
+        short,
+        long,
+        action = clap::ArgAction::Count,
+        long_help = "Verbose mode: Write verbose logs to the file and show them on stderr.
+        By default, nothing but the errors of the command is shown on stderr and the log file gets info logs.
+        -v shows info logs on stderr and writes debug logs to the file,
+        -vv shows debug logs on stderr, and -vvv shows trace logs on both."
+    )]
+    pub verbose: u8,
+
+    /// Write the log to this file instead of the log directory
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["log_dir", "log_naming"])]
+    pub log_file: Option<PathBuf>,
+
+    /// Directory where the log file is written [default: ~/.local/state/everest-mod-cli]
+    #[arg(long, value_name = "DIR")]
+    pub log_dir: Option<PathBuf>,
+
+    /// Naming of the log file, to keep the logs of previous runs
+    #[arg(long, value_enum, default_value_t = LogNaming::Single)]
+    pub log_naming: LogNaming,
 
     /// The subcommand to execute
     #[command(subcommand)]
-    pub command: Commands,
+    pub command: Option<Commands>,
 }
 
 /// The set of available subcommands for the Everest Mod CLI
@@ -46,178 +355,1013 @@ pub struct Cli {
 pub enum Commands {
     /// Install a mod using the URL
     Install(InstallArgs),
+    /// Install every mod of a pack file
+    InstallPack(InstallPackArgs),
     /// List installed mods
-    List,
+    List(ListArgs),
     /// Show detailed information about an installed mod
     Show(ShowArgs),
     /// Check for updates
     Update(UpdateArgs),
+    /// Check the reachability and latency of each download mirror
+    Ping,
+    /// List the known download mirrors and the configured priority order
+    Mirrors,
+    /// Search the mod registry by name
+    Search(SearchArgs),
+    /// Show what the mod registry says about a mod, without installing it
+    RegistryShow(RegistryShowArgs),
+    /// Show the history of installed and updated mods
+    History(HistoryArgs),
+    /// Pin a mod to a version so that update skips newer ones
+    Pin(PinArgs),
+    /// List the mods that require a mod, according to the dependency graph
+    Dependents(DependentsArgs),
+    /// Compare the dependencies of an installed mod with those in the dependency graph
+    DepCheck(DepCheckArgs),
+    /// Export the dependency graph in the Graphviz DOT format
+    GraphExport(GraphExportArgs),
+    /// Check an everest.yaml file, or the one inside a mod archive, for mistakes
+    Validate(ValidateArgs),
+    /// Check a mod archive against the checksums in the registry, wherever it is stored
+    VerifyFile(VerifyFileArgs),
+    /// Manage the cache of remote files
+    Cache(CacheArgs),
+    /// Search the mod registry interactively and install the selected mods
+    #[cfg(feature = "browse")]
+    Browse,
+}
+
+impl Commands {
+    /// Returns the name of the subcommand as typed on the command line.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Commands::Install(_) => "install",
+            Commands::InstallPack(_) => "install-pack",
+            Commands::List(_) => "list",
+            Commands::Show(_) => "show",
+            Commands::Update(_) => "update",
+            Commands::Ping => "ping",
+            Commands::Mirrors => "mirrors",
+            Commands::Search(_) => "search",
+            Commands::RegistryShow(_) => "registry-show",
+            Commands::History(_) => "history",
+            Commands::Pin(_) => "pin",
+            Commands::Dependents(_) => "dependents",
+            Commands::DepCheck(_) => "dep-check",
+            Commands::GraphExport(_) => "graph-export",
+            Commands::Validate(_) => "validate",
+            Commands::VerifyFile(_) => "verify-file",
+            Commands::Cache(_) => "cache",
+            #[cfg(feature = "browse")]
+            Commands::Browse => "browse",
+        }
+    }
+}
+
+/// Returns a short overview of the commands, shown when no subcommand is given.
+pub fn overview() -> String {
+    let command = Cli::command();
+    let name = command.get_name().to_string();
+    let mut lines = vec![
+        format!(
+            "{name} {} - {}",
+            command.get_version().unwrap_or_default(),
+            command
+                .get_about()
+                .map(ToString::to_string)
+                .unwrap_or_default()
+        ),
+        String::new(),
+        format!("Usage: {name} [OPTIONS] <COMMAND>"),
+        String::new(),
+        "Commands:".to_string(),
+    ];
+    for subcommand in command.get_subcommands() {
+        lines.push(format!(
+            "  {:<10} {}",
+            subcommand.get_name(),
+            subcommand
+                .get_about()
+                .map(ToString::to_string)
+                .unwrap_or_default()
+        ));
+    }
+    lines.push(String::new());
+    lines.push(format!(
+        "Run '{name} --help' for all options, or '{name} <COMMAND> --help' for a command."
+    ));
+    lines.join("\n")
+}
+
+/// Prints the overview of the commands.
+pub fn print_overview() {
+    println!("{}", overview());
 }
 
 /// Arguments for the `install` subcommand
 #[derive(Debug, Args)]
 pub struct InstallArgs {
     /// The URL of the page where the mod is featured on the GameBanana
-    pub mod_page_url: String,
+    #[arg(
+        required_unless_present_any = ["from_list", "resume"],
+        conflicts_with = "from_list"
+    )]
+    pub mod_page_url: Option<String>,
+
+    /// Install every mod listed in the file, one page URL per line (`#` starts a comment)
+    #[arg(long, value_name = "FILE")]
+    pub from_list: Option<PathBuf>,
+
+    /// Print why each dependency is downloaded, as the path from the requested mod
+    #[arg(long, action)]
+    pub explain: bool,
+
+    /// Treat the given mods as installed, separated by commas (e.g., "FrostHelper,CommunalHelper")
+    #[arg(long, value_name = "NAMES", value_delimiter = ',')]
+    pub assume_installed: Vec<String>,
+
+    /// Install only the requested mods if the dependency graph can't be fetched, without asking
+    #[arg(long, action)]
+    pub no_deps_on_error: bool,
+
+    /// Print the mods that would be downloaded and their sizes, without downloading them
+    #[arg(long, action)]
+    pub dry_run: bool,
+
+    /// With --dry-run, also print the URLs each mod would be fetched from, in the order tried
+    #[arg(long, action, requires = "dry_run")]
+    pub show_mirror: bool,
+
+    /// Finish the interrupted batch of installs into the mods directory
+    #[arg(long, action, conflicts_with_all = ["mod_page_url", "from_list", "dry_run"])]
+    pub resume: bool,
+
+    /// Ask before downloading a mod requiring more than N dependencies
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = DEFAULT_CONFIRM_DEPS_OVER,
+        long_help = "Ask before downloading a mod requiring more than N dependencies that aren't installed yet,
+        showing them with their total size. Without a terminal, the install is cancelled unless --yes is given."
+    )]
+    pub confirm_deps_over: usize,
+
+    /// Install this version of the mod, downloading an older file from GameBanana if needed
+    #[arg(
+        id = "mod_version",
+        long = "version",
+        value_name = "VERSION",
+        requires = "mod_page_url",
+        conflicts_with_all = ["from_list", "resume"],
+        long_help = "Install this version of the mod, downloading an older file from GameBanana if needed.
+        The registry only has checksums for the latest version, so an older file is checked only by its
+        size and the version in its everest.yaml."
+    )]
+    pub mod_version: Option<String>,
+
+    /// What to do when dependencies require the same mod at conflicting versions
+    #[arg(
+        long,
+        value_name = "POLICY",
+        value_enum,
+        default_value_t = VersionConflictPolicy::Warn
+    )]
+    pub on_version_conflict: VersionConflictPolicy,
+
+    /// Names of the mods in the registry to install, as selected in `browse`
+    #[arg(skip)]
+    pub names: Vec<String>,
+}
+
+// Written by hand, so that the defaults match the ones given on the command line.
+impl Default for InstallArgs {
+    fn default() -> Self {
+        Self {
+            mod_page_url: None,
+            from_list: None,
+            explain: false,
+            assume_installed: Vec::new(),
+            no_deps_on_error: false,
+            dry_run: false,
+            show_mirror: false,
+            resume: false,
+            confirm_deps_over: DEFAULT_CONFIRM_DEPS_OVER,
+            mod_version: None,
+            on_version_conflict: VersionConflictPolicy::default(),
+            names: Vec::new(),
+        }
+    }
+}
+
+impl InstallArgs {
+    /// Returns true if an older file of a mod may be downloaded, as `install --version` does.
+    ///
+    /// A resumed install only finishes the saved batch, whose versions may come from a pack and
+    /// must not bypass the checksum verification.
+    pub fn installs_older_versions(&self) -> bool {
+        self.mod_version.is_some() && !self.resume
+    }
+}
+
+/// Arguments for the `install-pack` subcommand
+#[derive(Debug, Args)]
+pub struct InstallPackArgs {
+    /// The pack file listing the mods to install
+    pub path: PathBuf,
+
+    /// Print why each dependency is downloaded, as the path from the requested mod
+    #[arg(long, action)]
+    pub explain: bool,
+
+    /// Install only the requested mods if the dependency graph can't be fetched, without asking
+    #[arg(long, action)]
+    pub no_deps_on_error: bool,
+}
+
+/// Arguments for the `list` subcommand
+#[derive(Debug, Args)]
+pub struct ListArgs {
+    /// Print the whole list at once instead of paging it
+    #[arg(long, action)]
+    pub no_pager: bool,
+
+    /// Show only the mods no other installed mod depends on
+    #[arg(long, conflicts_with = "deps_only")]
+    pub top_level: bool,
+
+    /// Show only the mods other installed mods depend on
+    #[arg(long)]
+    pub deps_only: bool,
+
+    /// Show the mods with available updates first, then the most recently updated ones
+    #[arg(
+        long,
+        action,
+        long_help = "Show the mods with available updates first, then the most recently updated ones.
+        This fetches the mod registry, which `list` doesn't do otherwise. If it can't be fetched,
+        the mods are listed by name as usual."
+    )]
+    pub outdated_first: bool,
 }
 
 /// Arguments for the `show` subcommand
 #[derive(Debug, Args)]
 pub struct ShowArgs {
-    /// The name of the mod to show details for
+    /// The names of the mods to show details for, or glob patterns (e.g., "*Helper")
+    #[arg(required = true, value_name = "NAME")]
+    pub names: Vec<String>,
+
+    /// Show the dependency tree built from the manifests of the installed mods
+    #[arg(long, action)]
+    pub deps_tree: bool,
+
+    /// Show the checksum of the mod archive, also in the given algorithm if not xxh64
+    #[arg(
+        long,
+        value_name = "ALGO",
+        value_enum,
+        num_args = 0..=1,
+        default_missing_value = "xxh64"
+    )]
+    pub checksum: Option<ChecksumAlgorithm>,
+
+    /// Show the archive comment, where some tools record where the file came from
+    #[arg(long, action)]
+    pub provenance: bool,
+
+    /// Show the N-th of the mods listed for a name that partially matches several mods
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+    pub index: Option<u64>,
+}
+
+/// Arguments for the `search` subcommand
+#[derive(Debug, Args)]
+pub struct SearchArgs {
+    /// Part of the mod name to search for, case-insensitive
+    pub term: String,
+
+    /// Also show the number of dependencies of each mod (fetches the dependency graph)
+    #[arg(long, action)]
+    pub with_deps: bool,
+
+    /// Also show the downloads, likes and category of each mod from GameBanana
+    #[arg(long, action)]
+    pub enrich: bool,
+}
+
+/// Arguments for the `registry-show` subcommand
+#[derive(Debug, Args)]
+pub struct RegistryShowArgs {
+    /// The name of the mod in the registry, or the URL of its page on the GameBanana
+    pub mod_ref: String,
+}
+
+/// Arguments for the `history` subcommand
+#[derive(Debug, Args)]
+pub struct HistoryArgs {
+    /// Show only the given number of most recent entries
+    #[arg(long, value_name = "N")]
+    pub limit: Option<usize>,
+}
+
+/// Arguments for the `pin` subcommand
+#[derive(Debug, Args)]
+pub struct PinArgs {
+    /// Name of the mod to pin, or list the pinned mods if omitted
+    pub name: Option<String>,
+
+    /// Version to keep the mod at
+    #[arg(requires = "name")]
+    pub version: Option<String>,
+
+    /// Remove the pin of the mod
+    #[arg(long, requires = "name", conflicts_with = "version")]
+    pub remove: bool,
+}
+
+/// Arguments for the `dependents` subcommand
+#[derive(Debug, Args)]
+pub struct DependentsArgs {
+    /// The name of the mod
+    pub name: String,
+
+    /// Also list the mods requiring it through other mods
+    #[arg(long, action)]
+    pub transitive: bool,
+
+    /// List only the installed mods
+    #[arg(long, action)]
+    pub installed_only: bool,
+}
+
+/// Arguments for the `dep-check` subcommand
+#[derive(Debug, Args)]
+pub struct DepCheckArgs {
+    /// The name of the installed mod
     pub name: String,
 }
 
+/// Arguments for the `graph-export` subcommand
+#[derive(Debug, Args)]
+pub struct GraphExportArgs {
+    /// Export only the dependencies of this mod instead of the whole graph
+    pub name: Option<String>,
+
+    /// Write the graph to this file instead of the standard output
+    #[arg(short, long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+}
+
+/// Arguments for the `validate` subcommand
+#[derive(Debug, Args)]
+pub struct ValidateArgs {
+    /// Path to an everest.yaml file or a mod archive (.zip) containing one
+    pub path: PathBuf,
+}
+
+/// Arguments for the `verify-file` subcommand
+#[derive(Debug, Args)]
+pub struct VerifyFileArgs {
+    /// Path to the mod archive (.zip)
+    pub path: PathBuf,
+}
+
+/// Arguments for the `cache` subcommand
+#[derive(Debug, Args)]
+pub struct CacheArgs {
+    /// What to do with the cache
+    #[command(subcommand)]
+    pub action: CacheAction,
+}
+
+/// The actions of the `cache` subcommand
+#[derive(Debug, Subcommand)]
+pub enum CacheAction {
+    /// Delete every cached file
+    Clear,
+}
+
 /// Arguments for the `update` subcommand
 #[derive(Debug, Args)]
 pub struct UpdateArgs {
     /// Install available updates
     #[arg(long, action)]
     pub install: bool,
+
+    /// Append the installed updates to a changelog file [default: updates-<date>.txt in the state directory]
+    #[arg(long, value_name = "FILE", num_args = 0..=1, requires = "install")]
+    pub log_updates: Option<Option<PathBuf>>,
+
+    /// Install only the N most recently updated mods of the available updates
+    #[arg(long, value_name = "N", requires = "install")]
+    pub newest: Option<usize>,
+
+    /// Hash every mod, even the ones unchanged since the last check
+    #[arg(
+        long,
+        action,
+        long_help = "Hash every mod, even the ones unchanged since the last check.
+        Without this option, a mod found up to date before is not hashed again as long as neither its
+        registry entry nor its archive changed since."
+    )]
+    pub full: bool,
+
+    /// Check for updates against a saved copy of everest_update.yaml instead of the live registry
+    #[arg(
+        long,
+        value_name = "FILE",
+        long_help = "Check for updates against a saved copy of everest_update.yaml instead of the live registry.
+        Updates are still downloaded from the URLs of the snapshot, so it should be recent enough for
+        those to be served."
+    )]
+    pub registry_file: Option<PathBuf>,
+}
+
+/// What to do when a mod is required at minimum versions the registry can't all satisfy.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum VersionConflictPolicy {
+    /// Report the conflict and install anyway
+    #[default]
+    Warn,
+    /// Report the conflict and don't install the mod
+    Abort,
+}
+
+/// How the order of the mirrors is decided.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum MirrorStrategy {
+    /// The order of the mirror preferences
+    Priority,
+    /// The lowest latency first, probed once per run
+    Fastest,
+}
+
+/// Naming scheme of the log file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum LogNaming {
+    /// A single file, overwritten by each run
+    Single,
+    /// A file per run, named after the UTC start time
+    Timestamp,
+    /// A file per run, named after the process ID
+    Pid,
+}
+
+impl LogNaming {
+    /// Returns the name of the log file of a run started at `now` by the process `pid`.
+    pub fn file_name(self, now: UtcDateTime, pid: u32) -> String {
+        match self {
+            LogNaming::Single => format!("{LOG_FILE_STEM}.log"),
+            LogNaming::Timestamp => format!(
+                "{LOG_FILE_STEM}-{:04}{:02}{:02}T{:02}{:02}{:02}Z.log",
+                now.year, now.month, now.day, now.hour, now.minute, now.second
+            ),
+            LogNaming::Pid => format!("{LOG_FILE_STEM}-{pid}.log"),
+        }
+    }
+}
+
+/// Program used to transfer the mod files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Downloader {
+    /// Built-in single-connection downloader
+    Builtin,
+    /// External multi-connection downloader (aria2c)
+    External,
 }
 
 /// A valid prefix for the mod page URL
-const VALID_MOD_PAGE_URL_PREFIX: &str = "https://gamebanana.com/mods/";
+pub const VALID_MOD_PAGE_URL_PREFIX: &str = "https://gamebanana.com/mods/";
 
-/// An error can be occured when trying to extract an ID from an URL
+/// Host of the mod page URLs accepted by `install`
+const GAMEBANANA_HOST: &str = "gamebanana.com";
+
+/// Lowercases a host and strips its trailing dot and `www.` subdomain, so that every spelling
+/// of the GameBanana host compares equal to [`GAMEBANANA_HOST`].
+fn normalize_host(host: &str) -> String {
+    let host = host.to_ascii_lowercase();
+    let host = host.strip_suffix('.').unwrap_or(&host);
+    host.strip_prefix("www.").unwrap_or(host).to_string()
+}
+
+/// Hosts of common link shorteners, which are not followed
+const LINK_SHORTENER_HOSTS: [&str; 5] = ["bit.ly", "tinyurl.com", "t.co", "goo.gl", "is.gd"];
+
+/// An error can be occured when trying to parse a mod page URL
 #[derive(thiserror::Error, Debug, PartialEq, Eq)]
-pub enum IdExtractionError {
-    #[error("'{url}' does not have valid prefix (expected: '{VALID_MOD_PAGE_URL_PREFIX}')")]
-    InvalidPrefix { url: String },
+pub enum ModPageUrlError {
+    #[error("'{url}' is not a valid URL")]
+    NotAUrl { url: String },
+    #[error(
+        "'{url}' is not a GameBanana mod page; only gamebanana.com mod page URLs are accepted (e.g., '{VALID_MOD_PAGE_URL_PREFIX}123456'){}",
+        .hint.as_ref().map(|hint| format!("\n  hint: {hint}")).unwrap_or_default()
+    )]
+    NotAModPage { url: String, hint: Option<String> },
     #[error("no valid ID segment in given URL")]
     NoIdSegment,
+    #[error("'{id}' is not a valid mod ID: {source}")]
+    InvalidId { id: String, source: ParseIntError },
 }
 
-/// Extracts an ID segment from given URL string.
-pub fn extract_id(url: &str) -> Result<&str, IdExtractionError> {
-    let id_str = url.strip_prefix(VALID_MOD_PAGE_URL_PREFIX);
-    match id_str {
-        Some(id) if !id.is_empty() => Ok(id),
-        Some(_) => Err(IdExtractionError::NoIdSegment),
-        None => Err(IdExtractionError::InvalidPrefix {
+/// Parses a GameBanana mod page URL into the mod ID.
+///
+/// Trailing path segments, query parameters and fragments after the ID are ignored.
+///
+/// The host is case-insensitive and may have a trailing dot or the `www.` subdomain.
+///
+/// # Errors
+/// Returns an error if the URL is not a `https://gamebanana.com/mods/<ID>` URL. URLs from
+/// mirrors, file downloads and link shorteners get a hint on what to pass instead.
+pub fn parse_mod_page_url(url: &str) -> Result<u32, ModPageUrlError> {
+    let parsed = reqwest::Url::parse(url).map_err(|_| ModPageUrlError::NotAUrl {
+        url: url.to_string(),
+    })?;
+    if parsed.scheme() != "https"
+        || parsed.host_str().map(normalize_host).as_deref() != Some(GAMEBANANA_HOST)
+        || !parsed.path().starts_with("/mods/")
+    {
+        return Err(ModPageUrlError::NotAModPage {
             url: url.to_string(),
-        }),
+            hint: suggest_mod_page_url(&parsed),
+        });
     }
-}
 
-/// Parses given string into an integer.
-pub fn parse_id(id_str: &str) -> Result<u32, ParseIntError> {
-    id_str
+    let id_segment = parsed.path()["/mods/".len()..]
+        .split('/')
+        .next()
+        .unwrap_or_default();
+    if id_segment.is_empty() {
+        return Err(ModPageUrlError::NoIdSegment);
+    }
+    id_segment
         .parse::<u32>()
         .inspect(|id| tracing::info!("parsed id: {}", id))
-        .inspect_err(|err| tracing::error!("failed to parse '{}' cause: {}", id_str, err))
+        .map_err(|source| ModPageUrlError::InvalidId {
+            id: id_segment.to_string(),
+            source,
+        })
+}
+
+/// Returns a hint for a URL which looks like it was meant to point to a mod page.
+fn suggest_mod_page_url(url: &reqwest::Url) -> Option<String> {
+    let host = normalize_host(url.host_str()?);
+    let is_gamebanana = host == GAMEBANANA_HOST;
+    let is_file_host = host == "files.gamebanana.com";
+
+    if is_gamebanana && url.path().starts_with("/mods/") {
+        let canonical = format!(
+            "https://{GAMEBANANA_HOST}{}{}",
+            url.path(),
+            url.query()
+                .map(|query| format!("?{query}"))
+                .unwrap_or_default()
+        );
+        Some(format!("did you mean '{canonical}'?"))
+    } else if is_file_host
+        || is_gamebanana && (url.path().starts_with("/mmdl/") || url.path().starts_with("/dl/"))
+    {
+        Some("this is a file download link, pass the mod page it belongs to instead".to_string())
+    } else if mirror_list::MIRRORS.iter().any(|mirror| {
+        reqwest::Url::parse(mirror.base_url)
+            .ok()
+            .and_then(|base| base.host_str().map(|mirror_host| mirror_host == host))
+            .unwrap_or(false)
+    }) {
+        Some(
+            "this is a download mirror, which is chosen automatically (see --mirror-priority); pass the mod page on gamebanana.com instead"
+                .to_string(),
+        )
+    } else if LINK_SHORTENER_HOSTS.contains(&host.as_str()) {
+        Some(
+            "shortened links are not followed, open it in a browser and pass the gamebanana.com mod page it redirects to"
+                .to_string(),
+        )
+    } else {
+        None
+    }
+}
+
+/// Parses the contents of a `--from-list` file into mod page URLs.
+///
+/// Blank lines, lines starting with `#` and trailing ` # comments` are ignored.
+pub fn parse_url_list(contents: &str) -> Vec<String> {
+    contents
+        .lines()
+        .map(|line| line.split_once(" #").map_or(line, |(url, _)| url).trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(String::from)
+        .collect()
 }
 
 #[cfg(test)]
-mod tests_id_extraction {
+mod tests_mod_page_url {
     use super::*;
 
-    #[test]
-    fn test_extract_id_valid_numeric() {
-        let url = "https://gamebanana.com/mods/123456";
-        assert_eq!(extract_id(url).unwrap(), "123456");
+    fn hint_of(url: &str) -> Option<String> {
+        match parse_mod_page_url(url) {
+            Err(ModPageUrlError::NotAModPage { hint, .. }) => hint,
+            other => panic!("expected NotAModPage for '{url}', got {other:?}"),
+        }
     }
 
     #[test]
-    fn test_extract_id_valid_with_trailing_path() {
-        let url = "https://gamebanana.com/mods/123456/download";
-        assert_eq!(extract_id(url).unwrap(), "123456/download");
+    fn test_parse_mod_page_url_valid() {
+        assert_eq!(
+            parse_mod_page_url("https://gamebanana.com/mods/123456"),
+            Ok(123456)
+        );
+        assert_eq!(
+            parse_mod_page_url("https://gamebanana.com/mods/123456/download?tab=files#top"),
+            Ok(123456)
+        );
     }
 
     #[test]
-    fn test_extract_id_valid_with_query_params() {
-        let url = "https://gamebanana.com/mods/123456?tab=comments";
-        assert_eq!(extract_id(url).unwrap(), "123456?tab=comments");
+    fn test_parse_mod_page_url_ignores_query_and_fragment() {
+        for url in [
+            "https://gamebanana.com/mods/123456?tab=comments",
+            "https://gamebanana.com/mods/123456#description",
+            "https://www.gamebanana.com/mods/123456",
+        ] {
+            assert_eq!(parse_mod_page_url(url), Ok(123456), "{url}");
+        }
+        for url in [
+            "http://gamebanana.com/mods/123456",
+            "https://gamebanana.com/mods123456",
+            "https://gamebanana.com/mmdl/123456",
+        ] {
+            assert!(
+                matches!(
+                    parse_mod_page_url(url),
+                    Err(ModPageUrlError::NotAModPage { .. })
+                ),
+                "{url}"
+            );
+        }
     }
 
     #[test]
-    fn test_extract_id_valid_alphanumeric() {
-        let url = "https://gamebanana.com/mods/abc123def";
-        assert_eq!(extract_id(url).unwrap(), "abc123def");
+    fn test_parse_mod_page_url_invalid_id() {
+        assert!(matches!(
+            parse_mod_page_url("https://gamebanana.com/mods/abc"),
+            Err(ModPageUrlError::InvalidId { .. })
+        ));
+        assert_eq!(
+            parse_mod_page_url("https://gamebanana.com/mods/"),
+            Err(ModPageUrlError::NoIdSegment)
+        );
+        assert!(matches!(
+            parse_mod_page_url("gamebanana.com/mods/123456"),
+            Err(ModPageUrlError::NotAUrl { .. })
+        ));
     }
 
     #[test]
-    fn test_extract_id_empty_id_segment() {
-        let url = "https://gamebanana.com/mods/";
-        let result = extract_id(url);
-        assert_eq!(result, Err(IdExtractionError::NoIdSegment));
+    fn test_parse_mod_page_url_normalizes_host() {
+        for url in [
+            "https://GameBanana.COM/mods/123456",
+            "https://gamebanana.com./mods/123456",
+            "https://www.gamebanana.com/mods/123456",
+            "HTTPS://WWW.GAMEBANANA.COM./mods/123456?tab=files",
+        ] {
+            assert_eq!(parse_mod_page_url(url), Ok(123456), "{url}");
+        }
     }
 
     #[test]
-    fn test_extract_id_invalid_prefix_different_domain() {
-        let url = "https://example.com/mods/123456";
-        let result = extract_id(url);
+    fn test_parse_mod_page_url_suggests_canonical_url() {
         assert_eq!(
-            result,
-            Err(IdExtractionError::InvalidPrefix {
-                url: url.to_string()
-            })
+            hint_of("http://gamebanana.com/mods/123456").as_deref(),
+            Some("did you mean 'https://gamebanana.com/mods/123456'?")
         );
     }
 
     #[test]
-    fn test_extract_id_invalid_prefix_different_path() {
-        let url = "https://gamebanana.com/mmdl/123456";
-        let result = extract_id(url);
-        assert_eq!(
-            result,
-            Err(IdExtractionError::InvalidPrefix {
-                url: url.to_string()
-            })
+    fn test_parse_mod_page_url_rejects_other_hosts() {
+        for url in [
+            "https://gamebanana.com/mmdl/123456",
+            "https://files.gamebanana.com/mods/foo.zip",
+        ] {
+            assert!(
+                hint_of(url).unwrap().contains("file download link"),
+                "{url}"
+            );
+        }
+        for url in [
+            "https://celeste.weg.fan/api/v2/download/gamebanana-files/123456",
+            "https://banana-mirror-mods.celestemods.com/123456.zip",
+        ] {
+            assert!(hint_of(url).unwrap().contains("download mirror"), "{url}");
+        }
+        assert!(
+            hint_of("https://bit.ly/abcdef")
+                .unwrap()
+                .contains("shortened")
         );
+        assert_eq!(hint_of("https://example.com/mods/123456"), None);
     }
+}
+
+#[cfg(test)]
+mod tests_cli_args {
+    use super::*;
 
     #[test]
-    fn test_extract_id_invalid_prefix_missing_trailing_slash() {
-        let url = "https://gamebanana.com/mods123456";
-        let result = extract_id(url);
-        assert_eq!(
-            result,
-            Err(IdExtractionError::InvalidPrefix {
-                url: url.to_string()
-            })
+    fn test_install_confirm_deps_over() {
+        let url = "https://gamebanana.com/mods/123456";
+        let cli = Cli::try_parse_from(["everest-mod-cli", "install", url]).unwrap();
+        let Some(Commands::Install(args)) = cli.command else {
+            panic!("expected the install command");
+        };
+        assert_eq!(args.confirm_deps_over, DEFAULT_CONFIRM_DEPS_OVER);
+        assert!(!cli.yes);
+
+        let cli = Cli::try_parse_from([
+            "everest-mod-cli",
+            "-y",
+            "install",
+            url,
+            "--confirm-deps-over",
+            "0",
+        ])
+        .unwrap();
+        let Some(Commands::Install(args)) = cli.command else {
+            panic!("expected the install command");
+        };
+        assert_eq!(args.confirm_deps_over, 0);
+        assert!(cli.yes);
+    }
+
+    #[test]
+    fn test_install_args_default_matches_command_line() {
+        // `browse` builds the arguments with `Default`, so they must match an empty command line.
+        let cli = Cli::try_parse_from(["everest-mod-cli", "install", "--resume"]).unwrap();
+        let Some(Commands::Install(parsed)) = cli.command else {
+            panic!("expected the install command");
+        };
+        let default = InstallArgs::default();
+        assert_eq!(default.confirm_deps_over, parsed.confirm_deps_over);
+        assert_eq!(default.on_version_conflict, parsed.on_version_conflict);
+        assert_eq!(default.confirm_deps_over, DEFAULT_CONFIRM_DEPS_OVER);
+    }
+
+    #[test]
+    fn test_install_show_mirror_requires_dry_run() {
+        let url = "https://gamebanana.com/mods/123456";
+        assert!(Cli::try_parse_from(["everest-mod-cli", "install", url, "--show-mirror"]).is_err());
+
+        let cli = Cli::try_parse_from([
+            "everest-mod-cli",
+            "install",
+            url,
+            "--dry-run",
+            "--show-mirror",
+        ])
+        .unwrap();
+        let Commands::Install(args) = cli.command.unwrap() else {
+            panic!("expected the install command");
+        };
+        assert!(args.dry_run && args.show_mirror);
+    }
+
+    #[test]
+    fn test_install_version() {
+        let url = "https://gamebanana.com/mods/123456";
+        let cli = Cli::try_parse_from(["everest-mod-cli", "install", url, "--version", "3.24.0"])
+            .unwrap();
+        let Some(Commands::Install(args)) = cli.command else {
+            panic!("expected the install command");
+        };
+        assert_eq!(args.mod_version.as_deref(), Some("3.24.0"));
+
+        // The version of the application is still shown.
+        let err = Cli::try_parse_from(["everest-mod-cli", "--version"]).unwrap_err();
+        assert_eq!(err.kind(), clap::error::ErrorKind::DisplayVersion);
+        assert!(
+            Cli::try_parse_from([
+                "everest-mod-cli",
+                "install",
+                "--from-list",
+                "mods.txt",
+                "--version",
+                "1.0.0"
+            ])
+            .is_err()
         );
     }
 
     #[test]
-    fn test_extract_id_invalid_prefix_http_instead_of_https() {
-        let url = "http://gamebanana.com/mods/123456";
-        let result = extract_id(url);
-        assert_eq!(
-            result,
-            Err(IdExtractionError::InvalidPrefix {
-                url: url.to_string()
-            })
+    fn test_installs_older_versions() {
+        let installs_older_versions = |args: &[&str]| {
+            let cli = Cli::try_parse_from(args).unwrap();
+            let Some(Commands::Install(args)) = cli.command else {
+                panic!("expected the install command");
+            };
+            args.installs_older_versions()
+        };
+        let url = "https://gamebanana.com/mods/123456";
+        assert!(installs_older_versions(&[
+            "everest-mod-cli",
+            "install",
+            url,
+            "--version",
+            "3.24.0"
+        ]));
+        assert!(!installs_older_versions(&[
+            "everest-mod-cli",
+            "install",
+            url
+        ]));
+
+        // The saved batch of a pack keeps its versions, but they aren't installed as older files.
+        assert!(!installs_older_versions(&[
+            "everest-mod-cli",
+            "install",
+            "--resume"
+        ]));
+    }
+
+    #[test]
+    fn test_install_assume_installed_comma_separated() {
+        let cli = Cli::try_parse_from([
+            "everest-mod-cli",
+            "install",
+            "https://gamebanana.com/mods/123456",
+            "--assume-installed",
+            "FrostHelper,CommunalHelper",
+        ])
+        .unwrap();
+        let Commands::Install(args) = cli.command.unwrap() else {
+            panic!("expected the install command");
+        };
+        assert_eq!(args.assume_installed, ["FrostHelper", "CommunalHelper"]);
+    }
+
+    #[test]
+    fn test_downloader_defaults_to_builtin() {
+        let cli = Cli::try_parse_from(["everest-mod-cli", "list"]).unwrap();
+        assert_eq!(cli.downloader, Downloader::Builtin);
+
+        let cli =
+            Cli::try_parse_from(["everest-mod-cli", "--downloader", "external", "list"]).unwrap();
+        assert_eq!(cli.downloader, Downloader::External);
+        assert!(Cli::try_parse_from(["everest-mod-cli", "--downloader", "curl", "list"]).is_err());
+    }
+
+    #[test]
+    fn test_verify_signature_requires_trusted_key() {
+        assert!(Cli::try_parse_from(["everest-mod-cli", "--verify-signature", "update"]).is_err());
+        assert!(
+            Cli::try_parse_from(["everest-mod-cli", "--trusted-key", "key.asc", "update"]).is_err()
         );
+        let cli = Cli::try_parse_from([
+            "everest-mod-cli",
+            "--verify-signature",
+            "--trusted-key",
+            "key.asc",
+            "update",
+        ])
+        .unwrap();
+        assert_eq!(cli.trusted_key, Some(PathBuf::from("key.asc")));
     }
 
     #[test]
-    fn test_extract_id_invalid_prefix_with_subdomain() {
-        let url = "https://www.gamebanana.com/mods/123456";
-        let result = extract_id(url);
+    fn test_no_verify_conflicts_with_verify_after() {
+        let cli = Cli::try_parse_from(["everest-mod-cli", "--no-verify", "update"]).unwrap();
+        assert!(cli.no_verify);
+        assert!(
+            Cli::try_parse_from(["everest-mod-cli", "--no-verify", "--verify-after", "update"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_pin_args() {
+        let cli =
+            Cli::try_parse_from(["everest-mod-cli", "pin", "SpeedrunTool", "3.24.0"]).unwrap();
+        let Commands::Pin(args) = cli.command.unwrap() else {
+            panic!("expected the pin command");
+        };
+        assert_eq!(args.name.as_deref(), Some("SpeedrunTool"));
+        assert_eq!(args.version.as_deref(), Some("3.24.0"));
+
+        assert!(Cli::try_parse_from(["everest-mod-cli", "pin"]).is_ok());
+        assert!(Cli::try_parse_from(["everest-mod-cli", "pin", "--remove"]).is_err());
+        assert!(Cli::try_parse_from(["everest-mod-cli", "pin", "A", "1.0.0", "--remove"]).is_err());
+    }
+
+    #[test]
+    fn test_log_file_name() {
+        // 2025-01-31T12:34:56Z
+        let now = UtcDateTime::from_unix_secs(1_738_326_896);
         assert_eq!(
-            result,
-            Err(IdExtractionError::InvalidPrefix {
-                url: url.to_string()
-            })
+            LogNaming::Single.file_name(now, 4242),
+            "everest-mod-cli.log"
         );
+        assert_eq!(
+            LogNaming::Timestamp.file_name(now, 4242),
+            "everest-mod-cli-20250131T123456Z.log"
+        );
+        assert_eq!(
+            LogNaming::Pid.file_name(now, 4242),
+            "everest-mod-cli-4242.log"
+        );
+
+        let cli =
+            Cli::try_parse_from(["everest-mod-cli", "--log-file", "run.log", "list"]).unwrap();
+        assert_eq!(cli.log_file, Some(PathBuf::from("run.log")));
+        assert!(
+            Cli::try_parse_from([
+                "everest-mod-cli",
+                "--log-file",
+                "run.log",
+                "--log-naming",
+                "pid",
+                "list"
+            ])
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_verbose_counts_occurrences() {
+        let cli = Cli::try_parse_from(["everest-mod-cli", "list"]).unwrap();
+        assert_eq!(cli.verbose, 0);
+        let cli = Cli::try_parse_from(["everest-mod-cli", "-vv", "list"]).unwrap();
+        assert_eq!(cli.verbose, 2);
+        let cli =
+            Cli::try_parse_from(["everest-mod-cli", "-v", "--verbose", "-v", "list"]).unwrap();
+        assert_eq!(cli.verbose, 3);
+    }
+
+    #[test]
+    fn test_no_subcommand_shows_overview() {
+        let cli = Cli::try_parse_from(["everest-mod-cli"]).unwrap();
+        assert!(cli.command.is_none());
+
+        let overview = overview();
+        for name in ["install", "list", "show", "update", "ping"] {
+            assert!(overview.contains(&format!("  {name} ")), "missing '{name}'");
+        }
+        assert!(overview.contains("--help"));
     }
 
     #[test]
-    fn test_extract_id_empty_string() {
-        let url = "";
-        let result = extract_id(url);
+    fn test_install_requires_url_or_list() {
+        assert!(Cli::try_parse_from(["everest-mod-cli", "install"]).is_err());
+        assert!(
+            Cli::try_parse_from([
+                "everest-mod-cli",
+                "install",
+                "https://gamebanana.com/mods/123456",
+                "--from-list",
+                "mods.txt",
+            ])
+            .is_err()
+        );
+
+        let cli =
+            Cli::try_parse_from(["everest-mod-cli", "install", "--from-list", "mods.txt"]).unwrap();
+        let Commands::Install(args) = cli.command.unwrap() else {
+            panic!("expected the install command");
+        };
+        assert_eq!(args.mod_page_url, None);
+        assert_eq!(args.from_list, Some(PathBuf::from("mods.txt")));
+    }
+
+    #[test]
+    fn test_parse_url_list_skips_comments_and_blank_lines() {
+        let contents = "# Helpers\nhttps://gamebanana.com/mods/1\n\n  https://gamebanana.com/mods/2  # collab\n";
         assert_eq!(
-            result,
-            Err(IdExtractionError::InvalidPrefix {
-                url: url.to_string()
-            })
+            parse_url_list(contents),
+            [
+                "https://gamebanana.com/mods/1",
+                "https://gamebanana.com/mods/2"
+            ]
         );
     }
 
     #[test]
-    fn test_extract_id_valid_with_fragment() {
-        let url = "https://gamebanana.com/mods/123456#description";
-        assert_eq!(extract_id(url).unwrap(), "123456#description");
+    fn test_update_log_updates_optional_path() {
+        let cli = Cli::try_parse_from(["everest-mod-cli", "update", "--install", "--log-updates"])
+            .unwrap();
+        let Commands::Update(args) = cli.command.unwrap() else {
+            panic!("expected the update command");
+        };
+        assert_eq!(args.log_updates, Some(None));
+
+        let cli = Cli::try_parse_from([
+            "everest-mod-cli",
+            "update",
+            "--install",
+            "--log-updates",
+            "changes.txt",
+        ])
+        .unwrap();
+        let Commands::Update(args) = cli.command.unwrap() else {
+            panic!("expected the update command");
+        };
+        assert_eq!(args.log_updates, Some(Some(PathBuf::from("changes.txt"))));
     }
 }
@@ -5,14 +5,22 @@ use std::{
     io::{BufRead, BufReader},
     path::{Path, PathBuf},
     sync::Arc,
+    time::Duration,
 };
 
 use anyhow::{Context, Result};
 
 use crate::{
-    cli::Cli,
-    constant::{STEAM_MODS_DIRECTORY_PATH, UPDATER_BLACKLIST_FILE},
+    cache,
+    cli::{Cli, Downloader},
+    constant::{
+        DEDUP_STORE_DIRECTORY_NAME, DEFAULT_JOBS, DEFAULT_MIRROR_PRIORITY, MAX_SCAN_DEPTH,
+        PARTIAL_FILE_SUFFIXES, SIGNATURE_PROGRAM, STATE_DIRECTORY_PATH, STEAM_MODS_DIRECTORY_PATH,
+        UPDATER_BLACKLIST_FILE, UPDATER_WHITELIST_FILE,
+    },
+    download::{self, VerificationPolicy},
     fileutil,
+    profile::{self, Profile},
 };
 
 /// Config to manage mods.
@@ -22,6 +30,38 @@ pub struct Config {
     directory: PathBuf,
     /// List of mirror names, separated by commas (e.g., "gb,wegfan,jade,otobot")
     mirror_preferences: String,
+    /// Maximum number of mods downloaded at once.
+    jobs: usize,
+    /// Maximum number of connections opened to a single mirror at once.
+    max_connections_per_host: usize,
+    /// Program used to transfer the mod files.
+    downloader: Downloader,
+    /// Maximum number of idle connections kept alive per host.
+    pool_max_idle_per_host: usize,
+    /// How long an idle connection is kept alive for reuse.
+    pool_idle_timeout: Duration,
+    /// How long a download may receive no data before it is abandoned.
+    stall_timeout: Duration,
+    /// Gitignore-style patterns of files excluded from the installed mod scan.
+    ignore_patterns: Vec<String>,
+    /// Whether subdirectories of the mods directory are scanned too.
+    recursive: bool,
+    /// Whether unexpected files found by the installed mod scan are reported.
+    warn_unknown: bool,
+    /// How downloaded files that don't match the registry checksums are handled.
+    verification_policy: VerificationPolicy,
+    /// The content-addressed store the downloads are linked from, with `--dedup-store`.
+    dedup_store: Option<PathBuf>,
+    /// Number of consecutive failed downloads after which a batch is aborted.
+    fail_fast_threshold: Option<usize>,
+    /// Number of concurrent byte ranges a large file is downloaded in.
+    segments: usize,
+    /// Proxy every request is sent through, from the profile.
+    proxy: Option<String>,
+    /// Whether mods may be downloaded from URLs that aren't https.
+    allow_insecure_download: bool,
+    /// The public key the signatures are verified against, with `--verify-signature`.
+    trusted_key: Option<PathBuf>,
 }
 
 impl Config {
@@ -33,18 +73,80 @@ impl Config {
     ///
     /// If the user's home directory could not be determined, an error is returned.
     pub fn new(cli: &Cli) -> Result<Arc<Self>> {
+        let profile = match &cli.profile {
+            Some(name) => {
+                let path = profile::config_path()
+                    .context("could not determine the location of the config file for --profile")?;
+                profile::load(&path, name)?
+            }
+            None => Profile::default(),
+        };
+        if let Some(proxy) = &profile.proxy {
+            reqwest::Proxy::all(proxy).with_context(|| format!("invalid proxy '{proxy}'"))?;
+        }
+
+        let trusted_key = cli.trusted_key.clone().filter(|_| cli.verify_signature);
+        if let Some(trusted_key) = &trusted_key {
+            if !trusted_key.is_file() {
+                anyhow::bail!(
+                    "the trusted key '{}' is not found",
+                    fileutil::replace_home_dir_with_tilde(trusted_key)
+                );
+            }
+            if download::signature_program().is_none() {
+                anyhow::bail!(
+                    "--verify-signature requires {SIGNATURE_PROGRAM}, which is not found on PATH"
+                );
+            }
+        }
+
         let directory = cli
             .mods_directory
             .clone()
+            .or(profile.mods_dir)
             .or_else(get_default_mods_directory)
             .context(
                 "could not determine home directory location!\
                 please specify the mods directory using --mods-dir",
             )?;
 
+        let dedup_store = if cli.dedup_store {
+            let cache_directory = cache::cache_directory(cli.cache_dir.as_deref()).context(
+                "could not determine the cache directory for --dedup-store, \
+                please specify it using --cache-dir",
+            )?;
+            Some(cache_directory.join(DEDUP_STORE_DIRECTORY_NAME))
+        } else {
+            None
+        };
+
         Ok(Arc::new(Self {
             directory,
-            mirror_preferences: cli.mirror_preferences.to_string(),
+            mirror_preferences: cli
+                .mirror_preferences
+                .clone()
+                .or(profile.mirror_priority)
+                .unwrap_or_else(|| DEFAULT_MIRROR_PRIORITY.to_string()),
+            jobs: cli.jobs.or(profile.jobs).unwrap_or(DEFAULT_JOBS),
+            max_connections_per_host: cli.max_connections_per_host,
+            downloader: cli.downloader,
+            pool_max_idle_per_host: cli.pool_max_idle_per_host,
+            pool_idle_timeout: Duration::from_secs(cli.pool_idle_timeout),
+            stall_timeout: Duration::from_secs(cli.stall_timeout),
+            ignore_patterns: cli.ignore_patterns.clone(),
+            recursive: cli.recursive,
+            warn_unknown: cli.warn_unknown,
+            verification_policy: if cli.no_verify {
+                VerificationPolicy::WarnOnly
+            } else {
+                VerificationPolicy::Enforce
+            },
+            dedup_store,
+            fail_fast_threshold: cli.fail_fast_threshold,
+            segments: cli.segments,
+            proxy: profile.proxy,
+            allow_insecure_download: cli.allow_insecure_download,
+            trusted_key,
         }))
     }
 
@@ -58,8 +160,91 @@ impl Config {
         &self.mirror_preferences
     }
 
+    /// Returns a copy of this config with the given priority of download mirrors.
+    pub fn with_mirror_preferences(&self, mirror_preferences: String) -> Arc<Self> {
+        Arc::new(Self {
+            mirror_preferences,
+            ..self.clone()
+        })
+    }
+
+    /// Maximum number of mods downloaded at once
+    pub fn jobs(&self) -> usize {
+        self.jobs
+    }
+
+    /// Maximum number of connections opened to a single mirror at once
+    pub fn max_connections_per_host(&self) -> usize {
+        self.max_connections_per_host
+    }
+
+    /// How downloaded files that don't match the registry checksums are handled
+    pub fn verification_policy(&self) -> VerificationPolicy {
+        self.verification_policy
+    }
+
+    /// The content-addressed store the downloads are linked from, if `--dedup-store` is given
+    pub fn dedup_store(&self) -> Option<&Path> {
+        self.dedup_store.as_deref()
+    }
+
+    /// Number of consecutive failed downloads after which a batch is aborted, if any
+    pub fn fail_fast_threshold(&self) -> Option<usize> {
+        self.fail_fast_threshold
+    }
+
+    /// Number of concurrent byte ranges a large file is downloaded in, 1 for a single stream
+    pub fn segments(&self) -> usize {
+        self.segments
+    }
+
+    /// Proxy every request is sent through, if the profile sets one
+    pub fn proxy(&self) -> Option<&str> {
+        self.proxy.as_deref()
+    }
+
+    /// Whether mods may be downloaded from URLs that aren't https
+    pub fn allow_insecure_download(&self) -> bool {
+        self.allow_insecure_download
+    }
+
+    /// The public key the signatures are verified against, if `--verify-signature` is given
+    pub fn trusted_key(&self) -> Option<&Path> {
+        self.trusted_key.as_deref()
+    }
+
+    /// Program used to transfer the mod files
+    pub fn downloader(&self) -> Downloader {
+        self.downloader
+    }
+
+    /// Maximum number of idle connections kept alive per host
+    pub fn pool_max_idle_per_host(&self) -> usize {
+        self.pool_max_idle_per_host
+    }
+
+    /// Idle timeout of pooled connections
+    pub fn pool_idle_timeout(&self) -> Duration {
+        self.pool_idle_timeout
+    }
+
+    /// How long a download may receive no data before it is abandoned
+    pub fn stall_timeout(&self) -> Duration {
+        self.stall_timeout
+    }
+
     /// Scans the mods directory and returns a list of all installed mod archive files.
     ///
+    /// Files matching any of the ignore patterns are left out, so they are neither parsed nor
+    /// reported as broken mods.
+    ///
+    /// Symlinked archives are resolved and included under the path of the link. Broken symlinks
+    /// and symlink cycles are skipped with a warning, and an archive reachable through several
+    /// links is only returned once.
+    ///
+    /// With `--recursive`, subdirectories are scanned too, up to [`MAX_SCAN_DEPTH`] levels deep.
+    /// Symlinked directories are followed, but each directory is only scanned once.
+    ///
     /// # Errors
     ///
     /// If the mods directory does not exist or cannot be read, an error is returned.
@@ -71,24 +256,135 @@ impl Config {
 
         tracing::debug!("Scanning the installed mod archives in {}", debug_filename);
 
-        let directory_entries = fs::read_dir(&self.directory)
-            .map_err(|e| anyhow::anyhow!("Failed to read mods directory: {}", e))?;
-        let mod_archives = directory_entries
-            .flatten() // eliminates unreadable directory entries
-            .map(|entry| entry.path())
-            .filter(|path| {
-                path.is_file()
-                    && path
-                        .extension()
-                        .is_some_and(|ext| ext.eq_ignore_ascii_case("zip"))
-            })
-            .collect::<Vec<PathBuf>>();
+        let mut visited: HashSet<PathBuf> = HashSet::new();
+        let mut visited_directories: HashSet<PathBuf> = HashSet::new();
+        if let Ok(root) = fs::canonicalize(&self.directory) {
+            visited_directories.insert(root);
+        }
+        let mut directories = vec![(self.directory.clone(), 0)];
+        let mut mod_archives = Vec::new();
+        while let Some((directory, depth)) = directories.pop() {
+            let directory_entries = match fs::read_dir(&directory) {
+                Ok(entries) => entries,
+                Err(e) if depth == 0 => anyhow::bail!("Failed to read mods directory: {}", e),
+                Err(e) => {
+                    tracing::warn!(
+                        "Skipping '{}': failed to read the directory: {}",
+                        fileutil::replace_home_dir_with_tilde(&directory),
+                        e
+                    );
+                    continue;
+                }
+            };
+
+            for path in directory_entries
+                .flatten() // eliminates unreadable directory entries
+                .map(|entry| entry.path())
+            {
+                let kind = ScannedFile::classify(&path);
+                if kind == ScannedFile::Hidden {
+                    tracing::debug!("Skipping hidden '{}'", path.display());
+                    continue;
+                }
+
+                if self.recursive && path.is_dir() {
+                    if let Ok(relative_path) = path.strip_prefix(&self.directory)
+                        && fileutil::is_ignored(relative_path, &self.ignore_patterns)
+                    {
+                        tracing::debug!("Ignoring '{}'", relative_path.display());
+                        continue;
+                    }
+                    if depth + 1 > MAX_SCAN_DEPTH {
+                        tracing::warn!(
+                            "Skipping '{}': nested deeper than {} directories",
+                            fileutil::replace_home_dir_with_tilde(&path),
+                            MAX_SCAN_DEPTH
+                        );
+                        continue;
+                    }
+                    // NOTE: Symlinked directories are resolved, so a cycle is entered only once.
+                    let resolved = fs::canonicalize(&path).unwrap_or_else(|_| path.clone());
+                    if visited_directories.insert(resolved) {
+                        directories.push((path, depth + 1));
+                    }
+                    continue;
+                }
+
+                match kind {
+                    ScannedFile::Archive => {}
+                    ScannedFile::Unknown if self.warn_unknown && path.is_file() => {
+                        println!(
+                            "⚠️  Unexpected file in the mods directory: {}",
+                            fileutil::replace_home_dir_with_tilde(&path)
+                        );
+                        continue;
+                    }
+                    _ => continue,
+                }
+
+                if let Ok(relative_path) = path.strip_prefix(&self.directory)
+                    && fileutil::is_ignored(relative_path, &self.ignore_patterns)
+                {
+                    tracing::debug!("Ignoring '{}'", relative_path.display());
+                    continue;
+                }
+
+                // NOTE: `fs::metadata` follows symlinks, so it fails on broken links and on cycles.
+                let metadata = match fs::metadata(&path) {
+                    Ok(metadata) => metadata,
+                    Err(e) => {
+                        tracing::warn!(
+                            "Skipping '{}': broken symlink or unreadable file: {}",
+                            fileutil::replace_home_dir_with_tilde(&path),
+                            e
+                        );
+                        continue;
+                    }
+                };
+                if !metadata.is_file() {
+                    continue;
+                }
+
+                let resolved = fs::canonicalize(&path).unwrap_or_else(|_| path.clone());
+                if !visited.insert(resolved) {
+                    tracing::debug!(
+                        "Skipping '{}': the archive is already linked by another entry",
+                        fileutil::replace_home_dir_with_tilde(&path)
+                    );
+                    continue;
+                }
+
+                mod_archives.push(path);
+            }
+        }
 
         tracing::info!("Found local mod files: {}", mod_archives.len());
 
         Ok(mod_archives)
     }
 
+    /// Checks that new mods can be written to the mods directory.
+    ///
+    /// Creates and removes a temporary file, so that a permission problem is reported upfront
+    /// instead of in the middle of the downloads.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the temporary file cannot be created in the mods directory.
+    pub fn ensure_directory_writable(&self) -> Result<()> {
+        tempfile::Builder::new()
+            .prefix(".everest-mod-cli-")
+            .tempfile_in(&self.directory)
+            .map(drop)
+            .inspect_err(|e| tracing::error!("Write permission check failed: {}", e))
+            .with_context(|| {
+                format!(
+                    "mods directory '{}' is not writable; try running with appropriate permissions or choose another directory with --mods-dir",
+                    fileutil::replace_home_dir_with_tilde(&self.directory)
+                )
+            })
+    }
+
     /// Returns a set of file paths if any are found in the `updaterblacklist.txt`.
     ///
     /// Returns `None` if the file is not found in the given mods directory.
@@ -98,15 +394,35 @@ impl Config {
     /// Returns an error if the file cannot be opened.
     pub fn read_updater_blacklist(&self) -> Result<Option<HashSet<PathBuf>>> {
         tracing::info!("Checking for the blacklisted mods...");
-        let path = self.directory.join(UPDATER_BLACKLIST_FILE);
+        self.read_mod_file_list(UPDATER_BLACKLIST_FILE)
+    }
+
+    /// Returns a set of file paths if any are found in the `whitelist.txt`.
+    ///
+    /// When present, only the listed mods are checked by `update`.
+    /// Returns `None` if the file is not found in the given mods directory.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be opened.
+    pub fn read_updater_whitelist(&self) -> Result<Option<HashSet<PathBuf>>> {
+        tracing::info!("Checking for the whitelisted mods...");
+        self.read_mod_file_list(UPDATER_WHITELIST_FILE)
+    }
+
+    /// Reads a list of mod file names in the mods directory, one per line.
+    ///
+    /// Blank lines and lines starting with `#` are skipped.
+    fn read_mod_file_list(&self, file_name: &str) -> Result<Option<HashSet<PathBuf>>> {
+        let path = self.directory.join(file_name);
 
         tracing::info!(
-            "Reading updater blacklist from '{}'",
+            "Reading mod list from '{}'",
             fileutil::replace_home_dir_with_tilde(&path)
         );
 
         if !path.exists() {
-            tracing::info!("The updater blacklist file does not exist.");
+            tracing::info!("The file '{}' does not exist.", file_name);
             return Ok(None);
         }
 
@@ -123,7 +439,7 @@ impl Config {
                         tracing::debug!("Skipping line {}: '{}'", line_number + 1, trimmed);
                         continue;
                     }
-                    tracing::info!("Blacklist entry: {:?}", trimmed);
+                    tracing::info!("{} entry: {:?}", file_name, trimmed);
 
                     // NOTE: It is easier to compare them as full paths.
                     filenames.insert(self.directory.join(trimmed));
@@ -140,7 +456,7 @@ impl Config {
             }
         }
 
-        tracing::debug!("Blacklist contains {} entries.", filenames.len());
+        tracing::debug!("{} contains {} entries.", file_name, filenames.len());
 
         Ok(Some(filenames))
     }
@@ -153,18 +469,90 @@ fn get_default_mods_directory() -> Option<PathBuf> {
     env::home_dir().map(|home_path| home_path.join(STEAM_MODS_DIRECTORY_PATH))
 }
 
+/// How an entry of the mods directory is treated by the installed mod scan.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ScannedFile {
+    /// A `.zip` file, which is loaded as a mod.
+    Archive,
+    /// A dotfile (e.g., `.DS_Store` or the `._Mod.zip` files of macOS), skipped silently.
+    Hidden,
+    /// A partial download or a temporary file (e.g., `Mod.zip.part`), skipped silently.
+    Partial,
+    /// A list of Everest, such as `blacklist.txt`.
+    EverestList,
+    /// Anything else, reported with `--warn-unknown`.
+    Unknown,
+}
+
+impl ScannedFile {
+    fn classify(path: &Path) -> Self {
+        let Some(file_name) = path.file_name().and_then(|name| name.to_str()) else {
+            return ScannedFile::Unknown;
+        };
+        let lowercase = file_name.to_lowercase();
+        if file_name.starts_with('.') {
+            ScannedFile::Hidden
+        } else if PARTIAL_FILE_SUFFIXES
+            .iter()
+            .any(|suffix| lowercase.ends_with(suffix))
+        {
+            ScannedFile::Partial
+        } else if lowercase.ends_with(".zip") {
+            ScannedFile::Archive
+        } else if lowercase.ends_with(".txt") {
+            ScannedFile::EverestList
+        } else {
+            ScannedFile::Unknown
+        }
+    }
+}
+
+/// Returns the path to the directory where the application state is stored.
+///
+/// If the user's home directory could not be determined, it returns None.
+pub fn get_state_directory() -> Option<PathBuf> {
+    env::home_dir().map(|home_path| home_path.join(STATE_DIRECTORY_PATH))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::io::Write;
     use tempfile::{TempDir, tempdir};
 
+    impl Default for Config {
+        fn default() -> Self {
+            Self {
+                directory: PathBuf::new(),
+                mirror_preferences: String::new(),
+                jobs: DEFAULT_JOBS,
+                max_connections_per_host: crate::constant::DEFAULT_MAX_CONNECTIONS_PER_HOST,
+                downloader: Downloader::Builtin,
+                pool_max_idle_per_host: crate::constant::DEFAULT_POOL_MAX_IDLE_PER_HOST,
+                pool_idle_timeout: Duration::from_secs(
+                    crate::constant::DEFAULT_POOL_IDLE_TIMEOUT_SECS,
+                ),
+                stall_timeout: Duration::from_secs(crate::constant::DEFAULT_STALL_TIMEOUT_SECS),
+                ignore_patterns: Vec::new(),
+                recursive: false,
+                warn_unknown: false,
+                verification_policy: VerificationPolicy::Enforce,
+                dedup_store: None,
+                fail_fast_threshold: None,
+                segments: 1,
+                proxy: None,
+                allow_insecure_download: false,
+                trusted_key: None,
+            }
+        }
+    }
+
     /// Helper to create a Config with a temp mods directory.
     fn config_with_temp_dir() -> (Config, TempDir) {
         let temp_dir = tempdir().unwrap();
         let config = Config {
             directory: temp_dir.path().to_path_buf(),
-            mirror_preferences: String::new(),
+            ..Default::default()
         };
         (config, temp_dir)
     }
@@ -183,12 +571,171 @@ mod tests {
         assert_eq!(archives[0], file_path);
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn test_ensure_directory_writable() {
+        let (config, temp_dir) = config_with_temp_dir();
+        assert!(config.ensure_directory_writable().is_ok());
+        // The probe file must not be left behind
+        assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_ensure_directory_writable_missing_directory() {
+        let (_, temp_dir) = config_with_temp_dir();
+        let config = Config {
+            directory: temp_dir.path().join("missing"),
+            ..Default::default()
+        };
+        let err = config.ensure_directory_writable().unwrap_err();
+        assert!(err.to_string().contains("is not writable"));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_find_installed_mod_archives_follows_symlink() {
+        use std::os::unix::fs::symlink;
+
+        let (config, temp_dir) = config_with_temp_dir();
+        let target = fs::canonicalize("./test/test-mod.zip").unwrap();
+        let link = temp_dir.path().join("linked.zip");
+        symlink(&target, &link).unwrap();
+
+        let archives = config.find_installed_mod_archives().unwrap();
+        assert_eq!(archives, vec![link]);
+
+        let local_mods = crate::local_mod::LocalMod::load_local_mods(&archives);
+        assert_eq!(local_mods.len(), 1);
+        assert_eq!(local_mods[0].manifest.name, "test-mod");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_find_installed_mod_archives_skips_broken_and_cyclic_symlinks() {
+        use std::os::unix::fs::symlink;
+
+        let (config, temp_dir) = config_with_temp_dir();
+        symlink(
+            temp_dir.path().join("missing.zip"),
+            temp_dir.path().join("broken.zip"),
+        )
+        .unwrap();
+        symlink(temp_dir.path().join("b.zip"), temp_dir.path().join("a.zip")).unwrap();
+        symlink(temp_dir.path().join("a.zip"), temp_dir.path().join("b.zip")).unwrap();
+
+        let archives = config.find_installed_mod_archives().unwrap();
+        assert!(archives.is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_find_installed_mod_archives_deduplicates_links_to_same_archive() {
+        use std::os::unix::fs::symlink;
+
+        let (config, temp_dir) = config_with_temp_dir();
+        let target = fs::canonicalize("./test/test-mod.zip").unwrap();
+        symlink(&target, temp_dir.path().join("first.zip")).unwrap();
+        symlink(&target, temp_dir.path().join("second.zip")).unwrap();
+
+        let archives = config.find_installed_mod_archives().unwrap();
+        assert_eq!(archives.len(), 1);
+    }
+
+    #[test]
+    fn test_find_installed_mod_archives_ignore_patterns() {
+        let (mut config, temp_dir) = config_with_temp_dir();
+        config.ignore_patterns = vec!["*-backup.zip".to_string()];
+        let kept = temp_dir.path().join("Mod.zip");
+        fs::File::create(&kept).unwrap();
+        fs::File::create(temp_dir.path().join("Mod-backup.zip")).unwrap();
+
+        let archives = config.find_installed_mod_archives().unwrap();
+        assert_eq!(archives, vec![kept]);
+    }
+
+    #[test]
+    fn test_find_installed_mod_archives_skips_junk_files() {
+        let (mut config, temp_dir) = config_with_temp_dir();
+        config.recursive = true;
+        let kept = temp_dir.path().join("Mod.zip");
+        fs::File::create(&kept).unwrap();
+        for junk in [
+            "._Mod.zip",
+            ".DS_Store",
+            "Other.zip.part",
+            "Other.zip~",
+            "blacklist.txt",
+            "notes.pdf",
+        ] {
+            fs::File::create(temp_dir.path().join(junk)).unwrap();
+        }
+        let hidden_dir = temp_dir.path().join(".git");
+        fs::create_dir(&hidden_dir).unwrap();
+        fs::File::create(hidden_dir.join("Hidden.zip")).unwrap();
+
+        assert_eq!(config.find_installed_mod_archives().unwrap(), vec![kept]);
+    }
+
+    #[test]
+    fn test_scanned_file_classify() {
+        let classify = |name: &str| ScannedFile::classify(Path::new(name));
+        assert_eq!(classify("Mods/Mod.ZIP"), ScannedFile::Archive);
+        assert_eq!(classify(".DS_Store"), ScannedFile::Hidden);
+        assert_eq!(classify("Mod.zip.crdownload"), ScannedFile::Partial);
+        assert_eq!(classify("updaterblacklist.txt"), ScannedFile::EverestList);
+        assert_eq!(classify("Thumbs.db"), ScannedFile::Unknown);
+    }
+
+    #[test]
+    fn test_find_installed_mod_archives_recursive() {
+        let (mut config, temp_dir) = config_with_temp_dir();
+        let top = temp_dir.path().join("Top.zip");
+        let nested_dir = temp_dir.path().join("Helpers").join("Frost");
+        fs::create_dir_all(&nested_dir).unwrap();
+        fs::File::create(&top).unwrap();
+        let nested = nested_dir.join("Nested.zip");
+        fs::File::create(&nested).unwrap();
+
+        assert_eq!(
+            config.find_installed_mod_archives().unwrap(),
+            vec![top.clone()]
+        );
+
+        config.recursive = true;
+        let mut archives = config.find_installed_mod_archives().unwrap();
+        archives.sort();
+        assert_eq!(archives, vec![nested, top]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_find_installed_mod_archives_recursive_guards_against_cycles() {
+        use std::os::unix::fs::symlink;
+
+        let (mut config, temp_dir) = config_with_temp_dir();
+        config.recursive = true;
+        let sub = temp_dir.path().join("sub");
+        fs::create_dir(&sub).unwrap();
+        fs::File::create(sub.join("Mod.zip")).unwrap();
+        symlink(temp_dir.path(), sub.join("loop")).unwrap();
+
+        let mut deep = temp_dir.path().to_path_buf();
+        for level in 0..MAX_SCAN_DEPTH + 1 {
+            deep = deep.join(format!("level{level}"));
+        }
+        fs::create_dir_all(&deep).unwrap();
+        fs::File::create(deep.join("TooDeep.zip")).unwrap();
+
+        let archives = config.find_installed_mod_archives().unwrap();
+        assert_eq!(archives, vec![sub.join("Mod.zip")]);
+    }
+
     #[test]
     fn test_find_installed_mod_archives_missing_directory() {
         let nonexistent_path = Path::new("nonexistent_directory");
         let config = Config {
             directory: nonexistent_path.to_path_buf(),
-            mirror_preferences: String::new(),
+            ..Default::default()
         };
 
         let result = config.find_installed_mod_archives();
@@ -224,4 +771,19 @@ mod tests {
         let optional_blacklist = result.unwrap();
         assert!(optional_blacklist.is_none());
     }
+
+    #[test]
+    fn test_read_updater_whitelist_skips_comments() {
+        let (config, temp_dir) = config_with_temp_dir();
+        let whitelist_file = temp_dir.path().join(UPDATER_WHITELIST_FILE);
+
+        let mut file = File::create(&whitelist_file).unwrap();
+        writeln!(file, "# Keep these up to date").unwrap();
+        writeln!(file).unwrap();
+        writeln!(file, "  whitelisted_mod.zip  ").unwrap();
+
+        let whitelist = config.read_updater_whitelist().unwrap().unwrap();
+        assert_eq!(whitelist.len(), 1);
+        assert!(whitelist.contains(&temp_dir.path().join("whitelisted_mod.zip")));
+    }
 }
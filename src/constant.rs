@@ -1,6 +1,42 @@
 /// The directory where the Celeste mods are stored.
 pub const STEAM_MODS_DIRECTORY_PATH: &str = ".local/share/Steam/steamapps/common/Celeste/Mods";
 
+/// The directory where the application stores its state, such as logs.
+pub const STATE_DIRECTORY_PATH: &str = ".local/state/everest-mod-cli";
+
+/// The file name of the log, without the extension.
+pub const LOG_FILE_STEM: &str = "everest-mod-cli";
+
+/// The maximum edit distance of an installed mod name suggested for a misspelled one.
+pub const MAX_SUGGESTION_DISTANCE: usize = 2;
+
+/// File name suffixes of partial downloads and temporary files, skipped by the installed mod scan.
+pub const PARTIAL_FILE_SUFFIXES: [&str; 5] = [".part", ".tmp", ".crdownload", ".download", "~"];
+
+/// The directory where the application caches remote files, relative to the home directory.
+pub const CACHE_DIRECTORY_PATH: &str = ".cache/everest-mod-cli";
+
+/// The name of the application's directory inside `XDG_CACHE_HOME`.
+pub const CACHE_DIRECTORY_NAME: &str = "everest-mod-cli";
+
+/// The directory of the config file, relative to the home directory.
+pub const CONFIG_DIRECTORY_PATH: &str = ".config/everest-mod-cli";
+
+/// The name of the application's directory inside `XDG_CONFIG_HOME`.
+pub const CONFIG_DIRECTORY_NAME: &str = "everest-mod-cli";
+
+/// The name of the config file defining the profiles of `--profile`.
+pub const CONFIG_FILE_NAME: &str = "config.toml";
+
+/// The mirror priority used unless `--mirror-priority` or the profile sets one.
+pub const DEFAULT_MIRROR_PRIORITY: &str = "otobot,gb,jade,wegfan";
+
+/// The directory inside the cache directory where `--dedup-store` keeps the archives.
+pub const DEDUP_STORE_DIRECTORY_NAME: &str = "store";
+
+/// How long in seconds a cached copy of the mod registry or the dependency graph is reused.
+pub const CACHE_TTL_SECS: u64 = 10 * 60;
+
 /// The URL to the remote mod registry.
 pub const MOD_REGISTRY_URL: &str = "https://maddie480.ovh/celeste/everest_update.yaml";
 
@@ -9,3 +45,81 @@ pub const MOD_DEPENDENCY_GRAPH: &str = "https://maddie480.ovh/celeste/mod_depend
 
 /// The name of the blacklist file.
 pub const UPDATER_BLACKLIST_FILE: &str = "updaterblacklist.txt";
+
+/// The name of the whitelist file.
+pub const UPDATER_WHITELIST_FILE: &str = "whitelist.txt";
+
+/// The default number of idle connections kept per host for reuse.
+///
+/// Matches the number of concurrent downloads so every worker can pick up a warm connection.
+pub const DEFAULT_POOL_MAX_IDLE_PER_HOST: usize = 6;
+
+/// The default number of seconds an idle connection is kept in the pool.
+pub const DEFAULT_POOL_IDLE_TIMEOUT_SECS: u64 = 90;
+
+/// The default number of dependencies above which `install` asks before downloading them.
+pub const DEFAULT_CONFIRM_DEPS_OVER: usize = 10;
+
+/// The default number of seconds without receiving any data after which a download is
+/// considered stalled.
+pub const DEFAULT_STALL_TIMEOUT_SECS: u64 = 30;
+
+/// The interval in seconds between the status lines printed while downloading with
+/// `--no-progress`.
+pub const PLAIN_STATUS_INTERVAL_SECS: u64 = 5;
+
+/// The interval of TCP keep-alive probes sent on pooled connections.
+pub const TCP_KEEPALIVE_SECS: u64 = 60;
+
+/// The timeout in seconds of a single request sent by the `ping` subcommand.
+pub const PING_TIMEOUT_SECS: u64 = 5;
+
+/// The default number of mods downloaded at once.
+pub const DEFAULT_JOBS: usize = 6;
+
+/// The default number of connections opened to a single mirror at once.
+pub const DEFAULT_MAX_CONNECTIONS_PER_HOST: usize = 4;
+
+/// The maximum length in bytes of a file name on common file systems.
+pub const MAX_FILE_NAME_LEN: usize = 255;
+
+/// The maximum length in bytes of a full path, `MAX_PATH` minus the terminating null on Windows.
+#[cfg(windows)]
+pub const MAX_PATH_LEN: usize = 259;
+
+/// The maximum length in bytes of a full path, `PATH_MAX` minus the terminating null.
+#[cfg(not(windows))]
+pub const MAX_PATH_LEN: usize = 4095;
+
+/// The minimum size in bytes of a file downloaded in segments with `--segments`.
+pub const MIN_SEGMENTED_DOWNLOAD_SIZE: u64 = 8 * 1024 * 1024;
+
+/// The external downloader used by `--downloader external`.
+pub const EXTERNAL_DOWNLOADER_PROGRAM: &str = "aria2c";
+
+/// The program verifying the signatures of `--verify-signature`.
+pub const SIGNATURE_PROGRAM: &str = "gpg";
+
+/// The base URL relative download URLs in the registry are resolved against.
+pub const GAMEBANANA_BASE_URL: &str = "https://gamebanana.com/";
+
+/// The base URL of the GameBanana API used by `search --enrich`.
+pub const GAMEBANANA_API_URL: &str = "https://gamebanana.com/apiv11/";
+
+/// The maximum number of requests sent to the GameBanana API at once.
+pub const GAMEBANANA_API_CONCURRENCY: usize = 4;
+
+/// How long in seconds the statistics fetched from the GameBanana API are cached.
+pub const GAMEBANANA_API_CACHE_TTL_SECS: u64 = 60 * 60;
+
+/// The GameBanana submission type of the mod pages accepted by `install`.
+pub const GAMEBANANA_MOD_TYPE: &str = "Mod";
+
+/// The maximum number of attempts to fetch the mod registry or the dependency graph.
+pub const REGISTRY_FETCH_ATTEMPTS: usize = 3;
+
+/// The delay in seconds before the first retry of a registry fetch, doubled on every retry.
+pub const REGISTRY_RETRY_DELAY_SECS: u64 = 2;
+
+/// The maximum depth of subdirectories scanned for mod archives with `--recursive`.
+pub const MAX_SCAN_DEPTH: usize = 8;
@@ -1,4 +1,8 @@
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::{
+    borrow::Cow,
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
+};
 
 use anyhow::Result;
 use reqwest::Client;
@@ -7,14 +11,14 @@ use serde::{Deserialize, Serialize};
 use crate::{
     constant::MOD_DEPENDENCY_GRAPH,
     fetch,
-    manifest::Dependency,
-    mod_registry::{RemoteModInfo, RemoteModRegistry},
+    manifest::{Dependency, compare_versions, normalize_name},
+    mod_registry::RemoteModRegistry,
 };
 
 /// Each entry of the `mod_dependency_graph.yaml`.
 #[derive(Debug, Default, Deserialize, Serialize, Clone, Hash, PartialEq, Eq)]
 pub struct DependencyInfo {
-    #[serde(rename = "OptionalDependencies")]
+    #[serde(rename = "OptionalDependencies", default)]
     optional_dependencies: Vec<Dependency>,
     #[serde(rename = "Dependencies")]
     dependencies: Vec<Dependency>,
@@ -25,23 +29,316 @@ pub struct DependencyInfo {
 /// Represents `mod_dependency_graph.yaml` which is the dependency graph.
 pub type DependencyGraph = HashMap<String, DependencyInfo>;
 
+/// Returns the path from the requested mod to the given dependency, e.g.
+/// `["RequestedMod", "FrameworkX", "HelperY"]`.
+///
+/// `predecessors` is the result of [`ModDependencyQuery::collect_dependency_predecessors`].
+/// Returns an empty vector if the dependency was not reached.
+pub fn dependency_path(
+    predecessors: &HashMap<String, Option<String>>,
+    dependency: &str,
+) -> Vec<String> {
+    let mut path = Vec::new();
+    let mut current = Some(dependency.to_string());
+    while let Some(name) = current {
+        let Some(predecessor) = predecessors.get(&name) else {
+            return Vec::new();
+        };
+        current = predecessor.clone();
+        path.push(name);
+    }
+    path.reverse();
+    path
+}
+
+/// Differences between the dependencies an installed mod declares in its `everest.yaml` and
+/// those of the same mod in the dependency graph.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct DependencyDiff {
+    /// Dependencies only the dependency graph declares
+    pub added: Vec<String>,
+    /// Dependencies only the installed manifest declares
+    pub removed: Vec<String>,
+}
+
+impl DependencyDiff {
+    /// Returns whether both sources declare the same dependencies.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// Compares the dependencies of an installed manifest with those of the dependency graph.
+///
+/// Names are compared after [`normalize_name`], and versions are ignored. The names are
+/// returned as declared, sorted.
+pub fn diff_dependencies(installed: &[Dependency], registry: &[Dependency]) -> DependencyDiff {
+    let only_in = |deps: &[Dependency], others: &[Dependency]| {
+        let others: HashSet<_> = others.iter().map(|dep| normalize_name(&dep.name)).collect();
+        let mut names: Vec<String> = deps
+            .iter()
+            .filter(|dep| !others.contains(&normalize_name(&dep.name)))
+            .map(|dep| dep.name.clone())
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    };
+    DependencyDiff {
+        added: only_in(registry, installed),
+        removed: only_in(installed, registry),
+    }
+}
+
+/// Renders the dependency graph in the Graphviz DOT format, for `graph-export`.
+///
+/// With a `root`, only the mods it requires directly or transitively are rendered, as well as
+/// their optional dependencies, which are not followed. Optional dependencies are dashed edges,
+/// and the mods in `installed` are filled. Everest itself is left out. The keys of `installed`
+/// are expected to be normalized with [`normalize_name`].
+pub fn to_dot(graph: &DependencyGraph, root: Option<&str>, installed: &HashSet<String>) -> String {
+    let dependents: BTreeSet<String> = match root {
+        Some(root) => graph
+            .collect_all_dependencies_bfs(root)
+            .into_iter()
+            .collect(),
+        None => graph.keys().cloned().collect(),
+    };
+
+    let mut nodes = dependents.clone();
+    let mut edges = BTreeSet::new();
+    for dependent in &dependents {
+        let Some(info) = graph.get_mod_info_by_name(dependent) else {
+            continue;
+        };
+        for (deps, optional) in [
+            (&info.dependencies, false),
+            (&info.optional_dependencies, true),
+        ] {
+            for dep in deps {
+                if matches!(dep.name.as_str(), "Everest" | "EverestCore")
+                    || is_self_dependency(dependent, dep)
+                {
+                    continue;
+                }
+                let name = normalize_name(&dep.name).into_owned();
+                nodes.insert(name.clone());
+                edges.insert((dependent.clone(), name, optional));
+            }
+        }
+    }
+
+    let quote = |name: &str| format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""));
+    let mut dot = String::from("digraph dependencies {\n    rankdir=LR;\n    node [shape=box];\n");
+    for node in &nodes {
+        if installed.contains(node) {
+            dot.push_str(&format!(
+                "    {} [style=filled, fillcolor=palegreen];\n",
+                quote(node)
+            ));
+        } else {
+            dot.push_str(&format!("    {};\n", quote(node)));
+        }
+    }
+    for (dependent, dependency, optional) in &edges {
+        let style = if *optional { " [style=dashed]" } else { "" };
+        dot.push_str(&format!(
+            "    {} -> {}{style};\n",
+            quote(dependent),
+            quote(dependency)
+        ));
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+/// The reverse of the dependency graph: the mods requiring each mod directly.
+///
+/// Optional dependencies and Everest itself are left out, like when collecting dependencies.
+#[derive(Debug, Default)]
+pub struct ReverseDependencyGraph {
+    required_by: HashMap<String, Vec<String>>,
+}
+
+impl ReverseDependencyGraph {
+    /// Builds the reverse adjacency map of the graph.
+    pub fn new(graph: &DependencyGraph) -> Self {
+        let mut required_by: HashMap<String, Vec<String>> = HashMap::new();
+        for (dependent, info) in graph {
+            for dep in &info.dependencies {
+                if !matches!(dep.name.as_str(), "Everest" | "EverestCore") {
+                    required_by
+                        .entry(normalize_name(&dep.name).into_owned())
+                        .or_default()
+                        .push(dependent.clone());
+                }
+            }
+        }
+        Self { required_by }
+    }
+
+    /// Collects the mods requiring the given mod, along with the dependent through which each
+    /// one requires it. Direct dependents map to `None`.
+    ///
+    /// With `transitive`, the mods requiring a dependent are collected too.
+    pub fn collect_dependents(
+        &self,
+        mod_name: &str,
+        transitive: bool,
+    ) -> BTreeMap<String, Option<String>> {
+        let mod_name = normalize_name(mod_name);
+        let mut dependents = BTreeMap::new();
+        let mut queue: VecDeque<(&str, Option<&str>)> = VecDeque::new();
+        let direct = self.required_by.get(mod_name.as_ref());
+        queue.extend(
+            direct
+                .into_iter()
+                .flatten()
+                .map(|name| (name.as_str(), None)),
+        );
+
+        while let Some((current, via)) = queue.pop_front() {
+            if current == mod_name || dependents.contains_key(current) {
+                continue;
+            }
+            dependents.insert(current.to_string(), via.map(String::from));
+            if transitive && let Some(required_by) = self.required_by.get(current) {
+                queue.extend(
+                    required_by
+                        .iter()
+                        .map(|name| (name.as_str(), Some(current))),
+                );
+            }
+        }
+        dependents
+    }
+}
+
+/// A dependency whose minimum version is not satisfied.
+#[derive(Debug, PartialEq, Eq)]
+pub struct UnsatisfiedMinimum {
+    /// Name of the mod requiring the dependency
+    pub dependent: String,
+    /// Name of the dependency
+    pub dependency: String,
+    /// Minimum version required by the dependent
+    pub minimum: String,
+    /// Version found, either installed or in the registry
+    pub found: String,
+    /// Whether the version found is the installed one
+    pub installed: bool,
+}
+
+impl std::fmt::Display for UnsatisfiedMinimum {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let source = if self.installed {
+            "installed version is"
+        } else {
+            "registry has"
+        };
+        write!(
+            f,
+            "[{}] requires {} >= {}, {} {}",
+            self.dependent, self.dependency, self.minimum, source, self.found
+        )
+    }
+}
+
+/// A dependency required at several minimum versions, not all of which are satisfied.
+///
+/// The registry has a single version of each mod, so this is a minimum that can't be satisfied
+/// while other mods are fine with the version found.
+#[derive(Debug, PartialEq, Eq)]
+pub struct VersionConflict {
+    /// Name of the dependency
+    pub dependency: String,
+    /// Version found, either installed or in the registry
+    pub found: String,
+    /// Whether the version found is the installed one
+    pub installed: bool,
+    /// Mods requiring the dependency with their minimum version, sorted by name
+    pub requirements: Vec<VersionRequirement>,
+}
+
+/// A minimum version of a dependency required by a mod.
+#[derive(Debug, PartialEq, Eq)]
+pub struct VersionRequirement {
+    /// Name of the mod requiring the dependency
+    pub dependent: String,
+    /// Minimum version required by the dependent
+    pub minimum: String,
+    /// Whether the version found satisfies the minimum
+    pub satisfied: bool,
+}
+
+impl std::fmt::Display for VersionConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let source = if self.installed {
+            "installed version is"
+        } else {
+            "registry has"
+        };
+        let requirements: Vec<String> = self
+            .requirements
+            .iter()
+            .map(|requirement| {
+                let mark = if requirement.satisfied { "" } else { " ❌" };
+                format!(
+                    "[{}] >= {}{mark}",
+                    requirement.dependent, requirement.minimum
+                )
+            })
+            .collect();
+        write!(
+            f,
+            "{} is required at conflicting versions, {} {}: {}",
+            self.dependency,
+            source,
+            self.found,
+            requirements.join(", ")
+        )
+    }
+}
+
 /// A trait for querying mod dependencies.
 pub trait ModDependencyQuery {
     async fn fetch(client: &Client) -> Result<DependencyGraph>;
     fn get_mod_info_by_name(&self, name: &str) -> Option<&DependencyInfo>;
+    fn required_deps(&self, name: &str) -> Option<&[Dependency]>;
+    fn optional_deps(&self, name: &str) -> Option<&[Dependency]>;
     fn collect_all_dependencies_bfs(&self, mod_name: &str) -> HashSet<String>;
-    fn check_dependencies(
+    fn collect_dependency_predecessors(&self, mod_name: &str) -> HashMap<String, Option<String>>;
+    fn count_dependencies(&self, mod_name: &str) -> Option<(usize, usize)>;
+    fn find_unsatisfied_minimums(
         &self,
         mod_name: &str,
         mod_registry: &RemoteModRegistry,
-        installed_mod_names: &HashSet<String>,
-    ) -> Vec<(String, RemoteModInfo)>;
+        installed_versions: &HashMap<String, String>,
+    ) -> Vec<UnsatisfiedMinimum>;
+    fn find_version_conflicts(
+        &self,
+        mod_name: &str,
+        other_mods: &[&str],
+        unsatisfied: &[UnsatisfiedMinimum],
+    ) -> Vec<VersionConflict>;
+    fn required_everest_version(&self, mod_name: &str) -> Option<String>;
+}
+
+/// Returns whether a mod lists itself as a dependency, which only a malformed manifest does.
+fn is_self_dependency(mod_name: &str, dep: &Dependency) -> bool {
+    normalize_name(&dep.name) == normalize_name(mod_name)
 }
 
 impl ModDependencyQuery for DependencyGraph {
     /// Fetches the Dependency Graph from the maddie480's server.
+    ///
+    /// The mod names used as keys are normalized with [`normalize_name`].
     async fn fetch(client: &Client) -> Result<Self> {
-        fetch::fetch_remote_data::<Self>(MOD_DEPENDENCY_GRAPH, client).await
+        let graph = fetch::fetch_remote_data::<Self>(MOD_DEPENDENCY_GRAPH, client).await?;
+        Ok(graph
+            .into_iter()
+            .map(|(name, info)| (normalize_name(&name).into_owned(), info))
+            .collect())
     }
 
     /// Gets a mod registry entry that matches the given name.
@@ -50,24 +347,58 @@ impl ModDependencyQuery for DependencyGraph {
             "Getting the dependency information matching the name: {}",
             name
         );
-        self.get(name)
+        self.get(normalize_name(name).as_ref())
+    }
+
+    /// Returns the required dependencies of a mod, or `None` if the mod is not in the graph.
+    fn required_deps(&self, name: &str) -> Option<&[Dependency]> {
+        self.get_mod_info_by_name(name)
+            .map(|info| info.dependencies.as_slice())
+    }
+
+    /// Returns the optional dependencies of a mod, or `None` if the mod is not in the graph.
+    ///
+    /// Optional dependencies are never followed when collecting the dependencies of a mod.
+    fn optional_deps(&self, name: &str) -> Option<&[Dependency]> {
+        self.get_mod_info_by_name(name)
+            .map(|info| info.optional_dependencies.as_slice())
     }
 
     /// Collects all dependencies for a given mod name using iterative BFS.
+    ///
+    /// The returned names are normalized with [`normalize_name`].
     fn collect_all_dependencies_bfs(&self, mod_name: &str) -> HashSet<String> {
-        let mut visited = HashSet::new();
-        let mut queue = VecDeque::new();
-        queue.push_back(mod_name);
+        self.collect_dependency_predecessors(mod_name)
+            .into_keys()
+            .collect()
+    }
+
+    /// Collects all dependencies for a given mod name using iterative BFS, along with the mod
+    /// through which each dependency was first reached.
+    ///
+    /// The requested mod itself maps to `None`. Use [`dependency_path`] to turn the result into
+    /// the path from the requested mod to a dependency. The names are normalized with
+    /// [`normalize_name`].
+    fn collect_dependency_predecessors(&self, mod_name: &str) -> HashMap<String, Option<String>> {
+        let mut predecessors = HashMap::new();
+        let mut queue: VecDeque<(Cow<'_, str>, Option<String>)> = VecDeque::new();
+        queue.push_back((normalize_name(mod_name), None));
 
-        while let Some(current_mod) = queue.pop_front() {
-            if !visited.insert(current_mod.to_string()) {
+        while let Some((current_mod, predecessor)) = queue.pop_front() {
+            if predecessors.contains_key(current_mod.as_ref()) {
                 continue;
             }
+            predecessors.insert(current_mod.to_string(), predecessor);
 
-            if let Some(mod_dep) = self.get_mod_info_by_name(current_mod) {
-                for dep in &mod_dep.dependencies {
-                    if !matches!(dep.name.as_str(), "Everest" | "EverestCore") {
-                        queue.push_back(&dep.name);
+            if let Some(dependencies) = self.required_deps(&current_mod) {
+                for dep in dependencies {
+                    if is_self_dependency(&current_mod, dep) {
+                        tracing::warn!(
+                            "Ignoring the malformed manifest of '{}', which lists itself as a dependency",
+                            current_mod
+                        );
+                    } else if !matches!(dep.name.as_str(), "Everest" | "EverestCore") {
+                        queue.push_back((normalize_name(&dep.name), Some(current_mod.to_string())));
                     }
                 }
             } else {
@@ -78,52 +409,160 @@ impl ModDependencyQuery for DependencyGraph {
             }
         }
 
-        visited
+        predecessors
     }
 
-    /// Checks for missing dependencies of a mod.
+    /// Counts the required dependencies of a mod, excluding Everest itself.
     ///
-    /// Returns a vector of tuples containing the missing dependency name and its remote information.
-    fn check_dependencies(
+    /// Returns the number of direct and transitive dependencies, or `None` if the mod is not
+    /// found in the dependency graph.
+    fn count_dependencies(&self, mod_name: &str) -> Option<(usize, usize)> {
+        let direct = self
+            .required_deps(mod_name)?
+            .iter()
+            .filter(|dep| {
+                !matches!(dep.name.as_str(), "Everest" | "EverestCore")
+                    && !is_self_dependency(mod_name, dep)
+            })
+            .count();
+        // NOTE: The collected dependencies include the mod itself.
+        let transitive = self.collect_all_dependencies_bfs(mod_name).len() - 1;
+        Some((direct, transitive))
+    }
+
+    /// Finds the dependencies of a mod whose minimum version is not satisfied.
+    ///
+    /// The version of a dependency is a minimum, see [`Dependency::is_satisfied_by`]. Installed
+    /// mods are checked against their installed version, the others against the version in the
+    /// registry. The keys of `installed_versions` are expected to be normalized with
+    /// [`normalize_name`].
+    fn find_unsatisfied_minimums(
         &self,
         mod_name: &str,
         mod_registry: &RemoteModRegistry,
-        installed_mod_names: &HashSet<String>,
-    ) -> Vec<(String, RemoteModInfo)> {
-        tracing::info!("Checking dependencies for mod: {}", mod_name);
-
-        // Collects required dependencies for the mod including the mod itself
-        let dependencies = self.collect_all_dependencies_bfs(mod_name);
+        installed_versions: &HashMap<String, String>,
+    ) -> Vec<UnsatisfiedMinimum> {
+        let mut dependents: Vec<String> = self
+            .collect_all_dependencies_bfs(mod_name)
+            .into_iter()
+            .collect();
+        dependents.sort();
 
-        tracing::debug!("Installed mods: {:?}", installed_mod_names);
-        tracing::debug!("Dependencies to check: {:?}", dependencies);
+        let mut unsatisfied = Vec::new();
+        for dependent in dependents {
+            let Some(dependencies) = self.required_deps(&dependent) else {
+                continue;
+            };
+            for dep in dependencies {
+                let Some(minimum) = &dep.version else {
+                    continue;
+                };
+                let name = normalize_name(&dep.name);
+                let (found, installed) = match installed_versions.get(name.as_ref()) {
+                    Some(version) => (version, true),
+                    None => match mod_registry.get(name.as_ref()) {
+                        Some(remote_mod) => (&remote_mod.version, false),
+                        None => continue,
+                    },
+                };
+                if !dep.is_satisfied_by(found) {
+                    tracing::warn!(
+                        "{} requires {} >= {}, found {}",
+                        dependent,
+                        dep.name,
+                        minimum,
+                        found
+                    );
+                    unsatisfied.push(UnsatisfiedMinimum {
+                        dependent: dependent.clone(),
+                        dependency: dep.name.clone(),
+                        minimum: minimum.clone(),
+                        found: found.clone(),
+                        installed,
+                    });
+                }
+            }
+        }
+        unsatisfied
+    }
 
-        // Filters out missing dependencies
-        let missing_deps = dependencies
-            .difference(installed_mod_names)
-            .collect::<Vec<_>>();
+    /// Groups the unsatisfied minimums found by [`Self::find_unsatisfied_minimums`] with the
+    /// other minimum versions of the same dependency required among the dependencies of a mod.
+    ///
+    /// The minimums required by `other_mods` and their dependencies are collected too, so that
+    /// a conflict with an installed mod or another mod of the same batch is found.
+    ///
+    /// Only the dependencies required by more than one mod are returned, sorted by name. The
+    /// others are reported as is.
+    fn find_version_conflicts(
+        &self,
+        mod_name: &str,
+        other_mods: &[&str],
+        unsatisfied: &[UnsatisfiedMinimum],
+    ) -> Vec<VersionConflict> {
+        if unsatisfied.is_empty() {
+            return Vec::new();
+        }
+        let dependents: BTreeSet<String> = std::iter::once(mod_name)
+            .chain(other_mods.iter().copied())
+            .flat_map(|name| self.collect_all_dependencies_bfs(name))
+            .collect();
 
-        tracing::info!("Missing mods: {:?}", missing_deps);
+        let mut conflicts: BTreeMap<String, VersionConflict> = BTreeMap::new();
+        for minimum in unsatisfied {
+            let key = normalize_name(&minimum.dependency).into_owned();
+            if conflicts.contains_key(&key) {
+                continue;
+            }
+            let requirements = dependents
+                .iter()
+                .filter_map(|dependent| {
+                    let dep = self
+                        .required_deps(dependent)?
+                        .iter()
+                        .find(|dep| normalize_name(&dep.name) == key.as_str())?;
+                    Some(VersionRequirement {
+                        dependent: dependent.clone(),
+                        minimum: dep.version.clone()?,
+                        satisfied: dep.is_satisfied_by(&minimum.found),
+                    })
+                })
+                .collect();
+            conflicts.insert(
+                key,
+                VersionConflict {
+                    dependency: minimum.dependency.clone(),
+                    found: minimum.found.clone(),
+                    installed: minimum.installed,
+                    requirements,
+                },
+            );
+        }
+        conflicts
+            .into_values()
+            .filter(|conflict| conflict.requirements.len() > 1)
+            .collect()
+    }
 
-        missing_deps
-            .into_iter()
-            .filter_map(|name| {
-                let name = name.clone();
-                if let Some(remote_mod) = mod_registry.get(&name) {
-                    tracing::info!("Mod [{}] is available: {}", name, remote_mod.download_url);
-                    Some((name, remote_mod.to_owned()))
-                } else {
-                    tracing::warn!("Mod [{}] is not available in the registry", name);
-                    None
-                }
-            })
-            .collect::<Vec<_>>()
+    /// Returns the highest Everest version required by a mod or any of its dependencies.
+    ///
+    /// Returns `None` if none of them declares a minimum Everest version.
+    fn required_everest_version(&self, mod_name: &str) -> Option<String> {
+        self.collect_all_dependencies_bfs(mod_name)
+            .iter()
+            .filter_map(|name| self.required_deps(name))
+            .flatten()
+            .filter(|dep| matches!(dep.name.as_str(), "Everest" | "EverestCore"))
+            .filter_map(|dep| dep.version.as_deref())
+            .max_by(|a, b| compare_versions(a, b).unwrap_or(Ordering::Equal))
+            .map(String::from)
     }
 }
 
 #[cfg(test)]
 mod tests_dependency {
     use super::*;
+    use crate::mod_registry::RemoteModInfo;
 
     impl DependencyInfo {
         pub fn new(dependencies: Vec<Dependency>) -> Self {
@@ -159,6 +598,36 @@ mod tests_dependency {
         graph
     }
 
+    #[test]
+    fn test_collect_dependents() {
+        let mut graph = sample_graph();
+        // E depends on A and D
+        graph.insert(
+            "E".to_string(),
+            DependencyInfo::new(vec![mock_dep("A"), mock_dep("D"), mock_dep("Everest")]),
+        );
+        let reverse = ReverseDependencyGraph::new(&graph);
+
+        let direct = reverse.collect_dependents("D", false);
+        assert_eq!(
+            direct,
+            BTreeMap::from([("B".to_string(), None), ("E".to_string(), None)])
+        );
+
+        // A is reached through B, while E requires D directly.
+        let transitive = reverse.collect_dependents("D", true);
+        assert_eq!(
+            transitive,
+            BTreeMap::from([
+                ("A".to_string(), Some("B".to_string())),
+                ("B".to_string(), None),
+                ("E".to_string(), None),
+            ])
+        );
+        assert!(reverse.collect_dependents("E", true).is_empty());
+        assert!(reverse.collect_dependents("Everest", true).is_empty());
+    }
+
     #[test]
     fn test_collect_all_dependencies_bfs() {
         let graph = sample_graph();
@@ -168,6 +637,65 @@ mod tests_dependency {
         assert_eq!(deps, expected);
     }
 
+    #[test]
+    fn test_required_and_optional_deps() -> anyhow::Result<()> {
+        let yaml = r#"
+        A:
+          URL: https://gamebanana.com/mmdl/1
+          Dependencies:
+          - Name: B
+            Version: 1.0.0
+          - Name: Everest
+            Version: 1.4000.0
+          OptionalDependencies:
+          - Name: C
+            Version: 2.0.0
+        B:
+          URL: https://gamebanana.com/mmdl/2
+          Dependencies: []
+        C:
+          URL: https://gamebanana.com/mmdl/3
+          Dependencies: []
+          OptionalDependencies: []
+        "#;
+        let graph: DependencyGraph = serde_yaml_ng::from_str(yaml)?;
+
+        fn names(deps: Option<&[Dependency]>) -> Vec<&str> {
+            deps.unwrap().iter().map(|dep| dep.name.as_str()).collect()
+        }
+        assert_eq!(names(graph.required_deps("A")), ["B", "Everest"]);
+        assert_eq!(names(graph.optional_deps("A")), ["C"]);
+        assert!(graph.optional_deps("B").unwrap().is_empty());
+        assert!(graph.required_deps("Unknown").is_none());
+
+        // Optional dependencies are not collected.
+        let deps = graph.collect_all_dependencies_bfs("A");
+        assert!(!deps.contains("C"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_dependency_path() {
+        let graph = sample_graph();
+        let predecessors = graph.collect_dependency_predecessors("A");
+
+        assert_eq!(dependency_path(&predecessors, "D"), ["A", "B", "D"]);
+        assert_eq!(dependency_path(&predecessors, "C"), ["A", "C"]);
+        assert_eq!(dependency_path(&predecessors, "A"), ["A"]);
+        assert!(dependency_path(&predecessors, "Unknown").is_empty());
+    }
+
+    #[test]
+    fn test_count_dependencies() {
+        let mut graph = sample_graph();
+        if let Some(a) = graph.get_mut("A") {
+            a.dependencies.push(mock_dep("Everest"));
+        }
+        assert_eq!(graph.count_dependencies("A"), Some((2, 3)));
+        assert_eq!(graph.count_dependencies("D"), Some((0, 0)));
+        assert_eq!(graph.count_dependencies("Unknown"), None);
+    }
+
     #[test]
     fn test_collect_all_dependencies_bfs_handles_cycles() {
         let mut graph = sample_graph();
@@ -181,6 +709,75 @@ mod tests_dependency {
         assert_eq!(deps, expected); // Should not infinite loop
     }
 
+    #[test]
+    fn test_self_dependency_is_ignored() {
+        let mut graph = sample_graph();
+        for name in ["A", "B"] {
+            if let Some(info) = graph.get_mut(name) {
+                info.dependencies.push(mock_dep(name));
+            }
+        }
+        let deps = graph.collect_all_dependencies_bfs("B");
+        let expected: std::collections::HashSet<_> =
+            ["B", "D"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(deps, expected);
+
+        let predecessors = graph.collect_dependency_predecessors("A");
+        assert_eq!(predecessors["A"], None);
+        assert_eq!(predecessors["B"], Some("A".to_string()));
+        assert_eq!(graph.count_dependencies("A"), Some((2, 3)));
+        assert_eq!(graph.count_dependencies("B"), Some((1, 1)));
+    }
+
+    #[test]
+    fn test_diff_dependencies() {
+        let installed = [
+            mock_dep("Everest"),
+            mock_dep("FrostHelper"),
+            mock_dep("OldHelper"),
+        ];
+        let registry = [
+            mock_dep("Everest"),
+            mock_dep("FrostHelper "),
+            mock_dep("VivHelper"),
+            mock_dep("CommunalHelper"),
+        ];
+        let diff = diff_dependencies(&installed, &registry);
+        assert_eq!(diff.added, ["CommunalHelper", "VivHelper"]);
+        assert_eq!(diff.removed, ["OldHelper"]);
+        assert!(!diff.is_empty());
+        assert!(diff_dependencies(&registry, &registry).is_empty());
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let mut graph = sample_graph();
+        if let Some(b) = graph.get_mut("B") {
+            b.optional_dependencies.push(mock_dep("E \"quoted\""));
+            b.dependencies.push(mock_dep("Everest"));
+        }
+        graph.insert("Unrelated".to_string(), DependencyInfo::new(vec![]));
+        let installed = HashSet::from(["B".to_string()]);
+
+        assert_eq!(
+            to_dot(&graph, Some("B"), &installed),
+            r#"digraph dependencies {
+    rankdir=LR;
+    node [shape=box];
+    "B" [style=filled, fillcolor=palegreen];
+    "D";
+    "E \"quoted\"";
+    "B" -> "D";
+    "B" -> "E \"quoted\"" [style=dashed];
+}
+"#
+        );
+
+        let whole = to_dot(&graph, None, &HashSet::new());
+        assert!(whole.contains("    \"Unrelated\";\n"));
+        assert!(whole.contains("    \"A\" -> \"C\";\n"));
+    }
+
     #[test]
     fn test_get_mod_info_by_name() {
         let graph = sample_graph();
@@ -189,18 +786,187 @@ mod tests_dependency {
         assert!(graph.get_mod_info_by_name("nonexistent").is_none());
     }
 
-    #[test]
-    fn test_check_dependencies() {
-        let graph = sample_graph();
-        let mut mod_registry = RemoteModRegistry::new(); // Assume this is properly initialized
-        for name in ["A", "B", "C", "D"] {
-            mod_registry.insert(name.to_string(), RemoteModInfo::default());
+    fn versioned_dep(name: &str, version: &str) -> Dependency {
+        Dependency {
+            name: name.to_string(),
+            version: Some(version.to_string()),
         }
-        let installed_mods: HashSet<String> = ["A", "B"].iter().map(|s| s.to_string()).collect();
+    }
+
+    fn registry_with(versions: &[(&str, &str)]) -> RemoteModRegistry {
+        versions
+            .iter()
+            .map(|(name, version)| {
+                (
+                    name.to_string(),
+                    RemoteModInfo {
+                        version: version.to_string(),
+                        ..Default::default()
+                    },
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_find_unsatisfied_minimums_satisfiable() {
+        let mut graph = DependencyGraph::new();
+        graph.insert(
+            "A".to_string(),
+            DependencyInfo::new(vec![
+                versioned_dep("B", "1.9.0"),
+                versioned_dep("C", "1.0.0"),
+            ]),
+        );
+        let registry = registry_with(&[("B", "1.10.0"), ("C", "2.0.0")]);
+
+        let unsatisfied = graph.find_unsatisfied_minimums("A", &registry, &HashMap::new());
+        assert!(unsatisfied.is_empty());
+    }
+
+    #[test]
+    fn test_find_unsatisfied_minimums_registry_and_installed() {
+        let mut graph = DependencyGraph::new();
+        graph.insert(
+            "A".to_string(),
+            DependencyInfo::new(vec![
+                versioned_dep("B", "2.0.0"),
+                versioned_dep("C", "1.5.0"),
+            ]),
+        );
+        let registry = registry_with(&[("B", "1.99.0"), ("C", "1.6.0")]);
+        // C is installed at a version below the minimum, even though the registry is newer
+        let installed = HashMap::from([("C".to_string(), "1.4.9".to_string())]);
 
-        let missing_deps = graph.check_dependencies("A", &mod_registry, &installed_mods);
-        assert_eq!(missing_deps.len(), 2); // C and D should be missing
-        assert!(missing_deps.iter().any(|(name, _)| name == "C"));
-        assert!(missing_deps.iter().any(|(name, _)| name == "D"));
+        let unsatisfied = graph.find_unsatisfied_minimums("A", &registry, &installed);
+        assert_eq!(
+            unsatisfied
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            [
+                "[A] requires B >= 2.0.0, registry has 1.99.0",
+                "[A] requires C >= 1.5.0, installed version is 1.4.9",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_version_conflicts() -> anyhow::Result<()> {
+        // A and B both require D, but only B is fine with the version in the registry.
+        let yaml = r#"
+        Collab:
+          URL: https://gamebanana.com/mmdl/1
+          Dependencies:
+          - Name: A
+          - Name: B
+          - Name: C
+        A:
+          URL: https://gamebanana.com/mmdl/2
+          Dependencies:
+          - Name: D
+            Version: 2.0.0
+        B:
+          URL: https://gamebanana.com/mmdl/3
+          Dependencies:
+          - Name: D
+            Version: 1.0.0
+          - Name: E
+            Version: 3.0.0
+        C:
+          URL: https://gamebanana.com/mmdl/4
+          Dependencies:
+          - Name: D
+        D:
+          URL: https://gamebanana.com/mmdl/5
+          Dependencies: []
+        E:
+          URL: https://gamebanana.com/mmdl/6
+          Dependencies: []
+        "#;
+        let graph: DependencyGraph = serde_yaml_ng::from_str(yaml)?;
+        let registry = registry_with(&[("D", "1.5.0"), ("E", "2.0.0")]);
+
+        let unsatisfied = graph.find_unsatisfied_minimums("Collab", &registry, &HashMap::new());
+        assert_eq!(unsatisfied.len(), 2);
+        let conflicts = graph.find_version_conflicts("Collab", &[], &unsatisfied);
+        // E is only required by B, so it is not a conflict.
+        assert_eq!(
+            conflicts
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            [
+                "D is required at conflicting versions, registry has 1.5.0: [A] >= 2.0.0 ❌, [B] >= 1.0.0"
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_version_conflicts_with_installed_mod() -> anyhow::Result<()> {
+        // The installed Helper is fine with D 1.5.0, which the requested A can't use.
+        let yaml = r#"
+        A:
+          URL: https://gamebanana.com/mmdl/1
+          Dependencies:
+          - Name: D
+            Version: 2.0.0
+        Helper:
+          URL: https://gamebanana.com/mmdl/2
+          Dependencies:
+          - Name: D
+            Version: 1.0.0
+        D:
+          URL: https://gamebanana.com/mmdl/3
+          Dependencies: []
+        "#;
+        let graph: DependencyGraph = serde_yaml_ng::from_str(yaml)?;
+        let registry = registry_with(&[("D", "1.5.0")]);
+        let installed_versions = HashMap::from([
+            ("Helper".to_string(), "1.0.0".to_string()),
+            ("D".to_string(), "1.5.0".to_string()),
+        ]);
+
+        let unsatisfied = graph.find_unsatisfied_minimums("A", &registry, &installed_versions);
+        assert_eq!(unsatisfied.len(), 1);
+        // A alone requires D, so this is not a conflict without the installed mods.
+        assert!(
+            graph
+                .find_version_conflicts("A", &[], &unsatisfied)
+                .is_empty()
+        );
+
+        let conflicts = graph.find_version_conflicts("A", &["Helper", "D"], &unsatisfied);
+        assert_eq!(
+            conflicts
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            [
+                "D is required at conflicting versions, installed version is 1.5.0: [A] >= 2.0.0 ❌, [Helper] >= 1.0.0"
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_required_everest_version_is_highest() {
+        let mut graph = DependencyGraph::new();
+        graph.insert(
+            "A".to_string(),
+            DependencyInfo::new(vec![versioned_dep("Everest", "1.4465.0"), mock_dep("B")]),
+        );
+        graph.insert(
+            "B".to_string(),
+            DependencyInfo::new(vec![versioned_dep("EverestCore", "1.5400.0")]),
+        );
+        graph.insert("C".to_string(), DependencyInfo::new(vec![]));
+
+        assert_eq!(
+            graph.required_everest_version("A").as_deref(),
+            Some("1.5400.0")
+        );
+        assert_eq!(graph.required_everest_version("C"), None);
     }
 }
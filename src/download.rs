@@ -1,107 +1,641 @@
-use std::{borrow::Cow, fs, io::Write, path::Path, sync::Arc};
+use std::{
+    borrow::{Borrow, Cow},
+    collections::HashMap,
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
 
 use anyhow::Result;
-use futures_util::StreamExt;
+use futures_util::{Stream, StreamExt};
 use indicatif::{MultiProgress, ProgressBar};
-use reqwest::{Client, Response};
+use reqwest::Client;
 use tempfile::NamedTempFile;
-use tokio::sync::Semaphore;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
 use xxhash_rust::xxh64::Xxh64;
 
-use crate::{config::Config, download, fileutil, mod_registry::RemoteModInfo};
+use crate::{
+    cli::Downloader,
+    config::Config,
+    constant::{EXTERNAL_DOWNLOADER_PROGRAM, PLAIN_STATUS_INTERVAL_SECS},
+    download, fetch, fileutil,
+    local_mod::LocalMod,
+    manifest::normalize_name,
+    mod_registry::RemoteModInfo,
+};
 
+mod external;
+mod segmented;
+mod signature;
+mod store;
 mod util;
 
+/// Errors that can occur while downloading a mod from the mirrors.
+#[derive(Debug, thiserror::Error)]
+pub enum DownloadError {
+    /// Every mirror that served the file failed checksum verification.
+    #[error(
+        "checksum verification failed for '{mod_name}' on every mirror: computed {:?}, expected one of {expected:?}",
+        .mismatches.iter().map(|mismatch| &mismatch.computed).collect::<Vec<_>>()
+    )]
+    InvalidChecksum {
+        mod_name: String,
+        mismatches: Vec<MismatchedDownload>,
+        expected: Vec<String>,
+    },
+    /// No data was received for the stall timeout.
+    #[error("no data received for {0:?}, the download stalled")]
+    Stalled(Duration),
+    /// No mirror could serve the file.
+    #[error("failed to download '{0}' from any mirror")]
+    AllMirrorsFailed(String),
+    /// Every mirror that served a signature served an invalid one.
+    #[error("signature verification failed for '{0}' on every mirror that served a signature")]
+    InvalidSignature(String),
+    /// Every mirror URL uses a scheme other than https.
+    #[error(
+        "refusing to download '{0}' over an insecure connection, pass --allow-insecure-download to allow it"
+    )]
+    InsecureUrl(String),
+    /// The batch was aborted before the download started.
+    #[error("skipped after {0} consecutive failed downloads")]
+    Aborted(usize),
+}
+
+/// A file served by a mirror that failed checksum verification.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MismatchedDownload {
+    /// URL of the mirror the file was downloaded from.
+    pub url: String,
+    /// Checksum computed from the downloaded file.
+    pub computed: String,
+    /// Size of the downloaded file in bytes.
+    pub size: u64,
+}
+
+/// How a checksum that doesn't match the registry is handled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerificationPolicy {
+    /// A mismatch is an error, to protect the integrity of the installed mods.
+    Enforce,
+    /// A mismatch is only reported, for diagnostics and `--no-verify`.
+    WarnOnly,
+}
+
+/// A computed checksum that matches none of the expected ones.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("computed hash '{computed}' does not match expected hashes: {expected:?}")]
+pub struct ChecksumMismatch {
+    pub computed: String,
+    pub expected: Vec<String>,
+}
+
+impl VerificationPolicy {
+    /// Compares the computed checksum against the expected ones, ignoring case.
+    ///
+    /// Returns `Ok(None)` on a match, and `Ok(Some(_))` for a mismatch the policy tolerates.
+    ///
+    /// # Errors
+    ///
+    /// Returns the mismatch if the policy enforces checksums.
+    pub fn check(
+        self,
+        computed: &str,
+        expected: &[String],
+    ) -> Result<Option<ChecksumMismatch>, ChecksumMismatch> {
+        if expected
+            .iter()
+            .any(|checksum| checksum.eq_ignore_ascii_case(computed))
+        {
+            return Ok(None);
+        }
+        let mismatch = ChecksumMismatch {
+            computed: computed.to_string(),
+            expected: expected.to_vec(),
+        };
+        match self {
+            VerificationPolicy::Enforce => Err(mismatch),
+            VerificationPolicy::WarnOnly => Ok(Some(mismatch)),
+        }
+    }
+}
+
+/// Aborts a batch of downloads after a number of consecutive failures, since the network or the
+/// mirrors are most likely broken then (`--fail-fast-threshold`).
+///
+/// One is shared by every download of a run, so that the count spans the mods of a batch.
+#[derive(Debug)]
+pub struct FailFast {
+    threshold: Option<usize>,
+    consecutive: AtomicUsize,
+    aborted: AtomicBool,
+}
+
+impl FailFast {
+    /// Creates a counter aborting after `threshold` consecutive failures, or never if `None`.
+    pub fn new(threshold: Option<usize>) -> Self {
+        Self {
+            threshold,
+            consecutive: AtomicUsize::new(0),
+            aborted: AtomicBool::new(false),
+        }
+    }
+
+    /// Checks if the remaining downloads should be skipped.
+    pub fn is_aborted(&self) -> bool {
+        self.aborted.load(Ordering::Relaxed)
+    }
+
+    /// Records the outcome of a finished download.
+    ///
+    /// Returns true if this failure aborts the batch, so that it is reported only once.
+    fn record(&self, succeeded: bool) -> bool {
+        if succeeded {
+            self.consecutive.store(0, Ordering::Relaxed);
+            return false;
+        }
+        let consecutive = self.consecutive.fetch_add(1, Ordering::Relaxed) + 1;
+        self.threshold
+            .is_some_and(|threshold| consecutive >= threshold)
+            && !self.aborted.swap(true, Ordering::Relaxed)
+    }
+}
+
+/// Limits the number of concurrent downloads, in total and per mirror host.
+///
+/// Some mirrors throttle or ban clients opening too many parallel connections, so the per-host
+/// limit applies even when the total limit is higher.
+#[derive(Debug)]
+pub struct ConnectionLimiter {
+    total: Arc<Semaphore>,
+    per_host_limit: usize,
+    hosts: Mutex<HashMap<String, Arc<Semaphore>>>,
+}
+
+impl ConnectionLimiter {
+    /// Creates a limiter allowing `jobs` downloads at once, at most `per_host_limit` per host.
+    pub fn new(jobs: usize, per_host_limit: usize) -> Self {
+        Self {
+            total: Arc::new(Semaphore::new(jobs.max(1))),
+            per_host_limit: per_host_limit.max(1),
+            hosts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Waits for a free download slot.
+    async fn acquire(&self) -> Result<OwnedSemaphorePermit> {
+        Ok(self.total.clone().acquire_owned().await?)
+    }
+
+    /// Waits for a free connection to the host of the given URL.
+    async fn acquire_host(&self, url: &str) -> Result<OwnedSemaphorePermit> {
+        Ok(self.host_semaphore(url)?.acquire_owned().await?)
+    }
+
+    /// Takes up to `count` more connections to the host of the given URL, only among the ones
+    /// that are free right now.
+    ///
+    /// Never waits, so that downloads already holding a connection can't wait on each other.
+    fn try_acquire_host(&self, url: &str, count: usize) -> Result<Vec<OwnedSemaphorePermit>> {
+        let semaphore = self.host_semaphore(url)?;
+        Ok(
+            std::iter::from_fn(|| semaphore.clone().try_acquire_owned().ok())
+                .take(count)
+                .collect(),
+        )
+    }
+
+    fn host_semaphore(&self, url: &str) -> Result<Arc<Semaphore>> {
+        let host = reqwest::Url::parse(url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_ascii_lowercase))
+            .unwrap_or_default();
+        let mut hosts = self
+            .hosts
+            .lock()
+            .map_err(|_| anyhow::anyhow!("connection limiter is poisoned"))?;
+        Ok(hosts
+            .entry(host)
+            .or_insert_with(|| Arc::new(Semaphore::new(self.per_host_limit)))
+            .clone())
+    }
+}
+
+/// How the mod files are transferred from the mirrors.
+#[derive(Debug, Clone)]
+enum Transport {
+    /// Streams the file with the HTTP client, giving up on a mirror once no data is received
+    /// for `stall_timeout`.
+    /// With more than one segment, large files are downloaded in concurrent byte ranges.
+    Builtin {
+        client: Client,
+        stall_timeout: Duration,
+        segments: usize,
+    },
+    /// Runs the external downloader found at the path with up to the given number of
+    /// connections, through the proxy if any, giving up once no data is received for
+    /// `stall_timeout`.
+    External {
+        program: PathBuf,
+        connections: usize,
+        proxy: Option<String>,
+        stall_timeout: Duration,
+    },
+}
+
 /// Downloads a mod file, returns the file path.
+///
+/// With [`VerificationPolicy::WarnOnly`], the other mirrors are still tried when a file doesn't
+/// match the expected hashes, and the first such file is installed with a warning only if none
+/// of them serves a matching one.
+#[allow(clippy::too_many_arguments)]
 async fn download_mod(
-    client: &Client,
+    transport: &Transport,
     mod_name: &str,
     mirror_urls: &[Cow<'_, str>],
     expected_hashes: &[String],
+    policy: VerificationPolicy,
+    allow_insecure: bool,
+    verifier: Option<&signature::Verifier>,
     download_dir: &Path,
+    store: Option<&Path>,
     pb: &ProgressBar,
-) -> Result<()> {
+    limiter: &ConnectionLimiter,
+) -> Result<PathBuf> {
     tracing::debug!("Original mod name: {}", mod_name);
     let sanitized_name = util::sanitize(mod_name);
 
     tracing::debug!("Sanitized name: {}", sanitized_name);
-    let filename = format!("{}.zip", &sanitized_name);
+    let install_destination = util::resolve_destination(download_dir, &sanitized_name, mod_name);
+    let filename = install_destination
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    if !filename.starts_with(sanitized_name.as_ref()) {
+        tracing::warn!(
+            "Truncated the file name of '{}' to '{}' to fit the path length limit",
+            mod_name,
+            filename
+        );
+        pb.suspend(|| {
+            eprintln!(
+                "⚠️  The file name of [{mod_name}] is too long here, saving it as '{filename}'."
+            )
+        });
+    }
 
-    let install_destination = download_dir.join(&filename);
     tracing::debug!(
         "Install destination: {}",
         fileutil::replace_home_dir_with_tilde(&install_destination)
     );
 
     let msg = pb_style::truncate_msg(mod_name);
+    let debug_filename = fileutil::replace_home_dir_with_tilde(&install_destination);
+
+    if let Some(entry) = store.and_then(|store| store::lookup(store, expected_hashes)) {
+        // `None` if the signature is checked and no mirror vouches for the stored archive
+        let signature = match verifier {
+            Some(verifier) => verify_stored(verifier, mirror_urls, allow_insecure, &entry, limiter)
+                .await?
+                .map(Some),
+            None => Some(None),
+        };
+        if let Some(signature) = signature {
+            tracing::info!(
+                "Linking '{}' from the store",
+                fileutil::replace_home_dir_with_tilde(&entry)
+            );
+            store::link(&entry, &install_destination)?;
+            let suffix = if signature == Some(signature::Signature::Valid) {
+                "from store, signed"
+            } else {
+                "from store"
+            };
+            pb_style::finish_with_message(pb, format!("🍓 {mod_name} [{filename}] ({suffix})"));
+            return Ok(install_destination);
+        }
+        tracing::warn!(
+            "No mirror serves a valid signature for the stored '{}', downloading it again",
+            fileutil::replace_home_dir_with_tilde(&entry)
+        );
+    }
+
+    // Files served by the mirrors that failed verification
+    let mut mismatches = Vec::new();
+    // The first file that failed verification, installed if no mirror serves a matching one
+    let mut unverified = None;
+
+    // Mirror URLs skipped because they aren't https
+    let mut insecure_urls = Vec::new();
+    // Whether a mirror served an invalid signature
+    let mut invalid_signature = false;
 
     for url in mirror_urls {
-        let response = client.get(url.as_ref()).send().await?;
-        if response.status().is_success() {
-            pb.set_message(msg.to_string());
-            match download_and_write(response, &install_destination, expected_hashes, pb).await {
-                Ok(_) => {
-                    pb.finish_with_message(format!("🍓 {mod_name} [{filename}]"));
-                    return Ok(());
+        if !allow_insecure && !is_https(url) {
+            tracing::warn!(
+                "Skipping the insecure download URL '{}' of '{}' (--allow-insecure-download)",
+                url,
+                mod_name
+            );
+            pb.suspend(|| {
+                eprintln!("⚠️  Skipping [{mod_name}] from '{url}': the download URL is not https.")
+            });
+            insecure_urls.push(url);
+            continue; // to the next mirror
+        }
+        let _host_permit = limiter.acquire_host(url).await?;
+        let downloaded = match transport {
+            Transport::Builtin {
+                client,
+                stall_timeout,
+                segments,
+            } => {
+                // Falls back to a single stream if the mirror can't serve segments.
+                let segmented = if *segments > 1 {
+                    pb.set_position(0);
+                    pb.set_message(msg.to_string());
+                    segmented::download_to_temp_file(
+                        client,
+                        url,
+                        *segments,
+                        *stall_timeout,
+                        pb,
+                        limiter,
+                    )
+                    .await
+                } else {
+                    None
+                };
+                if let Some(downloaded) = segmented {
+                    Ok(downloaded)
+                } else {
+                    let response = match client.get(url.as_ref()).send().await {
+                        Ok(response) if response.status().is_success() => response,
+                        Ok(response) => {
+                            tracing::warn!("'{}' -> Status: {}", url, response.status());
+                            tracing::warn!("Download failed, trying another mirror");
+                            pb.set_message("Download failed, trying another mirror");
+                            continue; // to the next mirror
+                        }
+                        Err(e) => {
+                            tracing::warn!("Failed to request '{}': {}", url, e);
+                            pb.set_message("Download failed, trying another mirror");
+                            continue; // to the next mirror
+                        }
+                    };
+
+                    pb.set_position(0);
+                    pb.set_message(msg.to_string());
+                    download_to_temp_file(response.bytes_stream(), *stall_timeout, pb).await
+                }
+            }
+            Transport::External {
+                program,
+                connections,
+                proxy,
+                stall_timeout,
+            } => {
+                // One connection is held already, the others are taken if they are free.
+                let extra_permits = limiter
+                    .try_acquire_host(url, connections.saturating_sub(1))
+                    .unwrap_or_default();
+                pb.set_position(0);
+                pb.set_message(msg.to_string());
+                let options = external::Options {
+                    connections: extra_permits.len() + 1,
+                    proxy: proxy.as_deref(),
+                    stall_timeout: *stall_timeout,
+                };
+                external::download_to_temp_file(program, url, &options, pb).await
+            }
+        };
+        let (temp_file, computed_hash) = match downloaded {
+            Ok(downloaded) => downloaded,
+            Err(e) => {
+                tracing::warn!("Failed to download from '{}': {}", url, e);
+                pb.set_message("Download failed, trying another mirror");
+                continue; // to the next mirror
+            }
+        };
+
+        tracing::info!("Verifying checksum for '{}'", debug_filename);
+        tracing::debug!("computed hash from '{}': {:?}", url, computed_hash);
+        tracing::debug!("expected hash: {:?}", expected_hashes);
+        match policy.check(&computed_hash, expected_hashes) {
+            Ok(None) => {}
+            Ok(Some(mismatch)) => {
+                tracing::warn!(
+                    "Checksum mismatch for '{}' downloaded from '{}', trying another mirror (--no-verify): {}",
+                    debug_filename,
+                    url,
+                    mismatch
+                );
+                pb.set_message("Checksum verification failed, trying another mirror");
+                if unverified.is_none() {
+                    unverified = Some((temp_file, computed_hash, url, mismatch));
                 }
+                continue; // to the next mirror
+            }
+            Err(mismatch) => {
+                // NOTE: The temp file will be removed automatically when it goes out of scope.
+                tracing::warn!(
+                    "Checksum verification failed for '{}' downloaded from '{}': {}",
+                    debug_filename,
+                    url,
+                    mismatch
+                );
+                pb.set_message("Checksum verification failed, trying another mirror");
+                mismatches.push(MismatchedDownload {
+                    url: url.to_string(),
+                    computed: mismatch.computed,
+                    size: temp_file.as_file().metadata().map_or(0, |meta| meta.len()),
+                });
+                continue; // to the next mirror
+            }
+        }
+        tracing::info!("Checksum verification passed for '{}'", debug_filename);
+
+        let signature = match verifier {
+            Some(verifier) => match verifier.verify(url, temp_file.path()).await {
+                Ok(signature) => Some(signature),
                 Err(e) => {
-                    tracing::error!("{}", e);
-                    pb.set_message("Checksum verification failed, trying another mirror");
+                    tracing::warn!(
+                        "Signature verification failed for '{}' downloaded from '{}': {}",
+                        debug_filename,
+                        url,
+                        e
+                    );
+                    pb.set_message("Signature verification failed, trying another mirror");
+                    invalid_signature = true;
                     continue; // to the next mirror
                 }
-            }
+            },
+            None => None,
+        };
+
+        install_file(temp_file, &install_destination, store, &computed_hash)?;
+        if signature == Some(signature::Signature::Valid) {
+            tracing::info!("Signature verification passed for '{}'", debug_filename);
+            pb_style::finish_with_message(pb, format!("🍓 {mod_name} [{filename}] (signed)"));
         } else {
-            tracing::warn!("Status: {}", response.status());
-            tracing::warn!("Download failed, trying another mirror");
-            pb.set_message("Download failed, trying another mirror");
-            continue; // to the next mirror
+            if signature == Some(signature::Signature::Missing) {
+                tracing::info!("No signature is served for '{}'", debug_filename);
+            }
+            pb_style::finish_with_message(pb, format!("🍓 {mod_name} [{filename}]"));
         }
+        return Ok(install_destination);
+    }
+
+    if let Some((temp_file, computed_hash, url, mismatch)) = unverified {
+        tracing::warn!(
+            "Installing '{}' downloaded from '{}' despite the checksum mismatch (--no-verify): {}",
+            debug_filename,
+            url,
+            mismatch
+        );
+        pb.suspend(|| {
+            eprintln!(
+                "{}",
+                console::style(format!(
+                    "⚠️  UNVERIFIED: [{mod_name}] does not match the registry on any mirror (computed {}, expected one of {:?}). Installed anyway because of --no-verify.",
+                    mismatch.computed, mismatch.expected
+                ))
+                .red()
+                .bold()
+            )
+        });
+        install_file(temp_file, &install_destination, store, &computed_hash)?;
+        pb_style::finish_with_message(pb, format!("⚠️  {mod_name} [{filename}] (unverified)"));
+        return Ok(install_destination);
     }
     pb.finish_and_clear();
-    anyhow::bail!("Failed to download the mod: {}", mod_name)
+
+    if !mismatches.is_empty() {
+        Err(DownloadError::InvalidChecksum {
+            mod_name: mod_name.to_string(),
+            mismatches,
+            expected: expected_hashes.to_vec(),
+        }
+        .into())
+    } else if invalid_signature {
+        Err(DownloadError::InvalidSignature(mod_name.to_string()).into())
+    } else if let [url, ..] = insecure_urls[..]
+        && insecure_urls.len() == mirror_urls.len()
+    {
+        Err(DownloadError::InsecureUrl(url.to_string()).into())
+    } else {
+        Err(DownloadError::AllMirrorsFailed(mod_name.to_string()).into())
+    }
 }
 
-/// Writes all bytes to the temporary file, verifies the checksum when the write is complete, and then moves them to the destination.
-async fn download_and_write(
-    response: Response,
-    install_destination: &Path,
-    expected_hashes: &[String],
+/// Verifies the archive found in the store against the signature served by each mirror in turn,
+/// like a downloaded file.
+///
+/// Returns the outcome for the first mirror whose signature is valid or missing, or `None` if
+/// every mirror serves an invalid one.
+///
+/// # Errors
+///
+/// Returns an error if the connection limiter is poisoned.
+async fn verify_stored(
+    verifier: &signature::Verifier,
+    mirror_urls: &[Cow<'_, str>],
+    allow_insecure: bool,
+    entry: &Path,
+    limiter: &ConnectionLimiter,
+) -> Result<Option<signature::Signature>> {
+    for url in mirror_urls {
+        if !allow_insecure && !is_https(url) {
+            continue; // to the next mirror
+        }
+        let _host_permit = limiter.acquire_host(url).await?;
+        match verifier.verify(url, entry).await {
+            Ok(signature) => return Ok(Some(signature)),
+            Err(e) => tracing::warn!(
+                "Signature verification failed for the stored '{}' served by '{}': {}",
+                fileutil::replace_home_dir_with_tilde(entry),
+                url,
+                e
+            ),
+        }
+    }
+    Ok(None)
+}
+
+/// Returns the path of the program verifying the signatures, if it is found on `PATH`.
+pub fn signature_program() -> Option<PathBuf> {
+    signature::find_program()
+}
+
+/// Checks if the URL uses https, ignoring the case of the scheme.
+fn is_https(url: &str) -> bool {
+    url.get(..8)
+        .is_some_and(|scheme| scheme.eq_ignore_ascii_case("https://"))
+}
+
+/// Writes all bytes to a temporary file while computing the xxHash of the contents.
+///
+/// Returns the temporary file and the computed hash as a hex string.
+///
+/// # Errors
+///
+/// Returns [`DownloadError::Stalled`] if no chunk arrives within `stall_timeout`.
+async fn download_to_temp_file<S, B, E>(
+    stream: S,
+    stall_timeout: Duration,
     pb: &ProgressBar,
-) -> Result<()> {
-    let debug_filename = fileutil::replace_home_dir_with_tilde(install_destination);
+) -> Result<(NamedTempFile, String)>
+where
+    S: Stream<Item = Result<B, E>>,
+    B: AsRef<[u8]>,
+    E: std::error::Error + Send + Sync + 'static,
+{
     let mut temp_file = NamedTempFile::new()?;
 
-    let mut stream = response.bytes_stream();
+    let mut stream = std::pin::pin!(stream);
     let mut hasher = Xxh64::new(0);
 
-    tracing::info!("Verifying checksum for '{}'", debug_filename);
-    while let Some(chunk) = stream.next().await {
-        let chunk = chunk?;
-        temp_file.write_all(&chunk)?;
-        hasher.update(&chunk);
+    loop {
+        let chunk = match tokio::time::timeout(stall_timeout, stream.next()).await {
+            Ok(Some(chunk)) => chunk?,
+            Ok(None) => break,
+            Err(_) => return Err(DownloadError::Stalled(stall_timeout).into()),
+        };
+        let chunk = chunk.as_ref();
+        temp_file.write_all(chunk)?;
+        hasher.update(chunk);
         pb.inc(chunk.len() as u64);
     }
     let computed_hash = hasher.digest();
-    let hash_str = format!("{computed_hash:016x}");
 
-    tracing::debug!("computed hash: {:?}", hash_str,);
-    tracing::debug!("expected hash: {:?}", expected_hashes);
-    tracing::info!("Checksum verification passed for '{}'", debug_filename);
+    Ok((temp_file, format!("{computed_hash:016x}")))
+}
 
-    if !expected_hashes.contains(&hash_str) {
-        anyhow::bail!(
-            "Checksum verification failed for '{}': computed hash '{}' does not match expected hashes: {:?}",
-            debug_filename,
-            hash_str,
-            expected_hashes
-        );
-        // NOTE: The temp file will be removed automatically when they goes out scope
-        // or when the program exits. So we don't need to remove it manually.
-    }
+/// Moves the verified file to the destination, replacing the previous version.
+///
+/// With a store, the file is added to it under its checksum and linked to the destination.
+fn install_file(
+    temp_file: NamedTempFile,
+    install_destination: &Path,
+    store: Option<&Path>,
+    checksum: &str,
+) -> Result<()> {
+    let debug_filename = fileutil::replace_home_dir_with_tilde(install_destination);
 
-    tracing::info!("Checksum verified.");
+    if let Some(store) = store {
+        let entry = store::add(store, temp_file.path(), checksum)?;
+        store::link(&entry, install_destination)?;
+        tracing::info!("The file linked to '{}'", debug_filename);
+        return Ok(());
+    }
 
-    if install_destination.exists() {
+    // A dangling symlink left by the store doesn't exist, but copying would follow it.
+    if install_destination.symlink_metadata().is_ok() {
         tracing::debug!(
             "'{}' is already exists. Trying to remove it.",
             debug_filename
@@ -118,106 +652,470 @@ async fn download_and_write(
     Ok(())
 }
 
+/// Outcome of a batch of downloads.
+#[derive(Debug, Default)]
+pub struct DownloadSummary {
+    /// Mods that were downloaded successfully, verified unless `--no-verify` installed a file
+    /// matching no checksum.
+    pub succeeded: Vec<(String, RemoteModInfo)>,
+    /// Mods that could not be downloaded, along with the cause.
+    pub failed: Vec<(String, RemoteModInfo, anyhow::Error)>,
+    /// Paths where the successfully downloaded mods were placed.
+    pub installed_files: HashMap<String, PathBuf>,
+}
+
+/// A newly installed file that failed the verification pass of `--verify-after`.
+#[derive(Debug)]
+pub struct VerificationFailure {
+    /// Name of the mod.
+    pub name: String,
+    /// Path of the installed file.
+    pub path: PathBuf,
+    /// Why the verification failed.
+    pub reason: String,
+}
+
+impl std::fmt::Display for VerificationFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "[{}] {}: {}",
+            self.name,
+            fileutil::replace_home_dir_with_tilde(&self.path),
+            self.reason
+        )
+    }
+}
+
+impl DownloadSummary {
+    /// Total size in bytes of the successfully downloaded mods.
+    pub fn total_bytes(&self) -> u64 {
+        self.succeeded.iter().map(|(_, info)| info.file_size).sum()
+    }
+
+    /// Re-reads and hashes every newly installed file in parallel, and checks it against the
+    /// checksums in the registry.
+    ///
+    /// Returns the files that couldn't be read or don't match.
+    pub fn verify_installed_files(&self) -> Vec<VerificationFailure> {
+        use rayon::prelude::*;
+
+        let mut failures = self
+            .succeeded
+            .par_iter()
+            .filter_map(|(name, info)| {
+                let path = self.installed_files.get(name)?;
+                let reason = match fileutil::hash_file(path) {
+                    Ok(hash) => match VerificationPolicy::Enforce.check(&hash, &info.checksums) {
+                        Ok(_) => return None,
+                        Err(mismatch) => mismatch.to_string(),
+                    },
+                    Err(err) => format!("failed to read the file: {err}"),
+                };
+                Some(VerificationFailure {
+                    name: name.clone(),
+                    path: path.clone(),
+                    reason,
+                })
+            })
+            .collect::<Vec<_>>();
+        failures.sort_by(|a, b| a.name.cmp(&b.name));
+        failures
+    }
+
+    /// Converts the summary into an error if any of the downloads failed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error listing every failed download.
+    pub fn ensure_success(&self) -> Result<()> {
+        if self.failed.is_empty() {
+            tracing::info!("Successfully download the mods.");
+            return Ok(());
+        }
+
+        let errors = self
+            .failed
+            .iter()
+            .map(|(_, _, err)| err)
+            .collect::<Vec<_>>();
+        for (i, error) in errors.iter().enumerate() {
+            tracing::error!("Error {}: {}", i + 1, error)
+        }
+        anyhow::bail!("Failed to download the mods: {:?}", errors)
+    }
+}
+
 /// Downloads mods concurrently with a limit on the number of concurrent downloads.
 ///
-/// # Errors
+/// Every download is attempted even if some of them fail. Use [`DownloadSummary::ensure_success`]
+/// to turn failures into an error.
 ///
-/// Returns an error if any of the downloads fail or if there are issues with the tasks.
-pub async fn download_mods_concurrently(
+/// The mods may be borrowed from the registry. Each one is cloned once to be moved into its
+/// download task, and that copy ends up in the summary.
+pub async fn download_mods_concurrently<M: Borrow<RemoteModInfo>>(
     client: &Client,
-    mods: &[(String, RemoteModInfo)],
+    mods: &[(String, M)],
     config: Arc<Config>,
-    semaphore: &Arc<Semaphore>,
-) -> Result<()> {
+    limiter: &Arc<ConnectionLimiter>,
+    fail_fast: &Arc<FailFast>,
+) -> DownloadSummary {
     tracing::debug!(
         "Mods to download: {:?}",
         mods.iter().map(|(n, _)| n).collect::<Vec<_>>()
     );
 
+    let mut summary = DownloadSummary::default();
+
     if mods.is_empty() {
         tracing::info!("No mods to download");
-        return Ok(());
+        return summary;
     }
 
-    let mp = MultiProgress::new();
+    let download_client = fetch::download_client(client, &config);
+    let transport = match config.downloader() {
+        Downloader::External => match external::find_program() {
+            Some(program) => {
+                tracing::info!("Using the external downloader: {}", program.display());
+                Transport::External {
+                    program,
+                    connections: config.max_connections_per_host(),
+                    proxy: config.proxy().map(str::to_string),
+                    stall_timeout: config.stall_timeout(),
+                }
+            }
+            None => {
+                tracing::warn!(
+                    "{} is not found on PATH, falling back to the built-in downloader",
+                    EXTERNAL_DOWNLOADER_PROGRAM
+                );
+                println!(
+                    "⚠️  {EXTERNAL_DOWNLOADER_PROGRAM} is not found on PATH, using the built-in downloader."
+                );
+                Transport::Builtin {
+                    client: download_client,
+                    stall_timeout: config.stall_timeout(),
+                    segments: config.segments(),
+                }
+            }
+        },
+        Downloader::Builtin => Transport::Builtin {
+            client: download_client,
+            stall_timeout: config.stall_timeout(),
+            segments: config.segments(),
+        },
+    };
+
+    let verifier = config
+        .trusted_key()
+        .and_then(|trusted_key| signature::Verifier::new(client.clone(), trusted_key));
+
+    let mp = MultiProgress::with_draw_target(pb_style::draw_target());
+
+    // In plain mode, the overall progress is printed periodically instead of drawing the bars.
+    let bars: Arc<Mutex<Vec<ProgressBar>>> = Arc::default();
+    let status_printer = pb_style::is_plain().then(|| {
+        let bars = bars.clone();
+        let total = mods.len();
+        tokio::spawn(async move {
+            let mut interval =
+                tokio::time::interval(Duration::from_secs(PLAIN_STATUS_INTERVAL_SECS));
+            interval.tick().await; // The first tick completes immediately.
+            loop {
+                interval.tick().await;
+                if let Ok(bars) = bars.lock() {
+                    eprintln!("{}", pb_style::plain_status(&bars, total));
+                }
+            }
+        })
+    });
 
     let mut handles = Vec::with_capacity(mods.len());
+    let done = Arc::new(AtomicUsize::new(0));
+    let total = mods.len();
+    pb_style::start_title();
+    pb_style::set_title(0, total);
 
     for (name, remote_mod) in mods {
-        let semaphore = semaphore.clone();
+        let fail_fast = fail_fast.clone();
+        let done = done.clone();
+        let limiter = limiter.clone();
         let config = config.clone();
-        let client = client.clone();
+        let transport = transport.clone();
+        let verifier = verifier.clone();
         let mp = mp.clone();
+        let bars = bars.clone();
         let name = name.clone();
-        let remote_mod = remote_mod.clone();
+        let remote_mod: RemoteModInfo = remote_mod.borrow().clone();
 
         let handle = tokio::spawn(async move {
-            let _permit = semaphore.acquire().await?;
-            let pb = mp.add(ProgressBar::new(remote_mod.file_size));
-            pb.set_style(pb_style::new());
-            let msg = pb_style::truncate_msg(&name);
-            pb.set_message(msg.to_string());
-
-            let mirror_urls = mirror_list::get_all_mirror_urls(
-                &remote_mod.download_url,
-                config.mirror_preferences(),
-            );
+            let result = async {
+                let _permit = limiter.acquire().await?;
+                if fail_fast.is_aborted() {
+                    return Err(DownloadError::Aborted(
+                        fail_fast.threshold.unwrap_or_default(),
+                    )
+                    .into());
+                }
+                let pb = mp.add(ProgressBar::new(remote_mod.file_size));
+                pb.set_style(pb_style::new());
+                let msg = pb_style::truncate_msg(&name);
+                pb.set_message(msg.to_string());
+                if let Ok(mut bars) = bars.lock() {
+                    bars.push(pb.clone());
+                }
 
-            download::download_mod(
-                &client,
-                &name,
-                &mirror_urls,
-                &remote_mod.checksums,
-                config.directory(),
-                &pb,
-            )
-            .await
+                let mirror_urls = mirror_list::get_all_mirror_urls(
+                    &remote_mod.download_url,
+                    config.mirror_preferences(),
+                );
+
+                let result = download::download_mod(
+                    &transport,
+                    &name,
+                    &mirror_urls,
+                    &remote_mod.checksums,
+                    config.verification_policy(),
+                    config.allow_insecure_download(),
+                    verifier.as_ref(),
+                    config.directory(),
+                    config.dedup_store(),
+                    &pb,
+                    &limiter,
+                )
+                .await;
+                if fail_fast.record(result.is_ok()) {
+                    let threshold = fail_fast.threshold.unwrap_or_default();
+                    tracing::error!(
+                        "Aborting the downloads after {} consecutive failures",
+                        threshold
+                    );
+                    mp.suspend(|| {
+                        eprintln!(
+                            "❌ {threshold} downloads failed in a row, skipping the remaining ones. The network or the mirrors may be down."
+                        )
+                    });
+                }
+                result
+            }
+            .await;
+            pb_style::set_title(done.fetch_add(1, Ordering::Relaxed) + 1, total);
+            (name, remote_mod, result)
         });
         handles.push(handle);
     }
 
-    let mut errors = Vec::with_capacity(handles.len());
-    for handle in handles {
+    for ((name, remote_mod), handle) in mods.iter().zip(handles) {
         match handle.await {
-            Ok(Ok(())) => {}
-            Ok(Err(err)) => {
+            Ok((name, remote_mod, Ok(path))) => {
+                summary.installed_files.insert(name.clone(), path);
+                summary.succeeded.push((name, remote_mod));
+            }
+            Ok((name, remote_mod, Err(err))) => {
                 tracing::error!("Failed to download the mod: {}", err);
-                errors.push(err);
+                summary.failed.push((name, remote_mod, err));
             }
             Err(err) => {
                 tracing::error!("Failed to join tasks: {}", err);
-                errors.push(err.into());
+                summary
+                    .failed
+                    .push((name.clone(), remote_mod.borrow().clone(), err.into()));
             }
         }
     }
+    if let Some(status_printer) = status_printer {
+        status_printer.abort();
+    }
+    pb_style::finish_title();
 
-    if errors.is_empty() {
-        tracing::info!("Successfully download the mods.")
-    } else {
-        for (i, error) in errors.iter().enumerate() {
-            tracing::error!("Error {}: {}", i + 1, error)
-        }
-        anyhow::bail!("Failed to download the mods: {:?}", errors)
+    summary
+}
+
+/// Downloads a historical version of a mod from GameBanana for `install --version`.
+///
+/// The registry has no checksum for older files, so the download can't be verified against it.
+/// Instead, the archive must hold the manifest of the same mod with the requested version, and
+/// its size must match the one given by GameBanana.
+///
+/// # Errors
+///
+/// Returns an error if the download fails, or if the archive is not the requested version of
+/// the mod.
+pub async fn download_historical_version(
+    client: &Client,
+    config: &Config,
+    mod_name: &str,
+    version: &str,
+    url: &str,
+    file_size: u64,
+) -> Result<PathBuf> {
+    if !config.allow_insecure_download() && !is_https(url) {
+        return Err(DownloadError::InsecureUrl(url.to_string()).into());
     }
 
-    Ok(())
+    let pb = ProgressBar::with_draw_target(Some(file_size), pb_style::draw_target());
+    pb.set_style(pb_style::new());
+    pb.set_message(pb_style::truncate_msg(mod_name).to_string());
+    let response = fetch::download_client(client, config)
+        .get(url)
+        .send()
+        .await?
+        .error_for_status()?;
+    let (temp_file, computed_hash) =
+        download_to_temp_file(response.bytes_stream(), config.stall_timeout(), &pb).await?;
+    pb.finish_and_clear();
+
+    let received = temp_file.as_file().metadata()?.len();
+    if file_size > 0 && received != file_size {
+        anyhow::bail!("'{url}' returned {received} bytes, but GameBanana lists {file_size} bytes");
+    }
+    let downloaded = LocalMod::from_path(temp_file.path())
+        .map_err(|e| anyhow::anyhow!("'{url}' is not a mod archive: {e}"))?;
+    let manifest = &downloaded.manifest;
+    if normalize_name(&manifest.name) != normalize_name(mod_name) {
+        anyhow::bail!(
+            "'{url}' contains the mod [{}], not [{mod_name}]",
+            manifest.name
+        );
+    }
+    if manifest.version.trim().trim_start_matches('v') != version.trim().trim_start_matches('v') {
+        anyhow::bail!(
+            "'{url}' contains [{mod_name}] {}, not {version}",
+            manifest.display_version()
+        );
+    }
+
+    let sanitized_name = util::sanitize(mod_name);
+    let install_destination =
+        util::resolve_destination(config.directory(), &sanitized_name, mod_name);
+    install_file(
+        temp_file,
+        &install_destination,
+        config.dedup_store(),
+        &computed_hash,
+    )?;
+    tracing::warn!(
+        "Installed [{}] {} from '{}' without checksum verification",
+        mod_name,
+        version,
+        url
+    );
+    Ok(install_destination)
 }
 
 /// Style configurations of a progress bar.
 pub mod pb_style {
-    use indicatif::{ProgressBar, ProgressStyle};
-    use std::borrow::Cow;
+    use indicatif::{HumanBytes, ProgressBar, ProgressDrawTarget, ProgressStyle};
+    use std::{
+        borrow::Cow,
+        io::Write,
+        sync::atomic::{AtomicBool, Ordering},
+    };
 
     const MAX_MSG_LENGTH: usize = 40;
     const ELLIPSIS: &str = "...";
 
+    /// Whether every progress bar and spinner should be hidden (`--quiet`).
+    static HIDDEN: AtomicBool = AtomicBool::new(false);
+
+    /// Whether progress is printed as plain status lines instead of being drawn
+    /// (`--no-progress`).
+    static PLAIN: AtomicBool = AtomicBool::new(false);
+
+    /// Whether the short progress bar template should be used (`--compact`).
+    static COMPACT: AtomicBool = AtomicBool::new(false);
+
+    /// Whether the overall progress is shown in the terminal title (`--title-progress`).
+    static TITLE: AtomicBool = AtomicBool::new(false);
+
+    /// Terminals narrower than this use the short template even without `--compact`.
+    const MIN_FULL_TEMPLATE_WIDTH: usize = 100;
+
+    /// Hides every progress bar and spinner created afterwards.
+    pub fn hide_all() {
+        HIDDEN.store(true, Ordering::Relaxed);
+    }
+
+    /// Prints plain status lines instead of drawing the progress bars and spinners created
+    /// afterwards.
+    pub fn use_plain() {
+        PLAIN.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns `true` if progress is printed as plain status lines. `--quiet` takes precedence.
+    pub fn is_plain() -> bool {
+        PLAIN.load(Ordering::Relaxed) && !HIDDEN.load(Ordering::Relaxed)
+    }
+
+    /// Uses the short progress bar template for every progress bar created afterwards.
+    pub fn use_compact() {
+        COMPACT.store(true, Ordering::Relaxed);
+    }
+
+    /// Shows the overall progress of the downloads in the terminal title.
+    pub fn use_title_progress() {
+        TITLE.store(true, Ordering::Relaxed);
+    }
+
+    /// Writes an escape sequence for the terminal title to stdout, with `--title-progress`.
+    fn write_title_sequence(sequence: &str) {
+        if TITLE.load(Ordering::Relaxed) {
+            let mut stdout = std::io::stdout().lock();
+            let _ = stdout
+                .write_all(sequence.as_bytes())
+                .and_then(|()| stdout.flush());
+        }
+    }
+
+    /// Saves the current terminal title, to be restored by [`finish_title`].
+    pub fn start_title() {
+        // Pushes the title on the title stack of xterm-compatible terminals.
+        write_title_sequence("\x1b[22;0t");
+    }
+
+    /// Shows how many of the mods are done in the terminal title.
+    pub fn set_title(done: usize, total: usize) {
+        write_title_sequence(&format!("\x1b]0;everest-mod-cli: {done}/{total} mods\x07"));
+    }
+
+    /// Restores the terminal title saved by [`start_title`].
+    ///
+    /// The title is cleared first, for terminals without a title stack.
+    pub fn finish_title() {
+        write_title_sequence("\x1b]0;\x07\x1b[23;0t");
+    }
+
+    /// Returns `true` if the short template should be used, either because `--compact` was
+    /// given or because the terminal is too narrow for the full one.
+    fn is_compact() -> bool {
+        COMPACT.load(Ordering::Relaxed)
+            || console::Term::stderr()
+                .size_checked()
+                .is_some_and(|(_, columns)| (columns as usize) < MIN_FULL_TEMPLATE_WIDTH)
+    }
+
+    /// Returns the draw target for new progress bars, respecting `--quiet` and `--no-progress`.
+    pub fn draw_target() -> ProgressDrawTarget {
+        if HIDDEN.load(Ordering::Relaxed) || PLAIN.load(Ordering::Relaxed) {
+            ProgressDrawTarget::hidden()
+        } else {
+            ProgressDrawTarget::stderr()
+        }
+    }
+
     /// Builds a ProgressBar style, fallbacks to the default.
+    ///
+    /// The compact style only shows the percentage and the rate, so that it fits on narrow
+    /// terminals.
     pub fn new() -> ProgressStyle {
-        ProgressStyle::with_template(
-        "{wide_msg} {total_bytes:>10.1.cyan/blue} {bytes_per_sec:>11.2} {elapsed_precise:>8} [{bar:>40}] {percent:>3}%",
-    )
-    .unwrap_or_else(|_| ProgressStyle::default_bar())
-    .progress_chars("#>-")
+        let template = if is_compact() {
+            "{wide_msg} {percent:>3}% {bytes_per_sec:>11.2}"
+        } else {
+            "{wide_msg} {total_bytes:>10.1.cyan/blue} {bytes_per_sec:>11.2} {elapsed_precise:>8} [{bar:>40}] {percent:>3}%"
+        };
+        ProgressStyle::with_template(template)
+            .unwrap_or_else(|_| ProgressStyle::default_bar())
+            .progress_chars("#>-")
     }
 
     /// Truncates a given string and adds an ellipsis at the end if the length exceeds `MAX_MSG_LENGTH`.
@@ -233,20 +1131,62 @@ pub mod pb_style {
         }
     }
 
-    pub fn create_spinner() -> ProgressBar {
+    /// Creates a spinner showing the given message. In plain mode, the message is printed once.
+    pub fn create_spinner(msg: &'static str) -> ProgressBar {
         use indicatif::ProgressStyle;
         use std::time::Duration;
 
-        let spinner = ProgressBar::new_spinner();
+        if is_plain() {
+            eprintln!("{msg}");
+        }
+        let spinner = ProgressBar::with_draw_target(None, draw_target());
         spinner.enable_steady_tick(Duration::from_millis(100));
         spinner.set_style(
             ProgressStyle::with_template("{spinner:.bold} {msg}")
                 .unwrap_or_else(|_| ProgressStyle::default_spinner()),
         );
-        spinner.set_message("Fetching online database...");
+        spinner.set_message(msg);
         spinner
     }
 
+    /// Creates a progress bar counting processed items, e.g. `⠙ Reading mod archives 42/138`.
+    pub fn create_counter(len: u64, msg: &'static str) -> ProgressBar {
+        use std::time::Duration;
+
+        if is_plain() {
+            eprintln!("{msg} ({len})...");
+        }
+        let pb = ProgressBar::with_draw_target(Some(len), draw_target());
+        pb.enable_steady_tick(Duration::from_millis(100));
+        pb.set_style(
+            ProgressStyle::with_template("{spinner:.bold} {msg} {pos}/{len}")
+                .unwrap_or_else(|_| ProgressStyle::default_bar()),
+        );
+        pb.set_message(msg);
+        pb
+    }
+
+    /// Finishes the progress bar with a message, which is printed as a line in plain mode.
+    pub fn finish_with_message(pb: &ProgressBar, msg: String) {
+        if is_plain() {
+            eprintln!("{msg}");
+        }
+        pb.finish_with_message(msg);
+    }
+
+    /// Returns the status line printed periodically in plain mode while mods are downloaded,
+    /// e.g. `Downloading: 2/5 mods done, 12.30 MiB of 45.60 MiB`.
+    pub fn plain_status(bars: &[ProgressBar], total: usize) -> String {
+        let done = bars.iter().filter(|pb| pb.is_finished()).count();
+        let downloaded: u64 = bars.iter().map(ProgressBar::position).sum();
+        let size: u64 = bars.iter().filter_map(ProgressBar::length).sum();
+        format!(
+            "Downloading: {done}/{total} mods done, {} of {}",
+            HumanBytes(downloaded),
+            HumanBytes(size)
+        )
+    }
+
     #[cfg(test)]
     mod tests {
         use super::*;
@@ -281,6 +1221,22 @@ pub mod pb_style {
             assert_eq!(result, expected);
         }
 
+        #[test]
+        fn test_plain_status() {
+            let finished = ProgressBar::hidden();
+            finished.set_length(1024);
+            finished.set_position(1024);
+            finished.finish();
+            let running = ProgressBar::hidden();
+            running.set_length(2048);
+            running.set_position(512);
+
+            assert_eq!(
+                plain_status(&[finished, running], 3),
+                "Downloading: 1/3 mods done, 1.50 KiB of 3.00 KiB"
+            );
+        }
+
         #[test]
         fn test_truncate_msg_empty_string() {
             let msg = "";
@@ -289,3 +1245,429 @@ pub mod pb_style {
         }
     }
 }
+
+#[cfg(test)]
+mod tests_stall {
+    use super::*;
+    use futures_util::stream;
+
+    #[tokio::test]
+    async fn test_download_to_temp_file_completes() -> Result<()> {
+        let chunks = stream::iter([
+            Ok::<_, std::io::Error>(b"test ".to_vec()),
+            Ok(b"data".to_vec()),
+        ]);
+        let (temp_file, hash) =
+            download_to_temp_file(chunks, Duration::from_secs(1), &ProgressBar::hidden()).await?;
+        assert_eq!(fs::read(temp_file.path())?, b"test data");
+        assert_eq!(hash, fileutil::hash_file(temp_file.path())?);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_download_to_temp_file_gives_up_on_stall() {
+        let chunks =
+            stream::iter([Ok::<_, std::io::Error>(b"test".to_vec())]).chain(stream::pending());
+        let result =
+            download_to_temp_file(chunks, Duration::from_millis(20), &ProgressBar::hidden()).await;
+        assert!(matches!(
+            result.unwrap_err().downcast_ref::<DownloadError>(),
+            Some(DownloadError::Stalled(_))
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests_fail_fast {
+    use super::*;
+
+    #[test]
+    fn test_aborts_after_consecutive_failures() {
+        let fail_fast = FailFast::new(Some(2));
+        assert!(!fail_fast.record(false));
+        assert!(!fail_fast.record(true)); // A success resets the count.
+        assert!(!fail_fast.record(false));
+        assert!(!fail_fast.is_aborted());
+        assert!(fail_fast.record(false));
+        assert!(fail_fast.is_aborted());
+        assert!(!fail_fast.record(false)); // Reported only once.
+    }
+
+    #[test]
+    fn test_never_aborts_without_threshold() {
+        let fail_fast = FailFast::new(None);
+        for _ in 0..100 {
+            assert!(!fail_fast.record(false));
+        }
+        assert!(!fail_fast.is_aborted());
+    }
+
+    #[tokio::test]
+    async fn test_threshold_spans_the_mods_of_a_batch() -> Result<()> {
+        use crate::cli::Cli;
+        use clap::Parser;
+
+        // Nothing listens on the port, so every download fails right away.
+        let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+        let url = format!("http://{}/mod.zip", listener.local_addr()?);
+        drop(listener);
+
+        let temp_dir = tempfile::tempdir()?;
+        let mods_dir = temp_dir.path().to_string_lossy().into_owned();
+        let cli = Cli::try_parse_from([
+            "everest-mod-cli",
+            "--mods-dir",
+            &mods_dir,
+            "--allow-insecure-download",
+            "--fail-fast-threshold",
+            "2",
+            "--jobs",
+            "1",
+            "list",
+        ])?;
+        let config = Config::new(&cli)?;
+        let client = Client::builder().no_proxy().build()?;
+        let limiter = Arc::new(ConnectionLimiter::new(1, 1));
+        let fail_fast = Arc::new(FailFast::new(config.fail_fast_threshold()));
+        let remote_mod = RemoteModInfo {
+            download_url: url,
+            checksums: vec!["0123456789abcdef".to_string()],
+            ..Default::default()
+        };
+
+        // Each requested mod of a batch is downloaded with its own call.
+        let mut aborted = Vec::new();
+        for name in ["A", "B", "C"] {
+            let mods = [(name.to_string(), &remote_mod)];
+            let summary =
+                download_mods_concurrently(&client, &mods, config.clone(), &limiter, &fail_fast)
+                    .await;
+            let (_, _, err) = &summary.failed[0];
+            aborted.push(matches!(
+                err.downcast_ref::<DownloadError>(),
+                Some(DownloadError::Aborted(2))
+            ));
+        }
+        assert_eq!(aborted, [false, false, true]);
+        assert!(fail_fast.is_aborted());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests_connection_limiter {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_per_host_limit() -> Result<()> {
+        let limiter = ConnectionLimiter::new(12, 2);
+        let _a1 = limiter.acquire_host("https://gamebanana.com/dl/1").await?;
+        let _a2 = limiter.acquire_host("https://GameBanana.com/dl/2").await?;
+
+        // A third connection to the same host has to wait...
+        let third = tokio::time::timeout(
+            Duration::from_millis(50),
+            limiter.acquire_host("https://gamebanana.com/dl/3"),
+        )
+        .await;
+        assert!(third.is_err());
+
+        // ...but another host is not affected.
+        let other = tokio::time::timeout(
+            Duration::from_millis(50),
+            limiter.acquire_host("https://celeste.weg.fan/api/v2/download/gamebanana-files/1"),
+        )
+        .await;
+        assert!(other.is_ok());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests_verification {
+    use super::*;
+
+    #[test]
+    fn test_verification_policies_on_mismatch() {
+        let expected = vec!["ABCD1234".to_string()];
+        for policy in [VerificationPolicy::Enforce, VerificationPolicy::WarnOnly] {
+            assert_eq!(policy.check("abcd1234", &expected), Ok(None));
+        }
+
+        let mismatch = ChecksumMismatch {
+            computed: "ffff0000".to_string(),
+            expected: expected.clone(),
+        };
+        assert_eq!(
+            VerificationPolicy::Enforce.check("ffff0000", &expected),
+            Err(mismatch.clone())
+        );
+        assert_eq!(
+            VerificationPolicy::WarnOnly.check("ffff0000", &expected),
+            Ok(Some(mismatch))
+        );
+    }
+
+    #[test]
+    fn test_verify_installed_files() -> Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let good = temp_dir.path().join("good.zip");
+        let bad = temp_dir.path().join("bad.zip");
+        fs::write(&good, b"good")?;
+        fs::write(&bad, b"bad")?;
+
+        let info = |path: &Path| RemoteModInfo {
+            checksums: vec![fileutil::hash_file(path).unwrap()],
+            ..Default::default()
+        };
+        let summary = DownloadSummary {
+            succeeded: vec![
+                ("Good".to_string(), info(&good)),
+                ("Bad".to_string(), info(&bad)),
+                ("Missing".to_string(), info(&good)),
+            ],
+            installed_files: HashMap::from([
+                ("Good".to_string(), good.clone()),
+                ("Bad".to_string(), bad.clone()),
+                ("Missing".to_string(), temp_dir.path().join("missing.zip")),
+            ]),
+            ..Default::default()
+        };
+        fs::write(&bad, b"corrupted")?;
+
+        let failures = summary.verify_installed_files();
+        let names = failures.iter().map(|f| f.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, ["Bad", "Missing"]);
+        assert!(failures[0].reason.contains("does not match"));
+        assert!(failures[1].reason.contains("failed to read"));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests_insecure_url {
+    use super::*;
+
+    #[test]
+    fn test_is_https() {
+        assert!(is_https("https://gamebanana.com/mmdl/1"));
+        assert!(is_https("HTTPS://gamebanana.com/mmdl/1"));
+        assert!(!is_https("http://gamebanana.com/mmdl/1"));
+        assert!(!is_https("ftp://x"));
+    }
+
+    #[tokio::test]
+    async fn test_download_mod_rejects_http_url() -> Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let transport = Transport::Builtin {
+            client: Client::new(),
+            stall_timeout: Duration::from_secs(1),
+            segments: 1,
+        };
+        let mirror_urls = [Cow::Borrowed("http://gamebanana.com/mmdl/1")];
+        let result = download_mod(
+            &transport,
+            "Insecure",
+            &mirror_urls,
+            &["0123456789abcdef".to_string()],
+            VerificationPolicy::Enforce,
+            false,
+            None,
+            temp_dir.path(),
+            None,
+            &ProgressBar::hidden(),
+            &ConnectionLimiter::new(1, 1),
+        )
+        .await;
+
+        let err = result.unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<DownloadError>(),
+            Some(DownloadError::InsecureUrl(url)) if url == "http://gamebanana.com/mmdl/1"
+        ));
+        assert!(!temp_dir.path().join("Insecure.zip").exists());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests_mirror_fallback {
+    use super::*;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    /// Serves the body to every request on a local port, returns the URL of the mirror.
+    async fn serve(body: &'static [u8]) -> Result<String> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://{}/mod.zip", listener.local_addr()?);
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut request = [0u8; 1024];
+                let _ = stream.read(&mut request).await;
+                let header = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    body.len()
+                );
+                let _ = stream.write_all(header.as_bytes()).await;
+                let _ = stream.write_all(body).await;
+            }
+        });
+        Ok(url)
+    }
+
+    fn xxh64(body: &[u8]) -> String {
+        format!("{:016x}", xxhash_rust::xxh64::xxh64(body, 0))
+    }
+
+    async fn download_from(
+        mirror_urls: &[String],
+        policy: VerificationPolicy,
+        download_dir: &Path,
+    ) -> Result<PathBuf> {
+        let transport = Transport::Builtin {
+            // The mirrors are local, so a proxy of the environment must not be used.
+            client: Client::builder().no_proxy().build()?,
+            stall_timeout: Duration::from_secs(5),
+            segments: 1,
+        };
+        let mirror_urls: Vec<Cow<'_, str>> = mirror_urls
+            .iter()
+            .map(|url| Cow::Borrowed(url.as_str()))
+            .collect();
+        download_mod(
+            &transport,
+            "Mirrored",
+            &mirror_urls,
+            &[xxh64(b"good")],
+            policy,
+            true,
+            None,
+            download_dir,
+            None,
+            &ProgressBar::hidden(),
+            &ConnectionLimiter::new(1, 1),
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_download_mod_falls_back_on_bad_checksum() -> Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let mirror_urls = [serve(b"corrupted").await?, serve(b"good").await?];
+
+        let path =
+            download_from(&mirror_urls, VerificationPolicy::Enforce, temp_dir.path()).await?;
+        assert_eq!(fs::read(path)?, b"good");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_download_mod_fails_when_every_checksum_is_bad() -> Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let mirror_urls = [serve(b"corrupted").await?, serve(b"truncated").await?];
+
+        let err = download_from(&mirror_urls, VerificationPolicy::Enforce, temp_dir.path())
+            .await
+            .unwrap_err();
+        let Some(DownloadError::InvalidChecksum { mismatches, .. }) =
+            err.downcast_ref::<DownloadError>()
+        else {
+            panic!("expected InvalidChecksum, got {err:?}");
+        };
+        let urls: Vec<&str> = mismatches.iter().map(|m| m.url.as_str()).collect();
+        assert_eq!(urls, mirror_urls);
+        assert_eq!(mismatches[0].computed, xxh64(b"corrupted"));
+        assert!(!temp_dir.path().join("Mirrored.zip").exists());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_download_mod_without_verification_prefers_a_matching_mirror() -> Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let mirror_urls = [serve(b"corrupted").await?, serve(b"good").await?];
+
+        let path =
+            download_from(&mirror_urls, VerificationPolicy::WarnOnly, temp_dir.path()).await?;
+        assert_eq!(fs::read(path)?, b"good");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_download_mod_without_verification_installs_the_first_mismatch() -> Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let mirror_urls = [serve(b"corrupted").await?, serve(b"truncated").await?];
+
+        let path =
+            download_from(&mirror_urls, VerificationPolicy::WarnOnly, temp_dir.path()).await?;
+        assert_eq!(fs::read(path)?, b"corrupted");
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_download_mod_checks_the_signature_of_a_stored_archive() -> Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let store = temp_dir.path().join("store");
+        let mods_dir = temp_dir.path().join("Mods");
+        fs::create_dir_all(&mods_dir)?;
+        let download = temp_dir.path().join("download.zip");
+        fs::write(&download, b"good")?;
+        store::add(&store, &download, &xxh64(b"good"))?;
+
+        // The mirror serves a signature, which the failing stand-in for gpg rejects.
+        let client = Client::builder().no_proxy().build()?;
+        let verifier = signature::Verifier::with_program(
+            client.clone(),
+            Path::new("false"),
+            Path::new("key.asc"),
+        );
+        let transport = Transport::Builtin {
+            client,
+            stall_timeout: Duration::from_secs(5),
+            segments: 1,
+        };
+        let err = download_mod(
+            &transport,
+            "Mirrored",
+            &[Cow::Owned(serve(b"good").await?)],
+            &[xxh64(b"good")],
+            VerificationPolicy::Enforce,
+            true,
+            Some(&verifier),
+            &mods_dir,
+            Some(&store),
+            &ProgressBar::hidden(),
+            &ConnectionLimiter::new(1, 1),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<DownloadError>(),
+            Some(DownloadError::InvalidSignature(_))
+        ));
+        assert!(mods_dir.join("Mirrored.zip").symlink_metadata().is_err());
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_install_file_replaces_a_dangling_link() -> Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let removed_entry = temp_dir.path().join("removed.zip");
+        let destination = temp_dir.path().join("Mod.zip");
+        std::os::unix::fs::symlink(&removed_entry, &destination)?;
+
+        let mut temp_file = NamedTempFile::new()?;
+        temp_file.write_all(b"good")?;
+        install_file(temp_file, &destination, None, &xxh64(b"good"))?;
+
+        assert!(destination.symlink_metadata()?.is_file());
+        assert_eq!(fs::read(&destination)?, b"good");
+        // The file isn't written through the link.
+        assert!(!removed_entry.exists());
+        Ok(())
+    }
+}
@@ -0,0 +1,132 @@
+use std::{
+    path::{Path, PathBuf},
+    process::Stdio,
+    time::Duration,
+};
+
+use anyhow::Result;
+use indicatif::ProgressBar;
+use tempfile::NamedTempFile;
+use tokio::process::Command;
+
+use crate::{constant::EXTERNAL_DOWNLOADER_PROGRAM, fileutil};
+
+/// Returns the path of the external downloader if it is found on `PATH`.
+pub fn find_program() -> Option<PathBuf> {
+    super::util::find_on_path(EXTERNAL_DOWNLOADER_PROGRAM)
+}
+
+/// Options of the external downloader.
+#[derive(Debug)]
+pub struct Options<'a> {
+    /// Number of connections opened to the server, each one holding a per-host permit
+    pub connections: usize,
+    /// Proxy every request is sent through, like the HTTP client
+    pub proxy: Option<&'a str>,
+    /// Time without receiving data after which the download fails
+    pub stall_timeout: Duration,
+}
+
+/// Returns the arguments of the external downloader, without the output file and the URL.
+fn arguments(options: &Options) -> Vec<String> {
+    // aria2c accepts at most 16 connections per server.
+    let connections = options.connections.clamp(1, 16).to_string();
+    let timeout = options.stall_timeout.as_secs().max(1).to_string();
+    let mut args: Vec<String> = [
+        "--quiet=true",
+        "--allow-overwrite=true",
+        "--auto-file-renaming=false",
+        "--max-connection-per-server",
+        &connections,
+        "--split",
+        &connections,
+        "--timeout",
+        &timeout,
+        "--connect-timeout",
+        &timeout,
+    ]
+    .into_iter()
+    .map(String::from)
+    .collect();
+    if let Some(proxy) = options.proxy {
+        args.push(format!("--all-proxy={proxy}"));
+    }
+    args
+}
+
+/// Downloads the file with the external downloader into a temporary file.
+///
+/// Returns the temporary file and the computed hash as a hex string.
+///
+/// # Errors
+///
+/// Returns an error if the downloader cannot be started or exits with a failure status.
+pub async fn download_to_temp_file(
+    program: &Path,
+    url: &str,
+    options: &Options<'_>,
+    pb: &ProgressBar,
+) -> Result<(NamedTempFile, String)> {
+    let temp_file = NamedTempFile::new()?;
+    let (Some(dir), Some(file_name)) = (temp_file.path().parent(), temp_file.path().file_name())
+    else {
+        anyhow::bail!(
+            "invalid temporary file path: {}",
+            temp_file.path().display()
+        );
+    };
+
+    let status = Command::new(program)
+        .args(arguments(options))
+        .arg("--dir")
+        .arg(dir)
+        .arg("--out")
+        .arg(file_name)
+        .arg(url)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .kill_on_drop(true)
+        .status()
+        .await?;
+    if !status.success() {
+        anyhow::bail!("{} exited with {}", program.display(), status);
+    }
+
+    let computed_hash = fileutil::hash_file(temp_file.path())?;
+    pb.set_position(pb.length().unwrap_or_default());
+
+    Ok((temp_file, computed_hash))
+}
+
+#[cfg(test)]
+mod tests_external {
+    use super::*;
+
+    #[test]
+    fn test_arguments() {
+        let options = Options {
+            connections: 32,
+            proxy: Some("http://192.168.1.2:3128"),
+            stall_timeout: Duration::from_secs(30),
+        };
+        let args = arguments(&options).join(" ");
+        assert!(
+            args.contains("--max-connection-per-server 16 --split 16"),
+            "{args}"
+        );
+        assert!(args.contains("--timeout 30 --connect-timeout 30"), "{args}");
+        assert!(
+            args.ends_with("--all-proxy=http://192.168.1.2:3128"),
+            "{args}"
+        );
+
+        let options = Options {
+            connections: 1,
+            proxy: None,
+            stall_timeout: Duration::ZERO,
+        };
+        let args = arguments(&options).join(" ");
+        assert!(args.contains("--split 1 --timeout 1"), "{args}");
+        assert!(!args.contains("--all-proxy"), "{args}");
+    }
+}
@@ -0,0 +1,279 @@
+//! Segmented downloads for `--segments`.
+//!
+//! The file is split into byte ranges downloaded concurrently, then concatenated and hashed as a
+//! whole, so the checksum is verified exactly like a single-stream download.
+use std::{
+    fs::File,
+    io::{Read, Write},
+    time::Duration,
+};
+
+use anyhow::Result;
+use indicatif::ProgressBar;
+use reqwest::{Client, StatusCode, header};
+use tempfile::NamedTempFile;
+use xxhash_rust::xxh64::Xxh64;
+
+use super::ConnectionLimiter;
+use crate::constant::MIN_SEGMENTED_DOWNLOAD_SIZE;
+
+/// Splits `len` bytes into at most `segments` inclusive ranges of almost the same size.
+fn split_ranges(len: u64, segments: usize) -> Vec<(u64, u64)> {
+    let segments = (segments as u64).clamp(1, len.max(1));
+    let size = len.div_ceil(segments);
+    (0..segments)
+        .map(|i| i * size)
+        .take_while(|start| *start < len)
+        .map(|start| (start, (start + size).min(len) - 1))
+        .collect()
+}
+
+/// Returns the size of the file if the server accepts byte range requests for it.
+async fn probe(client: &Client, url: &str) -> Option<u64> {
+    let response = client.head(url).send().await.ok()?;
+    if !response.status().is_success() {
+        return None;
+    }
+    let accepts_ranges = response
+        .headers()
+        .get(header::ACCEPT_RANGES)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.eq_ignore_ascii_case("bytes"));
+    let len = response
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse().ok())?;
+    accepts_ranges.then_some(len)
+}
+
+/// Downloads one inclusive byte range into a temporary file.
+async fn download_range(
+    client: &Client,
+    url: &str,
+    (start, end): (u64, u64),
+    stall_timeout: Duration,
+    pb: &ProgressBar,
+) -> Result<NamedTempFile> {
+    let response = client
+        .get(url)
+        .header(header::RANGE, format!("bytes={start}-{end}"))
+        .send()
+        .await?;
+    if response.status() != StatusCode::PARTIAL_CONTENT {
+        anyhow::bail!(
+            "range {start}-{end} was answered with status {}",
+            response.status()
+        );
+    }
+    let (part, _) =
+        super::download_to_temp_file(response.bytes_stream(), stall_timeout, pb).await?;
+    let received = part.as_file().metadata()?.len();
+    if received != end - start + 1 {
+        anyhow::bail!(
+            "range {start}-{end} returned {received} bytes instead of {}",
+            end - start + 1
+        );
+    }
+    Ok(part)
+}
+
+/// Concatenates the parts into a temporary file while computing the xxHash of the whole file.
+fn concatenate(parts: Vec<NamedTempFile>) -> Result<(NamedTempFile, String)> {
+    let mut temp_file = NamedTempFile::new()?;
+    let mut hasher = Xxh64::new(0);
+    let mut buffer = vec![0; 64 * 1024];
+
+    for part in parts {
+        let mut part = File::open(part.path())?;
+        loop {
+            let read = part.read(&mut buffer)?;
+            if read == 0 {
+                break;
+            }
+            temp_file.write_all(&buffer[..read])?;
+            hasher.update(&buffer[..read]);
+        }
+    }
+
+    Ok((temp_file, format!("{:016x}", hasher.digest())))
+}
+
+/// Downloads the file in `segments` concurrent ranges into a temporary file.
+///
+/// The caller holds one connection to the host, used for the first range. Each other range
+/// takes one more from the limiter, among the ones free at the time, so the per-host limit
+/// holds and the file may be split into fewer ranges than requested.
+///
+/// Returns the temporary file and the computed hash as a hex string, or `None` if the file
+/// should be downloaded as a single stream instead: the server doesn't accept range requests,
+/// the file is smaller than [`MIN_SEGMENTED_DOWNLOAD_SIZE`], no other connection is free, or
+/// a range failed.
+pub async fn download_to_temp_file(
+    client: &Client,
+    url: &str,
+    segments: usize,
+    stall_timeout: Duration,
+    pb: &ProgressBar,
+    limiter: &ConnectionLimiter,
+) -> Option<(NamedTempFile, String)> {
+    let Some(len) = probe(client, url).await else {
+        tracing::debug!("'{}' doesn't accept range requests", url);
+        return None;
+    };
+    if len < MIN_SEGMENTED_DOWNLOAD_SIZE {
+        tracing::debug!("'{}' is too small to be segmented: {} bytes", url, len);
+        return None;
+    }
+
+    // Released once every range is downloaded.
+    let host_permits = match limiter.try_acquire_host(url, segments.saturating_sub(1)) {
+        Ok(permits) if !permits.is_empty() => permits,
+        Ok(_) => {
+            tracing::debug!("No free connection to '{}' for more segments", url);
+            return None;
+        }
+        Err(e) => {
+            tracing::warn!("{:#}", e);
+            return None;
+        }
+    };
+    let ranges = split_ranges(len, host_permits.len() + 1);
+    tracing::info!("Downloading '{}' in {} segments", url, ranges.len());
+    let downloads = ranges
+        .into_iter()
+        .map(|range| download_range(client, url, range, stall_timeout, pb));
+
+    match futures_util::future::try_join_all(downloads)
+        .await
+        .and_then(concatenate)
+    {
+        Ok(downloaded) => Some(downloaded),
+        Err(e) => {
+            tracing::warn!(
+                "Segmented download from '{}' failed, retrying as a single stream: {}",
+                url,
+                e
+            );
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_segmented {
+    use super::*;
+    use std::sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    };
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    /// Serves the body with range requests on a local port, returns its URL, the number of
+    /// range requests being answered and the highest number of them at once.
+    async fn serve_ranges(body: Arc<Vec<u8>>) -> Result<(String, Arc<AtomicUsize>)> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://{}/mod.zip", listener.local_addr()?);
+        let active = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let peak_seen = Arc::clone(&peak);
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let (body, active, peak) = (body.clone(), active.clone(), peak.clone());
+                tokio::spawn(async move {
+                    let mut request = vec![0u8; 4096];
+                    let Ok(n) = stream.read(&mut request).await else {
+                        return;
+                    };
+                    let request = String::from_utf8_lossy(&request[..n]).to_ascii_lowercase();
+                    let range = request.lines().find_map(|line| {
+                        let (start, end) = line.strip_prefix("range: bytes=")?.split_once('-')?;
+                        Some((start.parse::<usize>().ok()?, end.parse::<usize>().ok()?))
+                    });
+                    let Some((start, end)) = range else {
+                        let header = format!(
+                            "HTTP/1.1 200 OK\r\nAccept-Ranges: bytes\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                            body.len()
+                        );
+                        let _ = stream.write_all(header.as_bytes()).await;
+                        return;
+                    };
+                    let now = active.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                    let header = format!(
+                        "HTTP/1.1 206 Partial Content\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                        end - start + 1
+                    );
+                    let _ = stream.write_all(header.as_bytes()).await;
+                    let _ = stream.write_all(&body[start..=end]).await;
+                    active.fetch_sub(1, Ordering::SeqCst);
+                });
+            }
+        });
+        Ok((url, peak_seen))
+    }
+
+    #[tokio::test]
+    async fn test_segments_stay_within_the_per_host_limit() -> Result<()> {
+        let body: Vec<u8> = (0..MIN_SEGMENTED_DOWNLOAD_SIZE as usize + 1)
+            .map(|i| i as u8)
+            .collect();
+        let body = Arc::new(body);
+        let (url, peak) = serve_ranges(body.clone()).await?;
+        let client = Client::builder().no_proxy().build()?;
+
+        let limiter = ConnectionLimiter::new(16, 3);
+        let _held = limiter.acquire_host(&url).await?;
+        let (file, hash) = download_to_temp_file(
+            &client,
+            &url,
+            16,
+            Duration::from_secs(5),
+            &ProgressBar::hidden(),
+            &limiter,
+        )
+        .await
+        .expect("the file should be downloaded in segments");
+
+        assert_eq!(std::fs::read(file.path())?, *body);
+        assert_eq!(
+            hash,
+            format!("{:016x}", xxhash_rust::xxh64::xxh64(&body, 0))
+        );
+        // 16 segments were asked for, but the host allows 3 connections.
+        assert_eq!(peak.load(Ordering::SeqCst), 3);
+        // The connections are given back once the segments are downloaded.
+        assert_eq!(limiter.try_acquire_host(&url, 16)?.len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_split_ranges() {
+        assert_eq!(split_ranges(10, 3), vec![(0, 3), (4, 7), (8, 9)]);
+        assert_eq!(split_ranges(8, 4), vec![(0, 1), (2, 3), (4, 5), (6, 7)]);
+        // Never more segments than bytes
+        assert_eq!(split_ranges(2, 4), vec![(0, 0), (1, 1)]);
+        assert_eq!(split_ranges(5, 1), vec![(0, 4)]);
+    }
+
+    #[test]
+    fn test_concatenate_hashes_the_whole_file() -> Result<()> {
+        let mut parts = Vec::new();
+        for chunk in [&b"hello "[..], b"world"] {
+            let mut part = NamedTempFile::new()?;
+            part.write_all(chunk)?;
+            parts.push(part);
+        }
+        let (file, hash) = concatenate(parts)?;
+        assert_eq!(std::fs::read(file.path())?, b"hello world");
+        assert_eq!(
+            hash,
+            format!("{:016x}", xxhash_rust::xxh64::xxh64(b"hello world", 0))
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,137 @@
+//! Verification of detached OpenPGP signatures for `--verify-signature`.
+//!
+//! A mirror may serve a signature of a mod at the download URL followed by `.sig`. It is
+//! verified with gpg against the key given by `--trusted-key`, in a temporary keyring so that
+//! the keyring of the user is neither used nor modified.
+use std::{
+    path::{Path, PathBuf},
+    process::Stdio,
+};
+
+use anyhow::Result;
+use reqwest::Client;
+use tokio::process::Command;
+
+use crate::constant::SIGNATURE_PROGRAM;
+
+/// Returns the path of gpg if it is found on `PATH`.
+pub fn find_program() -> Option<PathBuf> {
+    super::util::find_on_path(SIGNATURE_PROGRAM)
+}
+
+/// Returns the URL of the detached signature of a download.
+fn signature_url(url: &str) -> String {
+    format!("{url}.sig")
+}
+
+/// Result of a successful signature check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Signature {
+    /// The mirror serves a signature made by the trusted key.
+    Valid,
+    /// The mirror doesn't serve a signature.
+    Missing,
+}
+
+/// Verifies the downloads against the trusted key.
+#[derive(Debug, Clone)]
+pub struct Verifier {
+    client: Client,
+    program: PathBuf,
+    trusted_key: PathBuf,
+}
+
+impl Verifier {
+    /// Returns a verifier for the trusted key, or `None` if gpg is not found on `PATH`.
+    pub fn new(client: Client, trusted_key: &Path) -> Option<Self> {
+        Some(Self {
+            client,
+            program: find_program()?,
+            trusted_key: trusted_key.to_path_buf(),
+        })
+    }
+
+    /// Returns a verifier running the given program instead of gpg.
+    #[cfg(test)]
+    pub fn with_program(client: Client, program: &Path, trusted_key: &Path) -> Self {
+        Self {
+            client,
+            program: program.to_path_buf(),
+            trusted_key: trusted_key.to_path_buf(),
+        }
+    }
+
+    /// Downloads the signature of the file downloaded from `url`, and verifies it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the signature is invalid, is not made by the trusted key, or can't be
+    /// checked with gpg.
+    pub async fn verify(&self, url: &str, file: &Path) -> Result<Signature> {
+        let signature_url = signature_url(url);
+        let response = match self.client.get(&signature_url).send().await {
+            Ok(response) if response.status().is_success() => response,
+            Ok(response) => {
+                tracing::info!("'{}' -> Status: {}", signature_url, response.status());
+                return Ok(Signature::Missing);
+            }
+            Err(e) => {
+                tracing::info!("Failed to request '{}': {}", signature_url, e);
+                return Ok(Signature::Missing);
+            }
+        };
+        let signature = tempfile::NamedTempFile::new()?;
+        std::fs::write(signature.path(), response.bytes().await?)?;
+
+        let home = tempfile::tempdir()?;
+        self.run_gpg(
+            home.path(),
+            &["--import".as_ref(), self.trusted_key.as_os_str()],
+        )
+        .await?;
+        self.run_gpg(
+            home.path(),
+            &[
+                "--verify".as_ref(),
+                signature.path().as_os_str(),
+                file.as_os_str(),
+            ],
+        )
+        .await?;
+        Ok(Signature::Valid)
+    }
+
+    /// Runs gpg non-interactively with the given home directory.
+    async fn run_gpg(&self, home: &Path, args: &[&std::ffi::OsStr]) -> Result<()> {
+        let output = Command::new(&self.program)
+            .args(["--batch", "--quiet", "--no-tty", "--homedir"])
+            .arg(home)
+            .args(args)
+            .stdin(Stdio::null())
+            .kill_on_drop(true)
+            .output()
+            .await?;
+        if !output.status.success() {
+            anyhow::bail!(
+                "{} exited with {}: {}",
+                self.program.display(),
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests_signature {
+    use super::*;
+
+    #[test]
+    fn test_signature_url() {
+        assert_eq!(
+            signature_url("https://celeste.weg.fan/api/v2/download/gamebanana-files/1298450"),
+            "https://celeste.weg.fan/api/v2/download/gamebanana-files/1298450.sig"
+        );
+    }
+}
@@ -0,0 +1,109 @@
+//! Content-addressed store of the downloaded archives for `--dedup-store`.
+//!
+//! Each archive is stored once under its xxHash, and the mods directories link to it, so the
+//! same mod installed in several profiles takes disk space only once.
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::fileutil;
+
+/// Returns the path of the archive with the given checksum in the store.
+fn entry_path(store: &Path, checksum: &str) -> PathBuf {
+    store.join(format!("{}.zip", checksum.to_ascii_lowercase()))
+}
+
+/// Returns the stored archive matching one of the expected checksums, if any.
+pub fn lookup(store: &Path, expected_hashes: &[String]) -> Option<PathBuf> {
+    expected_hashes
+        .iter()
+        .map(|checksum| entry_path(store, checksum))
+        .find(|path| path.is_file())
+}
+
+/// Copies a verified archive into the store under its checksum, unless it is already there.
+///
+/// # Errors
+///
+/// Returns an error if the store directory can't be created or the archive can't be copied.
+pub fn add(store: &Path, file: &Path, checksum: &str) -> io::Result<PathBuf> {
+    let path = entry_path(store, checksum);
+    if !path.is_file() {
+        fs::create_dir_all(store)?;
+        fileutil::copy_atomic(file, &path)?;
+        tracing::info!(
+            "Added '{}' to the store",
+            fileutil::replace_home_dir_with_tilde(&path)
+        );
+    }
+    Ok(path)
+}
+
+/// Links the stored archive to the destination in the mods directory, replacing what was there.
+///
+/// Falls back to a copy where symlinks can't be created, e.g. on FAT32 drives or on Windows
+/// without the permission to create them.
+///
+/// # Errors
+///
+/// Returns an error if neither a link nor a copy could be created.
+pub fn link(entry: &Path, destination: &Path) -> io::Result<()> {
+    if destination.symlink_metadata().is_ok() {
+        fs::remove_file(destination)?;
+    }
+    if let Err(err) = symlink(entry, destination) {
+        tracing::warn!(
+            "Could not link '{}', copying it instead: {}",
+            fileutil::replace_home_dir_with_tilde(destination),
+            err
+        );
+        fs::copy(entry, destination)?;
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn symlink(original: &Path, link: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(original, link)
+}
+
+#[cfg(windows)]
+fn symlink(original: &Path, link: &Path) -> io::Result<()> {
+    std::os::windows::fs::symlink_file(original, link)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn symlink(_original: &Path, _link: &Path) -> io::Result<()> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+#[cfg(test)]
+mod tests_store {
+    use super::*;
+
+    #[test]
+    fn test_add_lookup_and_link() -> io::Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let store = temp_dir.path().join("store");
+        let download = temp_dir.path().join("download.zip");
+        fs::write(&download, b"archive")?;
+
+        let expected = vec![
+            "0000000000000000".to_string(),
+            "1F3C5A7E9B2D4C60".to_string(),
+        ];
+        assert_eq!(lookup(&store, &expected), None);
+        let entry = add(&store, &download, "1f3c5a7e9b2d4c60")?;
+        assert_eq!(lookup(&store, &expected), Some(entry.clone()));
+
+        // A profile gets a link to the stored archive, replacing its previous version.
+        let destination = temp_dir.path().join("Mods").join("Mod.zip");
+        fs::create_dir_all(destination.parent().unwrap())?;
+        fs::write(&destination, b"old version")?;
+        link(&entry, &destination)?;
+        assert!(destination.symlink_metadata()?.file_type().is_symlink());
+        assert_eq!(fs::read(&destination)?, b"archive");
+        Ok(())
+    }
+}
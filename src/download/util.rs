@@ -1,4 +1,23 @@
-use std::borrow::Cow;
+use std::{
+    borrow::Cow,
+    env,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    constant::{MAX_FILE_NAME_LEN, MAX_PATH_LEN},
+    local_mod::LocalMod,
+    manifest,
+};
+
+/// Returns the path of the program if it is found on `PATH`.
+pub fn find_on_path(program: &str) -> Option<PathBuf> {
+    let paths = env::var_os("PATH")?;
+    let file_name = format!("{program}{}", env::consts::EXE_SUFFIX);
+    env::split_paths(&paths)
+        .map(|dir| dir.join(&file_name))
+        .find(|candidate| candidate.is_file())
+}
 
 /// Returns sanitized mod name or "unnamed" if the given mod name is empty.
 ///
@@ -33,8 +52,8 @@ pub fn sanitize(mod_name: &str) -> Cow<'_, str> {
         result.push(replacement);
     }
 
-    if result.len() > 255 {
-        result.truncate(255);
+    if result.len() > MAX_FILE_NAME_LEN {
+        result.truncate(result.floor_char_boundary(MAX_FILE_NAME_LEN));
         changed = true;
     }
 
@@ -47,6 +66,152 @@ pub fn sanitize(mod_name: &str) -> Cow<'_, str> {
     }
 }
 
+/// Returns the file name `{stem}{suffix}.zip`, with the stem truncated so that neither the file
+/// name nor its full path in `download_dir` exceeds the limits of the platform.
+///
+/// The stem keeps at least one character, so a directory whose path alone is too long still
+/// fails when the file is written.
+fn fit_file_name(download_dir: &Path, stem: &str, suffix: &str) -> String {
+    const EXTENSION: &str = ".zip";
+
+    // The separator between the directory and the file name counts too.
+    let directory_len = download_dir.as_os_str().len() + 1;
+    let max_file_name_len = MAX_FILE_NAME_LEN.min(MAX_PATH_LEN.saturating_sub(directory_len));
+    let max_stem_len = max_file_name_len.saturating_sub(suffix.len() + EXTENSION.len());
+    let stem = if stem.len() > max_stem_len {
+        let first_char_len = stem.chars().next().map_or(0, char::len_utf8);
+        &stem[..stem.floor_char_boundary(max_stem_len).max(first_char_len)]
+    } else {
+        stem
+    };
+    format!("{stem}{suffix}{EXTENSION}")
+}
+
+/// Returns the path where the archive of the given mod should be written.
+///
+/// The file is named after the sanitized mod name, e.g. `SpringCollab2020.zip`. If a file with
+/// that name already belongs to a different mod, a counter is appended (`SpringCollab2020-2.zip`)
+/// so that the other mod isn't overwritten. A file belonging to the same mod, or one whose
+/// manifest can't be read, is reused so updates replace the previous version in place.
+///
+/// The name is truncated if the file name or the full path would be too long, see
+/// [`fit_file_name`].
+pub fn resolve_destination(download_dir: &Path, sanitized_name: &str, mod_name: &str) -> PathBuf {
+    let mut counter = 1;
+    loop {
+        let suffix = if counter == 1 {
+            String::new()
+        } else {
+            format!("-{counter}")
+        };
+        let filename = fit_file_name(download_dir, sanitized_name, &suffix);
+        let destination = download_dir.join(filename);
+
+        match LocalMod::from_path(&destination) {
+            Ok(existing)
+                if manifest::normalize_name(&existing.manifest.name)
+                    != manifest::normalize_name(mod_name) =>
+            {
+                tracing::debug!(
+                    "'{}' belongs to another mod [{}]",
+                    destination.display(),
+                    existing.manifest.name
+                );
+                counter += 1;
+            }
+            _ => return destination,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_destination {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_resolve_destination_new_file() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let destination = resolve_destination(temp_dir.path(), "test-mod", "test-mod");
+        assert_eq!(destination, temp_dir.path().join("test-mod.zip"));
+    }
+
+    #[test]
+    fn test_resolve_destination_same_mod_is_reused() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        fs::copy("./test/test-mod.zip", temp_dir.path().join("test-mod.zip")).unwrap();
+
+        let destination = resolve_destination(temp_dir.path(), "test-mod", "test-mod");
+        assert_eq!(destination, temp_dir.path().join("test-mod.zip"));
+    }
+
+    #[test]
+    fn test_resolve_destination_same_mod_with_another_spelling_is_reused() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        fs::copy("./test/test-mod.zip", temp_dir.path().join("test-mod.zip")).unwrap();
+
+        // Surrounding whitespace doesn't make it another mod.
+        let destination = resolve_destination(temp_dir.path(), "test-mod", " test-mod\t");
+        assert_eq!(destination, temp_dir.path().join("test-mod.zip"));
+    }
+
+    #[test]
+    fn test_resolve_destination_same_mod_in_another_normal_form_is_reused() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        // The archive names the mod "Café" with a precomposed "é" (NFC).
+        fs::copy("./test/cafe-mod.zip", temp_dir.path().join("Cafe.zip")).unwrap();
+
+        let destination = resolve_destination(temp_dir.path(), "Cafe", "Cafe\u{301}");
+        assert_eq!(destination, temp_dir.path().join("Cafe.zip"));
+    }
+
+    #[test]
+    fn test_resolve_destination_truncates_long_names() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let long_name = "Ä".repeat(200);
+
+        let destination = resolve_destination(temp_dir.path(), &long_name, &long_name);
+        let filename = destination.file_name().unwrap().to_str().unwrap();
+        assert!(filename.len() <= MAX_FILE_NAME_LEN, "{filename}");
+        assert!(filename.ends_with("ÄÄ.zip"));
+
+        // A deeply nested directory leaves less room for the file name.
+        let nested = temp_dir
+            .path()
+            .join("a".repeat(MAX_PATH_LEN - temp_dir.path().as_os_str().len() - 50));
+        let destination = resolve_destination(&nested, &long_name, &long_name);
+        // The path is as long as possible without splitting a two-byte character.
+        assert!(destination.as_os_str().len() <= MAX_PATH_LEN);
+        assert!(destination.as_os_str().len() >= MAX_PATH_LEN - 1);
+        assert_eq!(destination.parent(), Some(nested.as_path()));
+    }
+
+    #[test]
+    fn test_fit_file_name() {
+        let directory = Path::new("/mods");
+        assert_eq!(fit_file_name(directory, "test-mod", "-2"), "test-mod-2.zip");
+
+        let filename = fit_file_name(directory, &"x".repeat(300), "-12");
+        assert_eq!(filename.len(), MAX_FILE_NAME_LEN);
+        assert!(filename.ends_with("x-12.zip"));
+
+        // The directory alone is too long, but the stem keeps a character.
+        let directory = PathBuf::from("/".to_string() + &"d".repeat(MAX_PATH_LEN));
+        assert_eq!(fit_file_name(&directory, "Äbc", ""), "Ä.zip");
+    }
+
+    #[test]
+    fn test_resolve_destination_collision_appends_counter() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        // The archive contains the manifest of "test-mod", not "Other".
+        fs::copy("./test/test-mod.zip", temp_dir.path().join("Other.zip")).unwrap();
+        fs::copy("./test/test-mod.zip", temp_dir.path().join("Other-2.zip")).unwrap();
+
+        let destination = resolve_destination(temp_dir.path(), "Other", "Other");
+        assert_eq!(destination, temp_dir.path().join("Other-3.zip"));
+    }
+}
+
 #[cfg(test)]
 mod tests_sanitize {
     use super::*;
@@ -76,5 +241,8 @@ mod tests_sanitize {
     fn test_sanitize_long_name() {
         let long_name = "a".repeat(300);
         assert_eq!(sanitize(&long_name).len(), 255);
+        // Never splits a multi-byte character.
+        let long_name = "Ä".repeat(200);
+        assert_eq!(sanitize(&long_name).len(), 254);
     }
 }
@@ -0,0 +1,169 @@
+//! Failure modes of a command, which decide the exit code of the application.
+//!
+//! The commands return [`anyhow::Error`]s with context for the log. [`CliError::classify`]
+//! sorts them by the first recognized error of their chain, so that known failures can be
+//! told apart without parsing the messages.
+use std::{error::Error, io};
+
+use crate::{
+    cli::ModPageUrlError, download::DownloadError, local_mod::LoadModsError,
+    manifest::ManifestParseError, pack::PackError, zip::ZipError,
+};
+
+/// A failed command, by failure mode.
+///
+/// Each variant keeps the whole error, so it is displayed and logged as before.
+#[derive(Debug, thiserror::Error)]
+pub enum CliError {
+    /// The command line was valid, but what it asks for doesn't make sense.
+    #[error(transparent)]
+    Usage(anyhow::Error),
+    /// A request failed, or no mirror could serve a file.
+    #[error(transparent)]
+    Network(anyhow::Error),
+    /// A file doesn't match the checksums of the registry, or its signature is invalid.
+    #[error(transparent)]
+    Checksum(anyhow::Error),
+    /// The requested mod is not in the registry.
+    #[error(transparent)]
+    NotFound(anyhow::Error),
+    /// A manifest, an archive, a pack or a remote file could not be parsed.
+    #[error(transparent)]
+    Parse(anyhow::Error),
+    /// A local file could not be read or written.
+    #[error(transparent)]
+    Io(anyhow::Error),
+    /// Any other failure.
+    #[error(transparent)]
+    Other(anyhow::Error),
+}
+
+impl CliError {
+    /// Sorts the error by the first error of its chain whose failure mode is known.
+    pub fn classify(err: anyhow::Error) -> Self {
+        match err.chain().find_map(variant_of) {
+            Some(variant) => variant(err),
+            None => CliError::Other(err),
+        }
+    }
+
+    /// Returns the exit code of the application for this error.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            CliError::Other(_) => 1,
+            CliError::Usage(_) => 2,
+            CliError::Network(_) => 3,
+            CliError::Checksum(_) => 4,
+            CliError::NotFound(_) => 5,
+            CliError::Parse(_) => 6,
+            CliError::Io(_) => 7,
+        }
+    }
+
+    /// Returns a hint printed after the error message, if there is one for the failure mode.
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            CliError::Usage(_) => Some("Run with --help to see the usage."),
+            CliError::Network(_) => {
+                Some("Check the network connection, or try other mirrors with --mirror-priority.")
+            }
+            CliError::Checksum(_) => {
+                Some("The file may be corrupted or outdated on the mirror, try again later.")
+            }
+            CliError::NotFound(_) => Some("Search the registry with the `search` command."),
+            CliError::Parse(_) | CliError::Io(_) | CliError::Other(_) => None,
+        }
+    }
+}
+
+impl From<anyhow::Error> for CliError {
+    fn from(err: anyhow::Error) -> Self {
+        Self::classify(err)
+    }
+}
+
+/// Returns the variant for the failure mode of a single error, if it is known.
+fn variant_of(cause: &(dyn Error + 'static)) -> Option<fn(anyhow::Error) -> CliError> {
+    if let Some(err) = cause.downcast_ref::<CliError>() {
+        return Some(match err {
+            CliError::Usage(_) => CliError::Usage,
+            CliError::Network(_) => CliError::Network,
+            CliError::Checksum(_) => CliError::Checksum,
+            CliError::NotFound(_) => CliError::NotFound,
+            CliError::Parse(_) => CliError::Parse,
+            CliError::Io(_) => CliError::Io,
+            CliError::Other(_) => CliError::Other,
+        });
+    }
+    if let Some(err) = cause.downcast_ref::<DownloadError>() {
+        return Some(match err {
+            DownloadError::InvalidChecksum { .. } | DownloadError::InvalidSignature(_) => {
+                CliError::Checksum
+            }
+            DownloadError::InsecureUrl(_) => CliError::Usage,
+            DownloadError::Stalled(_)
+            | DownloadError::AllMirrorsFailed(_)
+            | DownloadError::Aborted(_) => CliError::Network,
+        });
+    }
+    if cause.is::<reqwest::Error>() {
+        return Some(CliError::Network);
+    }
+    if cause.is::<ModPageUrlError>() {
+        return Some(CliError::Usage);
+    }
+    if cause.is::<ManifestParseError>()
+        || cause.is::<ZipError>()
+        || cause.is::<PackError>()
+        || cause.is::<serde_yaml_ng::Error>()
+        || cause.is::<serde_json::Error>()
+    {
+        return Some(CliError::Parse);
+    }
+    if let Some(err) = cause.downcast_ref::<LoadModsError>() {
+        return Some(match err {
+            LoadModsError::Io(_) => CliError::Io,
+            LoadModsError::Manifest(_) | LoadModsError::Zip(_) => CliError::Parse,
+        });
+    }
+    if cause.is::<io::Error>() {
+        return Some(CliError::Io);
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests_error {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn test_classify_by_the_chain() {
+        let err = anyhow::Error::from(DownloadError::AllMirrorsFailed("Mod".to_string()))
+            .context("Failed to install [Mod]");
+        let err = CliError::classify(err);
+        assert!(matches!(err, CliError::Network(_)));
+        assert_eq!(err.exit_code(), 3);
+        // The message and its context are kept.
+        assert_eq!(err.to_string(), "Failed to install [Mod]");
+
+        let err: Result<(), _> = Err(io::Error::from(io::ErrorKind::PermissionDenied));
+        let err = CliError::classify(err.context("Failed to write").unwrap_err());
+        assert!(matches!(err, CliError::Io(_)));
+
+        let err = CliError::classify(anyhow::anyhow!("something else"));
+        assert!(matches!(err, CliError::Other(_)));
+        assert_eq!(err.exit_code(), 1);
+    }
+
+    #[test]
+    fn test_classify_explicit_variant() {
+        let err = anyhow::Error::from(CliError::NotFound(anyhow::anyhow!(
+            "[Mod] is not in the mod registry"
+        )))
+        .context("Failed to check the file");
+        let err = CliError::classify(err);
+        assert!(matches!(err, CliError::NotFound(_)));
+        assert_eq!(err.exit_code(), 5);
+    }
+}
@@ -0,0 +1,66 @@
+//! Detection of the Everest version installed in the Celeste directory.
+//!
+//! Everest injects a type named `EverestBuild<N>` into the game assembly, where `N` is the
+//! build number, and reports its version as `1.<N>.0`. This is how Olympus finds it too.
+use std::{fs, path::Path};
+
+/// Game assemblies that may contain the Everest build number, newest layout first.
+const GAME_ASSEMBLIES: [&str; 2] = ["Celeste.dll", "Celeste.exe"];
+
+const BUILD_MARKER: &[u8] = b"EverestBuild";
+
+/// Returns the version of Everest installed in the Celeste directory containing the mods
+/// directory, or `None` if it could not be determined.
+pub fn detect_installed_version(mods_directory: &Path) -> Option<String> {
+    let game_directory = mods_directory.parent()?;
+    GAME_ASSEMBLIES.iter().find_map(|assembly| {
+        let path = game_directory.join(assembly);
+        let bytes = fs::read(&path).ok()?;
+        let build = find_build_number(&bytes);
+        tracing::debug!("Everest build in '{}': {:?}", path.display(), build);
+        build.map(|build| format!("1.{build}.0"))
+    })
+}
+
+/// Finds the build number in the `EverestBuild<N>` type name of the assembly bytes.
+fn find_build_number(bytes: &[u8]) -> Option<u32> {
+    bytes
+        .windows(BUILD_MARKER.len())
+        .enumerate()
+        .filter(|(_, window)| *window == BUILD_MARKER)
+        .find_map(|(start, _)| {
+            let digits: String = bytes[start + BUILD_MARKER.len()..]
+                .iter()
+                .take_while(|byte| byte.is_ascii_digit())
+                .map(|&byte| char::from(byte))
+                .collect();
+            digits.parse().ok()
+        })
+}
+
+#[cfg(test)]
+mod tests_everest {
+    use super::*;
+
+    #[test]
+    fn test_find_build_number() {
+        let bytes = b"\0\0Celeste\0EverestBuild\0EverestBuild4465\0Monocle\0";
+        assert_eq!(find_build_number(bytes), Some(4465));
+        assert_eq!(find_build_number(b"\0Celeste\0Monocle\0"), None);
+    }
+
+    #[test]
+    fn test_detect_installed_version() -> anyhow::Result<()> {
+        let game_dir = tempfile::tempdir()?;
+        let mods_dir = game_dir.path().join("Mods");
+        fs::create_dir(&mods_dir)?;
+        assert_eq!(detect_installed_version(&mods_dir), None);
+
+        fs::write(game_dir.path().join("Celeste.dll"), b"\0EverestBuild5400\0")?;
+        assert_eq!(
+            detect_installed_version(&mods_dir).as_deref(),
+            Some("1.5400.0")
+        );
+        Ok(())
+    }
+}
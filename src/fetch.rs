@@ -1,49 +1,456 @@
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use reqwest::Client;
 use serde::de::DeserializeOwned;
 
 use crate::{
+    bench, cache,
+    config::Config,
+    constant::{
+        MOD_DEPENDENCY_GRAPH, MOD_REGISTRY_URL, REGISTRY_FETCH_ATTEMPTS, REGISTRY_RETRY_DELAY_SECS,
+        TCP_KEEPALIVE_SECS,
+    },
     dependency::{DependencyGraph, DependencyInfo, ModDependencyQuery},
     mod_registry::{ModRegistryQuery, RemoteModInfo, RemoteModRegistry},
 };
 
+/// Builds the HTTP client shared by every request of the application.
+///
+/// Idle connections are pooled per host so that concurrent downloads from the same mirror
+/// reuse warm connections instead of paying for a new TCP and TLS handshake per file.
+/// Only https URLs are accepted, see [`download_client`] for `--allow-insecure-download`.
+pub fn build_client(config: &Config) -> Client {
+    client_builder(config)
+        .https_only(true)
+        .build()
+        .unwrap_or_default()
+}
+
+/// Builds an HTTP client for the commands that run without a [`Config`], such as `ping`.
+///
+/// Only the proxies of the environment are used, since the profile is not read.
+pub fn build_default_client() -> Client {
+    reqwest::ClientBuilder::new()
+        .use_rustls_tls()
+        .https_only(true)
+        .tcp_keepalive(Duration::from_secs(TCP_KEEPALIVE_SECS))
+        .build()
+        .unwrap_or_default()
+}
+
+/// Returns the client used for the download URLs of the mirrors.
+///
+/// With `--allow-insecure-download`, it also accepts http URLs. Only the download path relaxes
+/// https, since it checks each URL itself before sending a request.
+pub fn download_client(client: &Client, config: &Config) -> Client {
+    if config.allow_insecure_download() {
+        client_builder(config).build().unwrap_or_default()
+    } else {
+        client.clone()
+    }
+}
+
+fn client_builder(config: &Config) -> reqwest::ClientBuilder {
+    tracing::debug!(
+        "Connection pool: max idle per host = {}, idle timeout = {:?}",
+        config.pool_max_idle_per_host(),
+        config.pool_idle_timeout()
+    );
+    let mut builder = reqwest::ClientBuilder::new();
+    if let Some(url) = config.proxy() {
+        tracing::debug!("Proxy: {}", url);
+        // The proxy was validated when the config was created.
+        if let Ok(proxy) = reqwest::Proxy::all(url) {
+            builder = builder.proxy(proxy);
+        }
+    }
+    builder
+        .use_rustls_tls()
+        .gzip(true)
+        .pool_max_idle_per_host(config.pool_max_idle_per_host())
+        .pool_idle_timeout(config.pool_idle_timeout())
+        .tcp_keepalive(Duration::from_secs(TCP_KEEPALIVE_SECS))
+}
+
 /// Fetches the remote data from the given URL and parses it into the specified type.
+///
+/// A fresh cached copy is used instead when `--cache` is given, and fetched data is cached.
 pub async fn fetch_remote_data<T>(url: &str, client: &Client) -> Result<T>
 where
     T: DeserializeOwned,
 {
+    if let Some(data) = cache::read(url) {
+        return Ok(data);
+    }
+
     let response = client.get(url).send().await?.error_for_status()?;
     tracing::info!("'{}' -> Status: {}", url, response.status());
 
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
     let bytes = response.bytes().await?;
-    let data = serde_yaml_ng::from_slice::<T>(&bytes)?;
+    let data = parse_remote_data::<T>(url, content_type.as_deref(), &bytes)?;
+    cache::write(url, &bytes);
 
     Ok(data)
 }
 
+/// Parses the body of a response as YAML.
+///
+/// # Errors
+///
+/// Returns an error if the body is an HTML page, which some mirrors and CDNs serve with a 200
+/// status during outages, or if it isn't valid YAML.
+fn parse_remote_data<T>(url: &str, content_type: Option<&str>, bytes: &[u8]) -> Result<T>
+where
+    T: DeserializeOwned,
+{
+    let is_html_type = content_type.is_some_and(|content_type| {
+        content_type
+            .trim_start()
+            .to_ascii_lowercase()
+            .starts_with("text/html")
+    });
+    let body = bytes.strip_prefix(b"\xef\xbb\xbf").unwrap_or(bytes);
+    if is_html_type || body.trim_ascii_start().starts_with(b"<") {
+        tracing::error!(
+            "'{}' returned an HTML page (Content-Type: {:?}): {}",
+            url,
+            content_type,
+            String::from_utf8_lossy(&body[..body.len().min(200)])
+        );
+        anyhow::bail!(
+            "registry endpoint '{url}' returned an HTML page instead of YAML (server may be down)"
+        );
+    }
+    serde_yaml_ng::from_slice::<T>(body)
+        .with_context(|| format!("Failed to parse the YAML returned by '{url}'"))
+}
+
+/// Runs the fetches of the mod registry and the dependency graph concurrently, naming the
+/// database that could not be fetched in the error.
+///
+/// A failure of the registry drops the graph fetch, while a failure of the graph is returned
+/// alongside the registry.
+async fn join_database_fetches<R, G>(
+    registry: impl Future<Output = Result<R>>,
+    graph: impl Future<Output = Result<G>>,
+) -> Result<(R, Result<G>)> {
+    tokio::try_join!(
+        async { registry.await.context("Failed to fetch the mod registry") },
+        async {
+            let graph = graph.await.context("Failed to fetch the dependency graph");
+            // NOTE: The graph error is kept, so that it doesn't cancel the registry fetch.
+            Ok::<_, anyhow::Error>(graph)
+        }
+    )
+}
+
+/// Runs the operation until it succeeds, at most `attempts` times.
+///
+/// The delay between attempts starts at `initial_delay` and doubles after every failure.
+/// `on_retry` is called with the number of the next attempt and the error before waiting.
+///
+/// # Errors
+///
+/// Returns the error of the last attempt if every attempt fails.
+pub async fn retry_with_backoff<T, F, Fut>(
+    attempts: usize,
+    initial_delay: Duration,
+    mut operation: F,
+    mut on_retry: impl FnMut(usize, &anyhow::Error),
+) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let mut delay = initial_delay;
+    let mut attempt = 1;
+    loop {
+        match operation().await {
+            Ok(value) => return Ok(value),
+            Err(err) if attempt < attempts => {
+                tracing::warn!("Attempt {} failed: {:#}", attempt, err);
+                attempt += 1;
+                on_retry(attempt, &err);
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
 /// Fetches online database.
+///
+/// Both requests run concurrently, and each one is retried on its own, so a failure of one
+/// doesn't fetch the other one again. Once the registry runs out of attempts, the graph request
+/// is dropped, which cancels it and frees the connection.
+///
+/// # Errors
+///
+/// Returns an error naming the database that could not be fetched.
 pub async fn fetch_online_database(
     client: &Client,
 ) -> Result<(
     HashMap<String, RemoteModInfo>,
     HashMap<String, DependencyInfo>,
+)> {
+    let (mod_registry, dependency_graph) = fetch_registry_and_graph(client).await?;
+    Ok((mod_registry, dependency_graph?))
+}
+
+/// Fetches online database like [`fetch_online_database`], but only fails if the mod registry
+/// could not be fetched.
+///
+/// A failure of the dependency graph is returned alongside the registry, so that the caller
+/// can carry on without dependency resolution.
+///
+/// # Errors
+///
+/// Returns an error if the mod registry could not be fetched.
+pub async fn fetch_registry_and_graph(
+    client: &Client,
+) -> Result<(
+    HashMap<String, RemoteModInfo>,
+    Result<HashMap<String, DependencyInfo>>,
 )> {
     tracing::info!("Fetching mod registry and dependency graph from remote server...");
-    let spinner = crate::download::pb_style::create_spinner();
-    let (mod_registry, dependency_graph) = tokio::try_join!(
-        RemoteModRegistry::fetch(client),
-        DependencyGraph::fetch(client)
-    )?;
+    let spinner = crate::download::pb_style::create_spinner("Fetching online database...");
+    let started = Instant::now();
+    let on_retry = |component: &str, attempt: usize, err: &anyhow::Error| {
+        spinner.suspend(|| println!("⚠️  {err}, retrying {component} fetch (attempt {attempt})"))
+    };
+    let fetched = join_database_fetches(
+        async {
+            let registry = retry_with_backoff(
+                REGISTRY_FETCH_ATTEMPTS,
+                Duration::from_secs(REGISTRY_RETRY_DELAY_SECS),
+                || RemoteModRegistry::fetch(client),
+                |attempt, err| on_retry("registry", attempt, err),
+            )
+            .await;
+            bench::record("registry fetch", started);
+            registry
+        },
+        async {
+            let graph = retry_with_backoff(
+                REGISTRY_FETCH_ATTEMPTS,
+                Duration::from_secs(REGISTRY_RETRY_DELAY_SECS),
+                || DependencyGraph::fetch(client),
+                |attempt, err| on_retry("dependency graph", attempt, err),
+            )
+            .await;
+            bench::record("dependency graph fetch", started);
+            graph
+        },
+    )
+    .await;
     spinner.finish_and_clear();
+    let (mod_registry, dependency_graph) = fetched?;
+
+    if cache::is_enabled() {
+        let source = |url| {
+            if cache::served_from_cache(url) {
+                "from cache"
+            } else {
+                "freshly fetched"
+            }
+        };
+        println!(
+            "Mod registry: {}, dependency graph: {}",
+            source(MOD_REGISTRY_URL),
+            source(MOD_DEPENDENCY_GRAPH)
+        );
+    }
 
-    tracing::info!("Successfully fetched mod registry and dependency graph");
     tracing::debug!("Fetched mod registry with {} entries", mod_registry.len());
-    tracing::debug!(
-        "Fetched dependency graph with {} entries",
-        dependency_graph.len()
-    );
+    match &dependency_graph {
+        Ok(dependency_graph) => {
+            tracing::info!("Successfully fetched mod registry and dependency graph");
+            tracing::debug!(
+                "Fetched dependency graph with {} entries",
+                dependency_graph.len()
+            );
+        }
+        Err(err) => tracing::warn!("Fetched the mod registry only: {:#}", err),
+    }
 
     Ok((mod_registry, dependency_graph))
 }
+
+#[cfg(test)]
+mod tests_parse_remote_data {
+    use super::*;
+
+    const URL: &str = "https://maddie480.ovh/celeste/everest_update.yaml";
+
+    #[test]
+    fn test_html_error_page_is_reported() {
+        let html = b"\n<!DOCTYPE html><html><body>502 Bad Gateway</body></html>";
+        for content_type in [Some("text/html; charset=UTF-8"), Some("text/yaml"), None] {
+            let err =
+                parse_remote_data::<HashMap<String, String>>(URL, content_type, html).unwrap_err();
+            assert!(
+                err.to_string()
+                    .contains("returned an HTML page instead of YAML"),
+                "{err}"
+            );
+            assert!(err.to_string().contains(URL));
+        }
+    }
+
+    #[test]
+    fn test_yaml_body_is_parsed() {
+        let data: HashMap<String, String> =
+            parse_remote_data(URL, Some("text/yaml"), b"SpeedrunTool: 3.24.0\n").unwrap();
+        assert_eq!(data["SpeedrunTool"], "3.24.0");
+    }
+}
+
+#[cfg(test)]
+mod tests_retry {
+    use super::*;
+    use std::cell::Cell;
+
+    #[tokio::test]
+    async fn test_retry_with_backoff_succeeds_after_failures() -> Result<()> {
+        let calls = Cell::new(0);
+        let mut retries = Vec::new();
+        let value = retry_with_backoff(
+            3,
+            Duration::ZERO,
+            || async {
+                calls.set(calls.get() + 1);
+                if calls.get() < 3 {
+                    anyhow::bail!("transient");
+                }
+                Ok(calls.get())
+            },
+            |attempt, _| retries.push(attempt),
+        )
+        .await?;
+        assert_eq!(value, 3);
+        assert_eq!(retries, [2, 3]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_join_database_fetches_names_the_failed_database() -> Result<()> {
+        // The graph fetch never finishes, so it must be dropped once the registry fails.
+        let err = join_database_fetches(
+            async { Err::<(), _>(anyhow::anyhow!("502 Bad Gateway")) },
+            std::future::pending::<Result<()>>(),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(
+            format!("{err:#}"),
+            "Failed to fetch the mod registry: 502 Bad Gateway"
+        );
+
+        let (registry, graph) = join_database_fetches(async { Ok(1) }, async {
+            Err::<(), _>(anyhow::anyhow!("timed out"))
+        })
+        .await?;
+        assert_eq!(registry, 1);
+        assert_eq!(
+            format!("{:#}", graph.unwrap_err()),
+            "Failed to fetch the dependency graph: timed out"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_retry_with_backoff_gives_up() {
+        let calls = Cell::new(0);
+        let result: Result<()> = retry_with_backoff(
+            2,
+            Duration::ZERO,
+            || async {
+                calls.set(calls.get() + 1);
+                anyhow::bail!("down")
+            },
+            |_, _| {},
+        )
+        .await;
+        assert!(result.is_err());
+        assert_eq!(calls.get(), 2);
+    }
+}
+
+#[cfg(test)]
+mod tests_connection_pool {
+    use super::*;
+    use crate::cli::Cli;
+    use clap::Parser;
+    use std::sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    };
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    /// Serves keep-alive responses on a local port, returns its URL and the number of
+    /// connections accepted so far.
+    async fn serve() -> Result<(String, Arc<AtomicUsize>)> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://{}/", listener.local_addr()?);
+        let connections = Arc::new(AtomicUsize::new(0));
+        let accepted = Arc::clone(&connections);
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                accepted.fetch_add(1, Ordering::SeqCst);
+                tokio::spawn(async move {
+                    let mut request = [0u8; 1024];
+                    while matches!(stream.read(&mut request).await, Ok(n) if n > 0) {
+                        let response = b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok";
+                        if stream.write_all(response).await.is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+        Ok((url, connections))
+    }
+
+    /// Sends sequential requests with the client built for the given options, returns the
+    /// number of connections it opened.
+    async fn connections_for(options: &[&str], requests: usize) -> Result<usize> {
+        let temp_dir = tempfile::tempdir()?;
+        let mods_dir = temp_dir.path().to_string_lossy().into_owned();
+        let args = ["everest-mod-cli", "--mods-dir", &mods_dir]
+            .into_iter()
+            .chain(["--allow-insecure-download"])
+            .chain(options.iter().copied())
+            .chain(["list"]);
+        let config = Config::new(&Cli::try_parse_from(args)?)?;
+        // The local server is plain http, which only the download client accepts.
+        let client = download_client(&build_client(&config), &config);
+
+        let (url, connections) = serve().await?;
+        for _ in 0..requests {
+            client.get(&url).send().await?.bytes().await?;
+        }
+        Ok(connections.load(Ordering::SeqCst))
+    }
+
+    #[tokio::test]
+    async fn test_pool_reuses_connections() -> Result<()> {
+        // Every request after the first one skips the TCP (and, on a mirror, TLS) handshake.
+        assert_eq!(connections_for(&[], 20).await?, 1);
+        // Without idle connections, each request opens a new one.
+        assert_eq!(connections_for(&["--pool-max-idle", "0"], 20).await?, 20);
+        Ok(())
+    }
+}
@@ -2,7 +2,7 @@ use std::{
     borrow::Cow,
     env,
     fs::File,
-    io::{self, BufReader, Read},
+    io::{self, BufReader, Read, Write},
     path::Path,
 };
 
@@ -23,28 +23,199 @@ pub fn replace_home_dir_with_tilde(destination: &Path) -> Cow<'_, str> {
     }
 }
 
+/// Checksum algorithms supported by [`hash_file_with`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ChecksumAlgorithm {
+    /// xxHash64, used by the mod registry
+    Xxh64,
+    /// CRC32, as shown by archive tools
+    Crc32,
+}
+
+impl ChecksumAlgorithm {
+    /// Human-readable name of the algorithm
+    pub fn label(self) -> &'static str {
+        match self {
+            ChecksumAlgorithm::Xxh64 => "xxHash64",
+            ChecksumAlgorithm::Crc32 => "CRC32",
+        }
+    }
+}
+
+/// A running checksum of one of the [`ChecksumAlgorithm`]s.
+enum Hasher {
+    Xxh64(Xxh64),
+    Crc32(crc32fast::Hasher),
+}
+
+impl Hasher {
+    fn new(algorithm: ChecksumAlgorithm) -> Self {
+        match algorithm {
+            ChecksumAlgorithm::Xxh64 => Hasher::Xxh64(Xxh64::new(0)),
+            ChecksumAlgorithm::Crc32 => Hasher::Crc32(crc32fast::Hasher::new()),
+        }
+    }
+
+    fn update(&mut self, bytes: &[u8]) {
+        match self {
+            Hasher::Xxh64(hasher) => hasher.update(bytes),
+            Hasher::Crc32(hasher) => hasher.update(bytes),
+        }
+    }
+
+    /// Returns the checksum as a zero-padded hexadecimal string.
+    fn finish(self) -> String {
+        match self {
+            Hasher::Xxh64(hasher) => format!("{:016x}", hasher.digest()),
+            Hasher::Crc32(hasher) => format!("{:08x}", hasher.finalize()),
+        }
+    }
+}
+
 /// Computes the xxhash of a given file and returns it as a hexadecimal string.
 pub fn hash_file(file_path: &Path) -> io::Result<String> {
+    let mut hashes = hash_file_with(file_path, &[ChecksumAlgorithm::Xxh64])?;
+    Ok(hashes.remove(0))
+}
+
+/// Computes the checksums of a given file with every algorithm in a single read.
+///
+/// Returns the checksums as hexadecimal strings, in the order of `algorithms`.
+pub fn hash_file_with(
+    file_path: &Path,
+    algorithms: &[ChecksumAlgorithm],
+) -> io::Result<Vec<String>> {
     let file = File::open(file_path)?;
     let mut reader = BufReader::new(file);
-    let mut hasher = Xxh64::new(0);
+    let mut hashers: Vec<Hasher> = algorithms.iter().copied().map(Hasher::new).collect();
     let mut buffer = [0u8; 64 * 1024]; // Read in 64 KB chunks
     loop {
         let bytes_read = reader.read(&mut buffer)?;
         if bytes_read == 0 {
             break;
         }
-        hasher.update(&buffer[..bytes_read]);
+        for hasher in &mut hashers {
+            hasher.update(&buffer[..bytes_read]);
+        }
+    }
+    Ok(hashers.into_iter().map(Hasher::finish).collect())
+}
+
+/// Writes the contents to the file so that it is either fully replaced or left untouched.
+///
+/// The contents are written to a temporary file next to the target, which is then renamed
+/// over it. An interrupted write leaves at most a stray temporary file, never a truncated target.
+///
+/// # Errors
+///
+/// Returns an error if the temporary file can't be written or renamed.
+pub fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    write_atomic_with(path, |file| file.write_all(contents.as_ref()))
+}
+
+/// Copies a file to the given path atomically, like [`write_atomic`].
+///
+/// # Errors
+///
+/// Returns an error if the source can't be read or the temporary file can't be written or
+/// renamed.
+pub fn copy_atomic(from: &Path, to: &Path) -> io::Result<()> {
+    let mut source = File::open(from)?;
+    write_atomic_with(to, |file| io::copy(&mut source, file).map(|_| ()))
+}
+
+fn write_atomic_with(
+    path: &Path,
+    write: impl FnOnce(&mut File) -> io::Result<()>,
+) -> io::Result<()> {
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    // NOTE: The temporary file is removed on drop if anything fails before it is persisted.
+    let mut temp_file = tempfile::NamedTempFile::new_in(parent)?;
+    write(temp_file.as_file_mut())?;
+    temp_file.as_file().sync_all()?;
+    temp_file.persist(path).map_err(|err| err.error)?;
+    Ok(())
+}
+
+/// Checks whether the given path, relative to a scanned directory, matches any of the
+/// gitignore-style patterns.
+///
+/// - A pattern without a slash matches the file name or any directory component.
+/// - A pattern containing a slash is matched against the whole relative path.
+/// - A trailing slash restricts the pattern to directories (e.g., `Cache/`).
+/// - `*` matches anything except `/`, `**` matches across directories and `?` matches one character.
+pub fn is_ignored(relative_path: &Path, patterns: &[String]) -> bool {
+    let components: Vec<String> = relative_path
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    let Some((file_name, directories)) = components.split_last() else {
+        return false;
+    };
+    let joined = components.join("/");
+
+    patterns.iter().any(|pattern| {
+        let pattern = pattern.trim();
+        if pattern.is_empty() || pattern.starts_with('#') {
+            return false;
+        }
+
+        if let Some(dir_pattern) = pattern.strip_suffix('/') {
+            let dir_pattern = dir_pattern.trim_start_matches('/');
+            return if dir_pattern.contains('/') {
+                (1..=directories.len())
+                    .any(|n| glob_match(dir_pattern, &directories[..n].join("/")))
+            } else {
+                directories.iter().any(|dir| glob_match(dir_pattern, dir))
+            };
+        }
+
+        if pattern.contains('/') {
+            glob_match(pattern.trim_start_matches('/'), &joined)
+        } else {
+            glob_match(pattern, file_name) || directories.iter().any(|dir| glob_match(pattern, dir))
+        }
+    })
+}
+
+/// Matches a text against a glob pattern supporting `*`, `**` and `?`.
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    glob_match_from(&pattern, &text)
+}
+
+fn glob_match_from(pattern: &[char], text: &[char]) -> bool {
+    match pattern.split_first() {
+        None => text.is_empty(),
+        Some(('*', rest)) if rest.first() == Some(&'*') => {
+            let rest = &rest[1..];
+            // `**/` may also match zero directories.
+            if let Some(after_slash) = rest.strip_prefix(&['/'][..])
+                && glob_match_from(after_slash, text)
+            {
+                return true;
+            }
+            (0..=text.len()).any(|i| glob_match_from(rest, &text[i..]))
+        }
+        Some(('*', rest)) => (0..=text.len())
+            .take_while(|&i| i == 0 || text[i - 1] != '/')
+            .any(|i| glob_match_from(rest, &text[i..])),
+        Some(('?', rest)) => {
+            !text.is_empty() && text[0] != '/' && glob_match_from(rest, &text[1..])
+        }
+        Some((c, rest)) => text.first() == Some(c) && glob_match_from(rest, &text[1..]),
     }
-    let hash_str = format!("{:016x}", hasher.digest());
-    Ok(hash_str)
 }
 
 #[cfg(test)]
 mod tests_fileutil {
     use super::*;
 
-    use std::io::Write;
+    use std::fs;
     use tempfile::NamedTempFile;
 
     #[test]
@@ -60,6 +231,41 @@ mod tests_fileutil {
         assert_eq!(replace_home_dir_with_tilde(path), "/etc/config.txt");
     }
 
+    #[test]
+    fn test_is_ignored_file_name_pattern() {
+        let patterns = vec!["*.bak.zip".to_string(), "Backup?.zip".to_string()];
+        assert!(is_ignored(Path::new("Mod.bak.zip"), &patterns));
+        assert!(is_ignored(Path::new("Backup1.zip"), &patterns));
+        assert!(is_ignored(Path::new("staging/Mod.bak.zip"), &patterns));
+        assert!(!is_ignored(Path::new("Mod.zip"), &patterns));
+        assert!(!is_ignored(Path::new("Backup12.zip"), &patterns));
+    }
+
+    #[test]
+    fn test_is_ignored_relative_path_pattern() {
+        let patterns = vec!["staging/*.zip".to_string()];
+        assert!(is_ignored(Path::new("staging/Mod.zip"), &patterns));
+        assert!(!is_ignored(Path::new("staging/nested/Mod.zip"), &patterns));
+        assert!(!is_ignored(Path::new("Mod.zip"), &patterns));
+
+        let patterns = vec!["staging/**/*.zip".to_string()];
+        assert!(is_ignored(Path::new("staging/Mod.zip"), &patterns));
+        assert!(is_ignored(Path::new("staging/nested/Mod.zip"), &patterns));
+    }
+
+    #[test]
+    fn test_is_ignored_directory_pattern() {
+        let patterns = vec!["Cache/".to_string()];
+        assert!(is_ignored(Path::new("Cache/Mod.zip"), &patterns));
+        assert!(!is_ignored(Path::new("Cache.zip"), &patterns));
+    }
+
+    #[test]
+    fn test_is_ignored_skips_comments_and_blank_patterns() {
+        let patterns = vec!["# *.zip".to_string(), "  ".to_string()];
+        assert!(!is_ignored(Path::new("Mod.zip"), &patterns));
+    }
+
     #[test]
     fn test_hash_file_success() {
         let temp_file = NamedTempFile::new().unwrap();
@@ -71,6 +277,49 @@ mod tests_fileutil {
         assert_eq!(result.unwrap().len(), 16); // Should return a valid 16-character hash
     }
 
+    #[test]
+    fn test_hash_file_with_multiple_algorithms() {
+        let temp_file = NamedTempFile::new().unwrap();
+        write!(temp_file.as_file(), "test data").unwrap();
+
+        let hashes = hash_file_with(
+            temp_file.path(),
+            &[ChecksumAlgorithm::Crc32, ChecksumAlgorithm::Xxh64],
+        )
+        .unwrap();
+
+        assert_eq!(hashes[0], format!("{:08x}", crc32fast::hash(b"test data")));
+        assert_eq!(hashes[1], hash_file(temp_file.path()).unwrap());
+    }
+
+    #[test]
+    fn test_write_atomic_replaces_contents() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("pins.json");
+        fs::write(&path, "old").unwrap();
+
+        write_atomic(&path, "new").unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+        assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_write_atomic_keeps_original_when_interrupted() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("pins.json");
+        fs::write(&path, "original").unwrap();
+
+        let result = write_atomic_with(&path, |file| {
+            file.write_all(b"partial")?;
+            Err(io::Error::new(io::ErrorKind::Interrupted, "interrupted"))
+        });
+
+        assert!(result.is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "original");
+        assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+    }
+
     #[test]
     fn test_hash_file_nonexistent() {
         let nonexistent_path = Path::new("nonexistent_file");
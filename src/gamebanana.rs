@@ -0,0 +1,288 @@
+//! The GameBanana API: live statistics of the mods shown by `search --enrich`, and the files of
+//! a mod page for `install --version`.
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+use futures_util::{StreamExt, stream};
+use indicatif::HumanCount;
+use reqwest::{Client, StatusCode};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    cache,
+    constant::{
+        GAMEBANANA_API_CACHE_TTL_SECS, GAMEBANANA_API_CONCURRENCY, GAMEBANANA_API_URL,
+        PING_TIMEOUT_SECS,
+    },
+};
+
+/// Statistics of a mod page on GameBanana.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ModStats {
+    #[serde(rename = "_nDownloadCount", default)]
+    pub downloads: u64,
+    #[serde(rename = "_nLikeCount", default)]
+    pub likes: u64,
+    #[serde(rename = "_aCategory", default)]
+    pub category: Option<Category>,
+}
+
+/// Category of a mod page on GameBanana.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Category {
+    #[serde(rename = "_sName")]
+    pub name: String,
+}
+
+impl fmt::Display for ModStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} downloads, {} likes",
+            HumanCount(self.downloads),
+            HumanCount(self.likes)
+        )?;
+        if let Some(category) = &self.category {
+            write!(f, ", category: {}", category.name)?;
+        }
+        Ok(())
+    }
+}
+
+/// Statistics fetched for a set of mods.
+#[derive(Debug, Default)]
+pub struct Enrichment {
+    /// Statistics by GameBanana ID. Mods that could not be fetched are missing.
+    pub stats: HashMap<u32, ModStats>,
+    /// Whether the API refused further requests because of its rate limit.
+    pub rate_limited: bool,
+}
+
+/// Fetches the statistics of the given mod pages, a few at a time.
+///
+/// Failures only leave the mod out of the result. Once the API answers with its rate limit,
+/// the remaining mods are not requested. With `--cache`, results are cached for a while.
+pub async fn fetch_stats(client: &Client, ids: &[u32]) -> Enrichment {
+    let rate_limited = AtomicBool::new(false);
+    let stats = stream::iter(ids.iter().copied().filter(|id| *id != 0))
+        .map(|id| {
+            let rate_limited = &rate_limited;
+            async move {
+                let cache_name = format!("gamebanana-{id}.json");
+                let cache_ttl = Duration::from_secs(GAMEBANANA_API_CACHE_TTL_SECS);
+                if let Some(stats) = cache::read_entry(&cache_name, cache_ttl) {
+                    return Some((id, stats));
+                }
+                if rate_limited.load(Ordering::Relaxed) {
+                    return None;
+                }
+                match fetch_mod_stats(client, id).await {
+                    Ok(stats) => {
+                        if let Ok(bytes) = serde_json::to_vec(&stats) {
+                            cache::write_entry(&cache_name, &bytes);
+                        }
+                        Some((id, stats))
+                    }
+                    Err(err) => {
+                        tracing::warn!("Failed to fetch the GameBanana stats of [{}]: {}", id, err);
+                        let status = err
+                            .downcast_ref::<reqwest::Error>()
+                            .and_then(reqwest::Error::status);
+                        if status == Some(StatusCode::TOO_MANY_REQUESTS) {
+                            rate_limited.store(true, Ordering::Relaxed);
+                        }
+                        None
+                    }
+                }
+            }
+        })
+        .buffer_unordered(GAMEBANANA_API_CONCURRENCY)
+        .filter_map(|result| async move { result })
+        .collect()
+        .await;
+
+    Enrichment {
+        stats,
+        rate_limited: rate_limited.into_inner(),
+    }
+}
+
+async fn fetch_mod_stats(client: &Client, id: u32) -> anyhow::Result<ModStats> {
+    let url = format!(
+        "{GAMEBANANA_API_URL}Mod/{id}?_csvProperties=_nDownloadCount,_nLikeCount,_aCategory"
+    );
+    let bytes = client
+        .get(&url)
+        .timeout(Duration::from_secs(PING_TIMEOUT_SECS))
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+    Ok(serde_json::from_slice(&bytes)?)
+}
+
+/// A file uploaded to a mod page on GameBanana.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct ModFile {
+    #[serde(rename = "_sFile", default)]
+    pub name: String,
+    #[serde(rename = "_nFilesize", default)]
+    pub size: u64,
+    #[serde(rename = "_sDownloadUrl", default)]
+    pub download_url: String,
+    #[serde(rename = "_sVersion", default)]
+    pub version: Option<String>,
+}
+
+/// The files of a mod page, current and archived.
+#[derive(Debug, Default, Deserialize)]
+struct ProfilePage {
+    #[serde(rename = "_aFiles", default)]
+    files: Vec<ModFile>,
+    #[serde(rename = "_aArchivedFiles", default)]
+    archived_files: Vec<ModFile>,
+}
+
+/// Fetches every file of a mod page, including the archived ones.
+///
+/// # Errors
+///
+/// Returns an error if the request fails or the response can't be parsed.
+pub async fn fetch_files(client: &Client, id: u32) -> anyhow::Result<Vec<ModFile>> {
+    let url = format!("{GAMEBANANA_API_URL}Mod/{id}/ProfilePage");
+    let bytes = client
+        .get(&url)
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+    let page: ProfilePage = serde_json::from_slice(&bytes)?;
+    Ok(page.files.into_iter().chain(page.archived_files).collect())
+}
+
+/// Finds the file of the given version among the files of a mod page.
+///
+/// The version given by the uploader is used first. Files without it are matched by their
+/// name, e.g. `SpeedrunTool_v3.20.0.zip` for `3.20.0`.
+///
+/// # Errors
+///
+/// Returns an error listing the known versions if no file matches, or the matching files if
+/// several do.
+pub fn find_file<'a>(files: &'a [ModFile], version: &str) -> anyhow::Result<&'a ModFile> {
+    let version = version.trim().trim_start_matches('v');
+    let by_version: Vec<_> = files
+        .iter()
+        .filter(|file| {
+            file.version
+                .as_deref()
+                .is_some_and(|candidate| candidate.trim().trim_start_matches('v') == version)
+        })
+        .collect();
+    let matches = if by_version.is_empty() {
+        files
+            .iter()
+            .filter(|file| file.version.is_none() && name_contains_version(&file.name, version))
+            .collect()
+    } else {
+        by_version
+    };
+
+    match matches[..] {
+        [file] => Ok(file),
+        [] => {
+            let known: Vec<&str> = files
+                .iter()
+                .filter_map(|file| file.version.as_deref())
+                .collect();
+            if known.is_empty() {
+                anyhow::bail!("no file of version {version} is found on the mod page")
+            }
+            anyhow::bail!(
+                "no file of version {version} is found on the mod page, known versions: {}",
+                known.join(", ")
+            )
+        }
+        _ => anyhow::bail!(
+            "several files match version {version}: {}",
+            matches
+                .iter()
+                .map(|file| file.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    }
+}
+
+/// Returns whether the version appears in the file name, not as a part of a longer version.
+fn name_contains_version(name: &str, version: &str) -> bool {
+    let is_version_char = |c: char| c.is_ascii_digit() || c == '.';
+    name.match_indices(version).any(|(start, _)| {
+        let before = name[..start].chars().next_back();
+        let after = name[start + version.len()..]
+            .trim_start_matches(".zip")
+            .chars()
+            .next();
+        !before.is_some_and(is_version_char) && !after.is_some_and(is_version_char)
+    })
+}
+
+#[cfg(test)]
+mod tests_gamebanana {
+    use super::*;
+
+    #[test]
+    fn test_parse_mod_stats() -> anyhow::Result<()> {
+        let json = r#"{"_nDownloadCount": 123456, "_nLikeCount": 78, "_aCategory": {"_idRow": 6800, "_sName": "Helpers"}}"#;
+        let stats: ModStats = serde_json::from_str(json)?;
+        assert_eq!(
+            stats.to_string(),
+            "123,456 downloads, 78 likes, category: Helpers"
+        );
+
+        let stats: ModStats = serde_json::from_str(r#"{"_nDownloadCount": 5}"#)?;
+        assert_eq!(stats.to_string(), "5 downloads, 0 likes");
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_file() -> anyhow::Result<()> {
+        let json = r#"{
+            "_aFiles": [{"_sFile": "speedruntool_3250.zip", "_nFilesize": 2048, "_sDownloadUrl": "https://gamebanana.com/dl/3", "_sVersion": "3.25.0"}],
+            "_aArchivedFiles": [
+                {"_sFile": "speedruntool_3240.zip", "_nFilesize": 1024, "_sDownloadUrl": "https://gamebanana.com/dl/2", "_sVersion": "v3.24.0"},
+                {"_sFile": "SpeedrunTool_v3.20.10.zip", "_sDownloadUrl": "https://gamebanana.com/dl/1"},
+                {"_sFile": "SpeedrunTool_v3.20.1.zip", "_sDownloadUrl": "https://gamebanana.com/dl/0"}
+            ]
+        }"#;
+        let page: ProfilePage = serde_json::from_str(json)?;
+        let files: Vec<_> = page.files.into_iter().chain(page.archived_files).collect();
+
+        assert_eq!(
+            find_file(&files, "3.24.0")?.download_url,
+            "https://gamebanana.com/dl/2"
+        );
+        // Matched by name, without matching a longer version
+        assert_eq!(
+            find_file(&files, "3.20.1")?.download_url,
+            "https://gamebanana.com/dl/0"
+        );
+        assert_eq!(
+            find_file(&files, "v3.20.10")?.download_url,
+            "https://gamebanana.com/dl/1"
+        );
+
+        let err = find_file(&files, "3.20").unwrap_err().to_string();
+        assert_eq!(
+            err,
+            "no file of version 3.20 is found on the mod page, known versions: 3.25.0, v3.24.0"
+        );
+        Ok(())
+    }
+}
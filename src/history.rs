@@ -0,0 +1,208 @@
+//! Append-only history of the installed and updated mods, shown by the `history` subcommand.
+//!
+//! Each line of the history file is a JSON object, so the file can be appended to without
+//! reading it first.
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::{config, fileutil, report::Report};
+
+/// The name of the history file in the state directory.
+const HISTORY_FILE: &str = "history.jsonl";
+
+/// Kind of change made to the mods directory.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Action {
+    Install,
+    Update,
+}
+
+/// A single change recorded in the history.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct HistoryEntry {
+    /// Time of the change, in RFC 3339 format
+    pub timestamp: String,
+    pub action: Action,
+    /// Name of the mod
+    pub name: String,
+    /// Version of the mod after the change
+    pub version: String,
+    /// Version of the mod before the update
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_version: Option<String>,
+}
+
+impl std::fmt::Display for HistoryEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let action = match self.action {
+            Action::Install => "install",
+            Action::Update => "update",
+        };
+        write!(f, "{}  {:<8} {} ", self.timestamp, action, self.name)?;
+        match &self.previous_version {
+            Some(previous) => write!(f, "{previous} -> {}", self.version),
+            None => write!(f, "{}", self.version),
+        }
+    }
+}
+
+/// Returns the path of the history file in the state directory.
+pub fn history_path() -> Option<PathBuf> {
+    config::get_state_directory().map(|state_dir| state_dir.join(HISTORY_FILE))
+}
+
+/// Returns the changes recorded in the report as history entries.
+fn entries_from_report(report: &Report) -> Vec<HistoryEntry> {
+    let timestamp = report.timestamp();
+    let installs = report
+        .installed()
+        .iter()
+        .map(|record| (Action::Install, record));
+    let updates = report
+        .updated()
+        .iter()
+        .map(|record| (Action::Update, record));
+    installs
+        .chain(updates)
+        .map(|(action, record)| HistoryEntry {
+            timestamp: timestamp.to_string(),
+            action,
+            name: record.name.clone(),
+            version: record.version.clone(),
+            previous_version: record.previous_version.clone(),
+        })
+        .collect()
+}
+
+/// Appends the changes recorded in the report to the history file.
+///
+/// This is best-effort: failures are logged and never fail the command.
+pub fn record(report: &Report) {
+    let entries = entries_from_report(report);
+    if entries.is_empty() {
+        return;
+    }
+    let Some(path) = history_path() else {
+        tracing::warn!("Could not determine home directory, skipping the history");
+        return;
+    };
+    if let Err(e) = append(&path, &entries) {
+        tracing::warn!("Failed to record the history: {:#}", e);
+    }
+}
+
+/// Appends the entries to the history file, one JSON object per line.
+fn append(path: &Path, entries: &[HistoryEntry]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| {
+            format!(
+                "Failed to open history file '{}'",
+                fileutil::replace_home_dir_with_tilde(path)
+            )
+        })?;
+    let mut writer = BufWriter::new(file);
+    for entry in entries {
+        serde_json::to_writer(&mut writer, entry)?;
+        writeln!(writer)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Reads the history file, most recent entries first.
+///
+/// Lines that can't be parsed are skipped with a warning. Returns an empty vector if the file
+/// does not exist.
+///
+/// # Errors
+///
+/// Returns an error if the file exists but cannot be read.
+pub fn read(path: &Path) -> Result<Vec<HistoryEntry>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let reader = BufReader::new(File::open(path)?);
+
+    let mut entries = Vec::new();
+    for (line_number, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str(&line) {
+            Ok(entry) => entries.push(entry),
+            Err(e) => tracing::warn!("Skipping line {} of the history: {}", line_number + 1, e),
+        }
+    }
+    entries.reverse();
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests_history {
+    use super::*;
+    use crate::{download::DownloadSummary, mod_registry::RemoteModInfo};
+
+    fn remote_mod(version: &str) -> RemoteModInfo {
+        RemoteModInfo {
+            version: version.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_append_and_read_most_recent_first() -> anyhow::Result<()> {
+        let mut report = Report::new("update");
+        report.record_updates(
+            &DownloadSummary {
+                succeeded: vec![("A".to_string(), remote_mod("1.1.0"))],
+                ..Default::default()
+            },
+            |_| Some("1.0.0".to_string()),
+        );
+        let mut install = Report::new("install");
+        install.record_installs(&DownloadSummary {
+            succeeded: vec![("B".to_string(), remote_mod("2.0.0"))],
+            ..Default::default()
+        });
+
+        let temp_dir = tempfile::tempdir()?;
+        let path = temp_dir.path().join(HISTORY_FILE);
+        append(&path, &entries_from_report(&report))?;
+        append(&path, &entries_from_report(&install))?;
+        fs::write(
+            &path,
+            fs::read_to_string(&path)? + "not json\n", // a corrupted line
+        )?;
+
+        let entries = read(&path)?;
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].name, "B");
+        assert_eq!(entries[0].action, Action::Install);
+        assert_eq!(
+            entries[1].to_string().split_once("  ").unwrap().1,
+            "update   A 1.0.0 -> 1.1.0"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_missing_file() -> anyhow::Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        assert!(read(&temp_dir.path().join(HISTORY_FILE))?.is_empty());
+        Ok(())
+    }
+}
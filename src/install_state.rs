@@ -0,0 +1,144 @@
+//! Progress of a batch of installs, saved after each mod so that `install --resume` can finish
+//! it after a crash, a power loss or Ctrl-C.
+//!
+//! The state file is written when a batch of `install --from-list` or `install-pack` starts,
+//! rewritten each time a requested mod is done, and removed once every mod is installed.
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::{config, fileutil, pack::PackMod};
+
+/// The name of the install state file in the state directory.
+const INSTALL_STATE_FILE: &str = "install-state.json";
+
+/// A batch of installs which is not finished.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InstallState {
+    /// The mods directory the batch installs to
+    pub mods_directory: PathBuf,
+    /// When the batch started, as a Unix timestamp
+    pub started_at: u64,
+    /// Number of mods requested by the batch
+    pub total: usize,
+    /// The requested mods which are not installed yet
+    pub pending: Vec<PackMod>,
+}
+
+impl InstallState {
+    /// Returns the state of a batch starting now.
+    pub fn new(mods_directory: &Path, requested_mods: &[PackMod]) -> Self {
+        Self {
+            mods_directory: mods_directory.to_path_buf(),
+            started_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs())
+                .unwrap_or_default(),
+            total: requested_mods.len(),
+            pending: requested_mods.to_vec(),
+        }
+    }
+
+    /// Marks the requested mod with the given name or URL as installed.
+    pub fn complete(&mut self, label: &str) {
+        if let Some(index) = self
+            .pending
+            .iter()
+            .position(|pending| pending.label() == label)
+        {
+            self.pending.remove(index);
+        }
+    }
+}
+
+/// Returns the path of the install state file in the state directory.
+pub fn install_state_path() -> Option<PathBuf> {
+    config::get_state_directory().map(|state_dir| state_dir.join(INSTALL_STATE_FILE))
+}
+
+/// Reads the state of the unfinished batch, if there is one.
+///
+/// # Errors
+///
+/// Returns an error if the file exists but cannot be read or parsed.
+pub fn read(path: &Path) -> Result<Option<InstallState>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let contents = fs::read_to_string(path)?;
+    serde_json::from_str(&contents).map(Some).with_context(|| {
+        format!(
+            "Failed to parse the install state '{}'",
+            fileutil::replace_home_dir_with_tilde(path)
+        )
+    })
+}
+
+/// Writes the state atomically, creating the state directory if needed.
+///
+/// The file is small, so it is cheap to rewrite after every mod.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be written.
+pub fn write(path: &Path, state: &InstallState) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fileutil::write_atomic(path, serde_json::to_string_pretty(state)?)?;
+    Ok(())
+}
+
+/// Removes the state of a finished batch.
+///
+/// # Errors
+///
+/// Returns an error if the file exists but cannot be removed.
+pub fn remove(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests_install_state {
+    use super::*;
+    use crate::pack::PackSource;
+
+    #[test]
+    fn test_complete_write_then_read() -> Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let path = temp_dir.path().join("state").join(INSTALL_STATE_FILE);
+        assert_eq!(read(&path)?, None);
+
+        let requested_mods = [
+            PackMod {
+                source: PackSource::Name("SpeedrunTool".to_string()),
+                version: Some("3.24.0".to_string()),
+            },
+            PackMod {
+                source: PackSource::Url("https://gamebanana.com/mods/150813".to_string()),
+                version: None,
+            },
+        ];
+        let mut state = InstallState::new(Path::new("/games/Celeste/Mods"), &requested_mods);
+        state.complete("SpeedrunTool");
+        state.complete("NotRequested");
+        assert_eq!(state.pending, requested_mods[1..]);
+        assert_eq!(state.total, 2);
+
+        write(&path, &state)?;
+        assert_eq!(read(&path)?, Some(state));
+
+        remove(&path)?;
+        assert_eq!(read(&path)?, None);
+        remove(&path)?; // Already removed
+        Ok(())
+    }
+}
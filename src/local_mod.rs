@@ -3,7 +3,8 @@
 //! This module provides functionality to load and manage locally installed mods
 //! by reading their manifest files from ZIP archives.
 use std::{
-    io,
+    collections::{HashMap, HashSet},
+    fs, io,
     path::{Path, PathBuf},
 };
 
@@ -11,8 +12,11 @@ use once_cell::sync::OnceCell;
 use thiserror::Error;
 
 use crate::{
+    constant::MAX_SUGGESTION_DISTANCE,
+    download::pb_style,
     fileutil,
-    manifest::{ManifestParseError, ModManifest},
+    manifest::{self, ManifestParseError, ModManifest},
+    mod_registry::RemoteModInfo,
     zip::{self, ZipError},
 };
 
@@ -30,6 +34,26 @@ pub enum LoadModsError {
     Zip(#[from] ZipError),
 }
 
+/// A mod archive that could not be loaded.
+#[derive(Debug)]
+pub struct LoadFailure {
+    /// Path to the archive
+    pub path: PathBuf,
+    /// Why it could not be loaded
+    pub error: LoadModsError,
+}
+
+impl std::fmt::Display for LoadFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {}",
+            fileutil::replace_home_dir_with_tilde(&self.path),
+            self.error
+        )
+    }
+}
+
 /// Information about a locally installed mod.
 #[derive(Debug, Clone)]
 pub struct LocalMod {
@@ -69,6 +93,9 @@ impl LocalMod {
 
     /// Loads all local mods from the provided archive paths.
     ///
+    /// A progress counter is shown while the archives are parsed, since this can take a while
+    /// on directories containing hundreds of mods.
+    ///
     /// # Notes
     ///
     /// Sometimes, `everest.yaml` file may not be present in the mod archive.
@@ -80,27 +107,332 @@ impl LocalMod {
     ///
     /// It's because we cannot do anything if some of the mod archives are broken but that's are not critical to stop the whole process.
     pub fn load_local_mods(archive_paths: &[PathBuf]) -> Vec<LocalMod> {
+        Self::load_local_mods_with_failures(archive_paths).0
+    }
+
+    /// Loads all local mods from the provided archive paths like [`Self::load_local_mods`],
+    /// also returning the archives that could not be loaded with their error, in the order of
+    /// `archive_paths`.
+    pub fn load_local_mods_with_failures(
+        archive_paths: &[PathBuf],
+    ) -> (Vec<LocalMod>, Vec<LoadFailure>) {
         use rayon::prelude::*;
 
         tracing::info!("Found {} mod archives to load", archive_paths.len());
         tracing::info!("Start parsing archive files.");
-        let local_mods: Vec<LocalMod> = archive_paths
+        let pb = pb_style::create_counter(archive_paths.len() as u64, "Reading mod archives");
+        let results: Vec<Result<LocalMod, LoadFailure>> = archive_paths
             .par_iter()
-            .filter_map(|archive_path| match LocalMod::from_path(archive_path) {
-                Ok(local_mod) => Some(local_mod),
-                Err(e) if matches!(e, LoadModsError::Zip(ZipError::NotFound)) => {
-                    tracing::warn!("{:?}: {}", archive_path.file_name(), e);
-                    None
-                }
-                Err(e) => {
-                    tracing::error!("Failed to load mod from {}: {}", archive_path.display(), e);
-                    None
-                }
+            .inspect(|_| pb.inc(1))
+            .map(|archive_path| {
+                LocalMod::from_path(archive_path).map_err(|e| {
+                    match &e {
+                        LoadModsError::Zip(ZipError::NotFound) => {
+                            tracing::warn!("{:?}: {}", archive_path.file_name(), e);
+                        }
+                        LoadModsError::Zip(ZipError::UnsupportedCompression(_)) => {
+                            tracing::warn!("{:?}: {}", archive_path.file_name(), e);
+                            pb.suspend(|| {
+                                eprintln!(
+                                    "⚠️  Skipping '{}': {}",
+                                    fileutil::replace_home_dir_with_tilde(archive_path),
+                                    e
+                                )
+                            });
+                        }
+                        _ => {
+                            tracing::error!(
+                                "Failed to load mod from {}: {}",
+                                archive_path.display(),
+                                e
+                            );
+                        }
+                    }
+                    LoadFailure {
+                        path: archive_path.clone(),
+                        error: e,
+                    }
+                })
             })
             .collect();
+        pb.finish_and_clear();
+
+        let mut local_mods = Vec::new();
+        let mut failures = Vec::new();
+        for result in results {
+            match result {
+                Ok(local_mod) => local_mods.push(local_mod),
+                Err(failure) => failures.push(failure),
+            }
+        }
         tracing::info!("Successfully loaded {} local mods", local_mods.len());
 
-        local_mods
+        (local_mods, failures)
+    }
+}
+
+/// Finds the installed mods matching any of the names or glob patterns (`*`, `?`).
+///
+/// Only the manifests already parsed in `local_mods` are searched, so no archive is opened
+/// again. A mod matched by several patterns is returned once. Also returns the patterns that
+/// matched nothing.
+pub fn find_by_patterns<'a, 'p>(
+    local_mods: &'a [LocalMod],
+    patterns: &'p [String],
+) -> (Vec<&'a LocalMod>, Vec<&'p str>) {
+    let mut found: Vec<&LocalMod> = Vec::new();
+    let mut unmatched = Vec::new();
+    for pattern in patterns {
+        let query = manifest::normalize_name(pattern);
+        let is_glob = query.contains(['*', '?']);
+        let mut matched = false;
+        for local_mod in local_mods {
+            let name = manifest::normalize_name(&local_mod.manifest.name);
+            let is_match = if is_glob {
+                fileutil::glob_match(&query, &name)
+            } else {
+                name == query
+            };
+            if !is_match {
+                continue;
+            }
+            matched = true;
+            if !found.iter().any(|other| std::ptr::eq(*other, local_mod)) {
+                found.push(local_mod);
+            }
+        }
+        if !matched {
+            unmatched.push(pattern.as_str());
+        }
+    }
+    (found, unmatched)
+}
+
+/// Returns the installed mod whose archive is the file of the given registry entry, even if
+/// the mod is installed under another name.
+///
+/// Only the archives of the same size as the registry entry are hashed, so the check is cheap.
+/// If the registry doesn't know the size, nothing is returned.
+pub fn find_by_checksum<'a>(
+    local_mods: &'a [LocalMod],
+    remote_mod: &RemoteModInfo,
+) -> Option<&'a LocalMod> {
+    if remote_mod.file_size == 0 {
+        return None;
+    }
+    local_mods.iter().find(|local_mod| {
+        fs::metadata(&local_mod.location)
+            .is_ok_and(|metadata| metadata.len() == remote_mod.file_size)
+            && local_mod
+                .checksum()
+                .is_ok_and(|hash| remote_mod.has_matching_hash(hash))
+    })
+}
+
+/// Returns the installed mods a query that matched no name exactly may refer to.
+///
+/// The mods whose name contains the query, ignoring case, come first, sorted by name. They are
+/// followed by the near misses, such as misspellings, sorted by how close they are. The order
+/// is stable, so an index into the result can be passed back with `show --index`.
+pub fn find_candidates<'a>(local_mods: &'a [LocalMod], query: &str) -> Vec<&'a LocalMod> {
+    let query = manifest::normalize_name(query).to_lowercase();
+    let mut partial: Vec<(String, &LocalMod)> = Vec::new();
+    let mut near: Vec<(usize, String, &LocalMod)> = Vec::new();
+    for local_mod in local_mods {
+        let name = manifest::normalize_name(&local_mod.manifest.name).to_lowercase();
+        if name.contains(&query) {
+            partial.push((name, local_mod));
+        } else {
+            let distance = edit_distance(&name, &query);
+            if distance <= MAX_SUGGESTION_DISTANCE {
+                near.push((distance, name, local_mod));
+            }
+        }
+    }
+    partial.sort_by(|a, b| a.0.cmp(&b.0));
+    near.sort_by(|a, b| (a.0, &a.1).cmp(&(b.0, &b.1)));
+
+    partial
+        .into_iter()
+        .map(|(_, local_mod)| local_mod)
+        .chain(near.into_iter().map(|(_, _, local_mod)| local_mod))
+        .collect()
+}
+
+/// Returns the Levenshtein distance between two strings, counted in characters.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Returns the normalized names of the installed mods required by another installed mod.
+///
+/// Mods not in the result are top-level: nothing else installed depends on them, so they were
+/// most likely installed explicitly.
+pub fn find_installed_dependencies(local_mods: &[LocalMod]) -> HashSet<String> {
+    let installed: HashSet<_> = local_mods
+        .iter()
+        .map(|local_mod| manifest::normalize_name(&local_mod.manifest.name))
+        .collect();
+
+    local_mods
+        .iter()
+        .flat_map(|local_mod| {
+            let name = manifest::normalize_name(&local_mod.manifest.name);
+            local_mod
+                .manifest
+                .dependencies
+                .iter()
+                .flatten()
+                .map(|dep| manifest::normalize_name(&dep.name))
+                .filter(move |dep_name| *dep_name != name)
+        })
+        .filter(|dep_name| installed.contains(dep_name))
+        .map(|dep_name| dep_name.into_owned())
+        .collect()
+}
+
+/// Returns the normalized names of the installed mods that nothing would require anymore once
+/// the given mods are updated, sorted by name.
+///
+/// `updated_dependencies` maps the normalized name of each updated mod to the names of the
+/// required dependencies of its new version. The installed mods reachable from the top-level
+/// mods are compared before and after the update, so a dependency only required by another
+/// orphaned mod is reported too.
+pub fn find_orphaned_after_update(
+    local_mods: &[LocalMod],
+    updated_dependencies: &HashMap<String, Vec<String>>,
+) -> Vec<String> {
+    let installed: HashMap<String, Vec<String>> = local_mods
+        .iter()
+        .map(|local_mod| {
+            let dependencies = local_mod
+                .manifest
+                .dependencies
+                .iter()
+                .flatten()
+                .map(|dep| manifest::normalize_name(&dep.name).into_owned())
+                .collect();
+            (
+                manifest::normalize_name(&local_mod.manifest.name).into_owned(),
+                dependencies,
+            )
+        })
+        .collect();
+    let dependencies = find_installed_dependencies(local_mods);
+    let top_level: Vec<&str> = installed
+        .keys()
+        .filter(|name| !dependencies.contains(*name))
+        .map(String::as_str)
+        .collect();
+
+    let reachable = |after_update: bool| {
+        let mut reached: HashSet<&str> = HashSet::new();
+        let mut stack = top_level.clone();
+        while let Some(name) = stack.pop() {
+            if !reached.insert(name) {
+                continue;
+            }
+            let dependencies = match updated_dependencies.get(name) {
+                Some(dependencies) if after_update => dependencies,
+                _ => &installed[name],
+            };
+            stack.extend(
+                dependencies
+                    .iter()
+                    .filter_map(|dep| installed.get_key_value(dep))
+                    .map(|(dep, _)| dep.as_str()),
+            );
+        }
+        reached
+    };
+
+    let after = reachable(true);
+    let mut orphaned: Vec<String> = reachable(false)
+        .difference(&after)
+        .map(|name| name.to_string())
+        .collect();
+    orphaned.sort();
+    orphaned
+}
+
+/// Renders the dependency tree of an installed mod from the manifests on disk.
+///
+/// Each dependency is looked up among `local_mods`, so this works offline and reflects what is
+/// actually installed. Dependencies listed in a manifest but absent on disk are marked as missing.
+pub fn render_dependency_tree(root: &LocalMod, local_mods: &[LocalMod]) -> Vec<String> {
+    let installed: HashMap<String, &LocalMod> = local_mods
+        .iter()
+        .map(|local_mod| {
+            (
+                manifest::normalize_name(&local_mod.manifest.name).into_owned(),
+                local_mod,
+            )
+        })
+        .collect();
+
+    let mut lines = vec![format!(
+        "{} ({})",
+        root.manifest.name,
+        root.manifest.display_version()
+    )];
+    let mut ancestors = vec![manifest::normalize_name(&root.manifest.name).into_owned()];
+    render_dependencies(&root.manifest, &installed, &mut ancestors, "", &mut lines);
+    lines
+}
+
+fn render_dependencies(
+    manifest: &ModManifest,
+    installed: &HashMap<String, &LocalMod>,
+    ancestors: &mut Vec<String>,
+    prefix: &str,
+    lines: &mut Vec<String>,
+) {
+    let dependencies: Vec<_> = manifest
+        .dependencies
+        .iter()
+        .flatten()
+        .filter(|dep| !matches!(dep.name.as_str(), "Everest" | "EverestCore"))
+        .collect();
+
+    for (i, dep) in dependencies.iter().enumerate() {
+        let is_last = i + 1 == dependencies.len();
+        let branch = if is_last { "└── " } else { "├── " };
+        let key = manifest::normalize_name(&dep.name).into_owned();
+
+        let Some(local_mod) = installed.get(&key) else {
+            lines.push(format!("{prefix}{branch}{} ❌ not installed", dep.name));
+            continue;
+        };
+        if ancestors.contains(&key) {
+            lines.push(format!("{prefix}{branch}{} (circular)", dep.name));
+            continue;
+        }
+
+        lines.push(format!(
+            "{prefix}{branch}{} ({})",
+            local_mod.manifest.name,
+            local_mod.manifest.display_version()
+        ));
+        let child_prefix = format!("{prefix}{}", if is_last { "    " } else { "│   " });
+        ancestors.push(key);
+        render_dependencies(
+            &local_mod.manifest,
+            installed,
+            ancestors,
+            &child_prefix,
+            lines,
+        );
+        ancestors.pop();
     }
 }
 
@@ -143,4 +475,166 @@ mod tests_local_mod {
         assert!(!local_mods.is_empty());
         assert_eq!(local_mods[0].manifest.name, "test-mod");
     }
+
+    #[test]
+    fn test_load_local_mods_with_failures() {
+        let archive_paths = vec![
+            PathBuf::from("./test/missing-manifest.zip"),
+            PathBuf::from("./test/test-mod.zip"),
+            PathBuf::from("./test/bzip2-manifest.zip"),
+        ];
+        let (local_mods, failures) = LocalMod::load_local_mods_with_failures(&archive_paths);
+        assert_eq!(local_mods.len(), 1);
+        assert_eq!(
+            failures
+                .iter()
+                .map(|failure| failure.path.as_path())
+                .collect::<Vec<_>>(),
+            [archive_paths[0].as_path(), archive_paths[2].as_path()]
+        );
+        assert!(matches!(
+            failures[0].error,
+            LoadModsError::Zip(ZipError::NotFound)
+        ));
+    }
+
+    fn local_mod(yaml: &str) -> LocalMod {
+        LocalMod {
+            location: PathBuf::new(),
+            manifest: ModManifest::from_slice(yaml.as_bytes()).unwrap(),
+            checksum: OnceCell::new(),
+        }
+    }
+
+    #[test]
+    fn test_find_installed_dependencies() {
+        let local_mods = vec![
+            local_mod(
+                "- Name: Collab\n  Version: 1.0.0\n  Dependencies:\n    - Name: Everest\n    - Name: CommunalHelper\n    - Name: FrostHelper\n",
+            ),
+            local_mod(
+                "- Name: CommunalHelper\n  Version: 1.2.3\n  Dependencies:\n    - Name: CommunalHelper\n",
+            ),
+            local_mod("- Name: SpeedrunTool\n  Version: 3.24.0\n"),
+        ];
+
+        let dependencies = find_installed_dependencies(&local_mods);
+        assert_eq!(dependencies, HashSet::from(["CommunalHelper".to_string()]));
+    }
+
+    #[test]
+    fn test_find_orphaned_after_update() {
+        let local_mods = vec![
+            local_mod(
+                "- Name: Collab\n  Version: 1.0.0\n  Dependencies:\n    - Name: OldHelper\n    - Name: SharedHelper\n",
+            ),
+            local_mod("- Name: OldHelper\n  Version: 1.0.0\n  Dependencies:\n    - Name: OldLib\n"),
+            local_mod("- Name: OldLib\n  Version: 1.0.0\n"),
+            local_mod("- Name: Map\n  Version: 1.0.0\n  Dependencies:\n    - Name: SharedHelper\n"),
+            local_mod("- Name: SharedHelper\n  Version: 1.0.0\n"),
+        ];
+
+        // The new Collab drops both helpers and depends on a mod that isn't installed.
+        let updated = HashMap::from([("Collab".to_string(), vec!["NewHelper".to_string()])]);
+        assert_eq!(
+            find_orphaned_after_update(&local_mods, &updated),
+            ["OldHelper", "OldLib"]
+        );
+
+        // Keeping the same dependencies orphans nothing.
+        let updated = HashMap::from([(
+            "Collab".to_string(),
+            vec!["OldHelper".to_string(), "SharedHelper".to_string()],
+        )]);
+        assert!(find_orphaned_after_update(&local_mods, &updated).is_empty());
+        assert!(find_orphaned_after_update(&local_mods, &HashMap::new()).is_empty());
+    }
+
+    #[test]
+    fn test_find_by_checksum_under_another_name() -> anyhow::Result<()> {
+        // The registry lists the mod under another key than the name in its manifest.
+        let temp_dir = tempfile::tempdir()?;
+        let location = temp_dir.path().join("Renamed.zip");
+        fs::copy("./test/test-mod.zip", &location)?;
+        let local_mods = vec![LocalMod::from_path(&location)?];
+        let mut remote_mod = RemoteModInfo {
+            file_size: fs::metadata(&location)?.len(),
+            checksums: vec![local_mods[0].checksum()?.to_uppercase()],
+            ..Default::default()
+        };
+
+        let found = find_by_checksum(&local_mods, &remote_mod).map(|m| &m.location);
+        assert_eq!(found, Some(&location));
+
+        remote_mod.checksums = vec!["0000000000000000".to_string()];
+        assert!(find_by_checksum(&local_mods, &remote_mod).is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_candidates() {
+        let local_mods = vec![
+            local_mod("- Name: FrostHelper\n  Version: 1.0.0\n"),
+            local_mod("- Name: SpeedrunTool\n  Version: 3.24.0\n"),
+            local_mod("- Name: CommunalHelper\n  Version: 1.2.3\n"),
+        ];
+        let names = |query| -> Vec<&str> {
+            find_candidates(&local_mods, query)
+                .iter()
+                .map(|m| m.manifest.name.as_str())
+                .collect()
+        };
+
+        assert_eq!(names("helper"), ["CommunalHelper", "FrostHelper"]);
+        assert_eq!(names("SpedrunTol"), ["SpeedrunTool"]);
+        assert!(names("Missing").is_empty());
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("same", "same"), 0);
+    }
+
+    #[test]
+    fn test_find_by_patterns() {
+        let local_mods = vec![
+            local_mod("- Name: FrostHelper\n  Version: 1.0.0\n"),
+            local_mod("- Name: CommunalHelper\n  Version: 1.2.3\n"),
+            local_mod("- Name: SpeedrunTool\n  Version: 3.24.0\n"),
+        ];
+        let patterns = [
+            "*Helper".to_string(),
+            "FrostHelper".to_string(),
+            "Missing".to_string(),
+        ];
+
+        let (found, unmatched) = find_by_patterns(&local_mods, &patterns);
+        let names: Vec<_> = found.iter().map(|m| m.manifest.name.as_str()).collect();
+        assert_eq!(names, ["FrostHelper", "CommunalHelper"]);
+        assert_eq!(unmatched, ["Missing"]);
+    }
+
+    #[test]
+    fn test_render_dependency_tree() {
+        let root = local_mod(
+            "- Name: Collab\n  Version: 1.0.0\n  Dependencies:\n    - Name: Everest\n      Version: 1.4000.0\n    - Name: CommunalHelper\n    - Name: FrostHelper\n",
+        );
+        let communal = local_mod(
+            "- Name: CommunalHelper\n  Version: 1.2.3\n  Dependencies:\n    - Name: Collab\n",
+        );
+        let local_mods = vec![root.clone(), communal];
+
+        let tree = render_dependency_tree(&root, &local_mods);
+        assert_eq!(
+            tree,
+            [
+                "Collab (1.0.0)",
+                "├── CommunalHelper (1.2.3)",
+                "│   └── Collab (circular)",
+                "└── FrostHelper ❌ not installed",
+            ]
+        );
+    }
 }
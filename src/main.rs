@@ -1,96 +1,650 @@
 use std::{
-    collections::HashSet,
-    env,
+    collections::{HashMap, HashSet},
     fs::{self, File},
-    sync::Arc,
+    io::IsTerminal,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, Instant},
 };
 
 use anyhow::{Context, Result};
 use clap::Parser;
 
+mod bench;
+#[cfg(feature = "browse")]
+mod browse;
+mod cache;
 mod cli;
 mod config;
 mod constant;
 mod dependency;
 mod download;
+mod error;
+mod everest;
 mod fetch;
 mod fileutil;
+mod gamebanana;
+mod history;
+mod install_state;
 mod local_mod;
 mod manifest;
+mod mirrors;
 mod mod_registry;
+mod pack;
+mod pager;
+mod pin;
+mod ping;
+mod process;
+mod profile;
+mod report;
+mod resolver;
+mod timeutil;
+mod update_check;
 mod zip;
 
 use crate::{
-    cli::{Cli, Commands},
+    cli::{CacheAction, CacheArgs, Cli, Commands, MirrorStrategy, VersionConflictPolicy},
     config::Config,
+    constant::{REGISTRY_FETCH_ATTEMPTS, REGISTRY_RETRY_DELAY_SECS},
     dependency::ModDependencyQuery,
+    error::CliError,
+    install_state::InstallState,
     local_mod::LocalMod,
     mod_registry::{ModRegistryQuery, RemoteModRegistry},
+    pack::PackSource,
+    report::Report,
 };
 
+/// Verifies the newly installed files of a batch again for `--verify-after`.
+///
+/// # Errors
+///
+/// Returns an error if any of the files doesn't match the checksums in the registry.
+fn verify_after(summary: &download::DownloadSummary) -> Result<()> {
+    let phase = Instant::now();
+    let failures = summary.verify_installed_files();
+    bench::record("verify after", phase);
+
+    if failures.is_empty() {
+        println!(
+            "✅ Verified {} installed file(s)",
+            summary.installed_files.len()
+        );
+        return Ok(());
+    }
+    for failure in &failures {
+        tracing::error!("Verification failed: {}", failure);
+        println!("❌ {failure}");
+    }
+    Err(CliError::Checksum(anyhow::anyhow!(
+        "{} installed file(s) failed verification",
+        failures.len()
+    ))
+    .into())
+}
+
+/// Checks that the game is not running before the mods directory is modified.
+///
+/// If it is, the user is asked whether to continue; without a terminal, `--force` is required.
+/// The check is skipped with `--force`, and a process list that can't be read only logs a
+/// warning.
+///
+/// # Errors
+///
+/// Returns an error if the game is running and the user doesn't continue.
+fn ensure_game_not_running(force: bool) -> Result<()> {
+    if force {
+        return Ok(());
+    }
+    let game = match process::find_running_game() {
+        Ok(Some(game)) => game,
+        Ok(None) => return Ok(()),
+        Err(err) => {
+            tracing::warn!("Could not check if the game is running: {}", err);
+            return Ok(());
+        }
+    };
+    tracing::warn!("The game is running: {} (PID {})", game.name, game.pid);
+    eprintln!(
+        "⚠️  Celeste is running ({}, PID {}). Changing mods while the game is running can fail on locked files or leave mods partially loaded.",
+        game.name, game.pid
+    );
+    if confirm("Continue anyway?") {
+        return Ok(());
+    }
+    anyhow::bail!("Close the game first, or pass --force to modify the mods anyway")
+}
+
+/// Writes the downloads of the summary that failed checksum verification to the file given by
+/// `--checksum-mismatch-report`, if any.
+fn write_checksum_mismatch_report(cli: &Cli, summary: &download::DownloadSummary) {
+    let Some(path) = &cli.checksum_mismatch_report else {
+        return;
+    };
+    match report::append_checksum_mismatches(path, summary) {
+        Ok(0) => {}
+        Ok(count) => println!(
+            "📝 Checksum mismatch report of {} mod(s) written to {}",
+            count,
+            fileutil::replace_home_dir_with_tilde(path)
+        ),
+        Err(err) => {
+            tracing::error!("{:#}", err);
+            eprintln!("Failed to write the checksum mismatch report: {err:#}");
+        }
+    }
+}
+
+/// Whether every confirmation prompt is answered yes (`--yes`).
+static ASSUME_YES: AtomicBool = AtomicBool::new(false);
+
+/// Reads the state of the interrupted batch of installs into the mods directory, for
+/// `install --resume`.
+///
+/// # Errors
+///
+/// Returns an error if there is no interrupted batch, or if it installs to another directory.
+fn read_resumable_state(config: &Config) -> Result<InstallState> {
+    let path = install_state::install_state_path().context("Could not determine home directory")?;
+    let Some(state) = install_state::read(&path)? else {
+        anyhow::bail!("There is no interrupted install to resume");
+    };
+    if state.mods_directory != config.directory() {
+        return Err(CliError::Usage(anyhow::anyhow!(
+            "The interrupted install was into '{}', pass it with --mods-dir to resume it",
+            fileutil::replace_home_dir_with_tilde(&state.mods_directory)
+        ))
+        .into());
+    }
+    Ok(state)
+}
+
+/// Tells about an interrupted batch of installs into the mods directory, if there is one.
+fn offer_resume(config: &Config) {
+    let Some(state) = install_state::install_state_path()
+        .and_then(|path| install_state::read(&path).ok().flatten())
+        .filter(|state| state.mods_directory == config.directory())
+    else {
+        return;
+    };
+    eprintln!(
+        "⚠️  An install was interrupted with {} of {} mod(s) left. Run `everest-mod-cli install --resume` to finish it.",
+        state.pending.len(),
+        state.total
+    );
+}
+
+/// Saves the progress of a batch of installs. A failure is only logged, since it doesn't
+/// affect the installs themselves.
+fn save_install_state(path: Option<&std::path::Path>, state: Option<&InstallState>) {
+    let (Some(path), Some(state)) = (path, state) else {
+        return;
+    };
+    if let Err(err) = install_state::write(path, state) {
+        tracing::warn!("Failed to save the install state: {:#}", err);
+    }
+}
+
+/// Asks a yes/no question on the terminal, defaulting to no.
+///
+/// Returns true without asking with `--yes`, and false without asking if stdin is not a
+/// terminal, so scripts never hang on a prompt.
+fn confirm(question: &str) -> bool {
+    use std::io::{BufRead, Write};
+
+    if ASSUME_YES.load(Ordering::Relaxed) {
+        println!("{question} [y/N] yes (--yes)");
+        return true;
+    }
+    let stdin = std::io::stdin();
+    if !stdin.is_terminal() {
+        return false;
+    }
+    print!("{question} [y/N] ");
+    let _ = std::io::stdout().flush();
+    let mut answer = String::new();
+    if stdin.lock().read_line(&mut answer).is_err() {
+        return false;
+    }
+    matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+/// Returns the path of the log file of this run, from `--log-file`, or from `--log-dir` and
+/// `--log-naming`.
+///
+/// # Errors
+///
+/// Returns an error if no log directory is given and the home directory can't be determined.
+fn log_file_path(cli: &Cli) -> Result<std::path::PathBuf> {
+    if let Some(path) = &cli.log_file {
+        return Ok(path.clone());
+    }
+    let log_dir = match &cli.log_dir {
+        Some(log_dir) => log_dir.clone(),
+        None => config::get_state_directory().context("Could not determine home directory")?,
+    };
+    let file_name = cli
+        .log_naming
+        .file_name(timeutil::UtcDateTime::now(), std::process::id());
+    Ok(log_dir.join(file_name))
+}
+
 /// Initialize logger
-fn setup_logger(verbose: bool) -> Result<()> {
-    let log_dir = env::home_dir()
-        .context("Could not determine home directory")?
-        .join(".local/state/everest-mod-cli/");
-    fs::create_dir_all(&log_dir).context("Failed to create log directory")?;
-
-    let log_file_path = log_dir.join("everest-mod-cli.log");
-    let log_file = File::create(&log_file_path).context("Failed to create log file")?;
-
-    // Determine the log level based on verbosity
-    let log_level = if verbose {
-        "everest_mod_cli=debug"
-    } else {
-        "everest_mod_cli=info"
+///
+/// The log file gets info logs by default, and nothing is logged to stderr. Each `-v` raises
+/// the level: `-v` shows info on stderr and writes debug to the file, `-vv` shows debug on
+/// stderr, and `-vvv` shows trace on both.
+fn setup_logger(verbosity: u8, log_file_path: &std::path::Path) -> Result<()> {
+    use tracing_subscriber::{EnvFilter, fmt, prelude::*};
+
+    if let Some(log_dir) = log_file_path.parent() {
+        fs::create_dir_all(log_dir).context("Failed to create log directory")?;
+    }
+    let log_file = File::create(log_file_path).with_context(|| {
+        format!(
+            "Failed to create log file '{}'",
+            fileutil::replace_home_dir_with_tilde(log_file_path)
+        )
+    })?;
+
+    // Determine the log levels of the file and of stderr based on verbosity
+    let (file_level, stderr_level) = match verbosity {
+        // NOTE: Errors are already reported by the commands themselves.
+        0 => ("info", "off"),
+        1 => ("debug", "info"),
+        2 => ("debug", "debug"),
+        _ => ("trace", "trace"),
     };
+    let filter = |level: &str| EnvFilter::new(format!("everest_mod_cli={level}"));
 
-    // construct a subscriber that prints formatted traces to stdout
-    let subscriber = tracing_subscriber::fmt()
+    let file_layer = fmt::layer()
         .compact()
-        .with_env_filter(log_level)
         .with_file(true)
         .with_line_number(true)
         .with_thread_ids(true)
         .with_target(false)
         .with_writer(log_file)
         .with_ansi(false)
-        .finish();
+        .with_filter(filter(file_level));
+    let stderr_layer = fmt::layer()
+        .compact()
+        .without_time()
+        .with_target(false)
+        .with_writer(std::io::stderr)
+        .with_filter(filter(stderr_level));
 
-    // Start configuring a `fmt` subscriber
-    tracing::subscriber::set_global_default(subscriber)?;
+    tracing_subscriber::registry()
+        .with(file_layer)
+        .with(stderr_layer)
+        .try_init()?;
 
     Ok(())
 }
 
-async fn run() -> Result<()> {
+async fn run() -> Result<(), CliError> {
     let cli = Cli::parse();
+    let Some(command) = &cli.command else {
+        cli::print_overview();
+        return Ok(());
+    };
 
-    setup_logger(cli.verbose)?;
+    setup_logger(cli.verbose, &log_file_path(&cli)?)?;
+
+    if cli.quiet {
+        download::pb_style::hide_all();
+    }
+    if cli.no_progress || !std::io::stdout().is_terminal() {
+        download::pb_style::use_plain();
+    }
+    if cli.compact {
+        download::pb_style::use_compact();
+    }
+    if cli.title_progress && std::io::stdout().is_terminal() {
+        download::pb_style::use_title_progress();
+    }
+    if cli.bench {
+        bench::enable();
+    }
+    if cli.yes {
+        ASSUME_YES.store(true, Ordering::Relaxed);
+    }
+    if cli.cache {
+        match cache::cache_directory(cli.cache_dir.as_deref()) {
+            Some(directory) => cache::enable(directory),
+            None => eprintln!("⚠️  Could not determine the cache directory, --cache is ignored"),
+        }
+    }
+    let started = Instant::now();
 
     tracing::info!("Application starts");
 
     tracing::debug!("Passed CLI arguments: {:#?}", &cli);
-    tracing::debug!("Command passed: {:?}", &cli.command);
+    tracing::debug!("Command passed: {:?}", command);
 
-    let config = Config::new(&cli)?;
+    let mut report = Report::new(command.name());
+    let result = execute(&cli, command, &mut report).await;
+    history::record(&report);
+    bench::print_summary(started.elapsed());
 
-    // Determine the mods directory.
-    let mods_directory = config.directory();
-    tracing::info!(
-        "Using mods directory: '{}'",
-        fileutil::replace_home_dir_with_tilde(mods_directory)
-    );
-    tracing::info!("Mirror preference: {}", config.mirror_preferences());
+    if let Some(report_path) = &cli.report {
+        report.finish(&result);
+        if let Err(err) = report.write_to(report_path) {
+            tracing::error!("{:#}", err);
+            eprintln!("Failed to write the report: {err:#}");
+        }
+    }
 
-    // Gathering mod paths
-    let archive_paths = config.find_installed_mod_archives()?;
+    Ok(result?)
+}
+
+/// Executes the given command, recording what happened to the report.
+/// Executes the given command, recording what happened to the report.
+async fn execute(cli: &Cli, command: &Commands, report: &mut Report) -> Result<()> {
+    match command {
+        // The mirrors can be pinged even if the mods directory can't be determined.
+        Commands::Ping => {
+            let client = fetch::build_default_client();
+            let spinner = download::pb_style::create_spinner("Pinging mirrors...");
+            let results = ping::ping_mirrors(&client).await;
+            spinner.finish_and_clear();
+            ping::print_table(&results);
+            Ok(())
+        }
+        Commands::Pin(args) => {
+            open_config(cli, command)?;
+            let path = pin::pins_path().context("Could not determine home directory")?;
+            let mut pins = pin::read(&path)?;
+            let Some(name) = &args.name else {
+                if pins.is_empty() {
+                    println!("No mods are pinned.");
+                }
+                for (name, version) in &pins {
+                    println!("📌 {name} {version}");
+                }
+                return Ok(());
+            };
+
+            let key = manifest::normalize_name(name).into_owned();
+            if args.remove {
+                if pins.remove(&key).is_none() {
+                    println!("[{name}] is not pinned.");
+                    return Ok(());
+                }
+                println!("Unpinned [{name}].");
+            } else {
+                let version = args
+                    .version
+                    .clone()
+                    .context("Specify the version to pin, or --remove to unpin")?;
+                println!("📌 Pinned [{name}] to {version}.");
+                pins.insert(key, version);
+            }
+            pin::write(&path, &pins)?;
+            Ok(())
+        }
+        Commands::Validate(args) => {
+            open_config(cli, command)?;
+            validate_manifest(&args.path)
+        }
+        Commands::Cache(CacheArgs {
+            action: CacheAction::Clear,
+        }) => {
+            open_config(cli, command)?;
+            let directory = cache::cache_directory(cli.cache_dir.as_deref())
+                .context("Could not determine the cache directory, pass it with --cache-dir")?;
+            let count = cache::clear(&directory).with_context(|| {
+                format!("Failed to clear the cache in '{}'", directory.display())
+            })?;
+            if count == 0 {
+                println!("The cache is already empty.");
+            } else {
+                println!(
+                    "✅ Deleted {} cached files from '{}'.",
+                    count,
+                    fileutil::replace_home_dir_with_tilde(&directory)
+                );
+            }
+            Ok(())
+        }
+        Commands::VerifyFile(args) => {
+            let config = open_config(cli, command)?;
+            let client = fetch::build_client(&config);
+            let spinner = download::pb_style::create_spinner("Fetching online database...");
+            let mod_registry = RemoteModRegistry::fetch(&client).await;
+            spinner.finish_and_clear();
+            verify_file(&args.path, &mod_registry?)
+        }
+        Commands::Mirrors => {
+            let config = open_config(cli, command)?;
+            for line in mirrors::render_table(config.mirror_preferences()) {
+                println!("{line}");
+            }
+            Ok(())
+        }
+        Commands::Search(args) => {
+            let config = open_config(cli, command)?;
+            let client = fetch::build_client(&config);
+            let (mod_registry, dependency_graph) = if args.with_deps {
+                let (mod_registry, dependency_graph) =
+                    fetch::fetch_online_database(&client).await?;
+                (mod_registry, Some(dependency_graph))
+            } else {
+                let spinner = download::pb_style::create_spinner("Fetching online database...");
+                let mod_registry = RemoteModRegistry::fetch(&client).await?;
+                spinner.finish_and_clear();
+                (mod_registry, None)
+            };
+
+            let matches = mod_registry.search(&args.term);
+            let enrichment = if args.enrich {
+                let ids: Vec<u32> = matches
+                    .iter()
+                    .map(|(_, remote_mod)| remote_mod.gamebanana_id)
+                    .collect();
+                let spinner = download::pb_style::create_spinner("Fetching GameBanana stats...");
+                let enrichment = gamebanana::fetch_stats(&client, &ids).await;
+                spinner.finish_and_clear();
+                Some(enrichment)
+            } else {
+                None
+            };
+            for (mod_name, remote_mod) in &matches {
+                println!(
+                    "- {} ({}) {}{}",
+                    mod_name,
+                    remote_mod.version,
+                    cli::VALID_MOD_PAGE_URL_PREFIX,
+                    remote_mod.gamebanana_id
+                );
+                if let Some(dependency_graph) = &dependency_graph {
+                    match dependency_graph.count_dependencies(mod_name) {
+                        Some((direct, transitive)) => {
+                            let optional = dependency_graph
+                                .optional_deps(mod_name)
+                                .map_or(0, <[_]>::len);
+                            println!(
+                                "  Dependencies: {direct} direct, {transitive} in total, {optional} optional"
+                            )
+                        }
+                        None => println!("  Dependencies: unknown"),
+                    }
+                }
+                if let Some(enrichment) = &enrichment {
+                    match enrichment.stats.get(&remote_mod.gamebanana_id) {
+                        Some(stats) => println!("  GameBanana: {stats}"),
+                        None => println!("  GameBanana: unavailable"),
+                    }
+                }
+            }
+            if enrichment.as_ref().is_some_and(|e| e.rate_limited) {
+                println!();
+                println!(
+                    "⚠️  The GameBanana API rate limit was reached, some mods only show registry data."
+                );
+            }
+            println!();
+            println!("✅ {} mods found.", matches.len());
+            Ok(())
+        }
+        Commands::RegistryShow(args) => {
+            let config = open_config(cli, command)?;
+            let client = fetch::build_client(&config);
+            let spinner = download::pb_style::create_spinner("Fetching online database...");
+            let mod_registry = RemoteModRegistry::fetch(&client).await;
+            spinner.finish_and_clear();
+            let mod_registry = mod_registry?;
+
+            let mod_names = if args.mod_ref.contains("://") {
+                let mod_id = cli::parse_mod_page_url(&args.mod_ref)?;
+                mod_registry.get_mod_name_by_id(mod_id)
+            } else {
+                let name = manifest::normalize_name(&args.mod_ref);
+                mod_registry
+                    .get_key_value(name.as_ref())
+                    .map(|(mod_name, _)| mod_name)
+                    .into_iter()
+                    .collect()
+            };
+            if mod_names.is_empty() {
+                println!(
+                    "Could not find '{}' in the registry. Try 'search' to look it up by part of its name.",
+                    args.mod_ref
+                );
+                return Ok(());
+            }
+            for (i, mod_name) in mod_names.into_iter().enumerate() {
+                if i > 0 {
+                    println!();
+                }
+                for line in mod_registry[mod_name].describe(mod_name) {
+                    println!("{line}");
+                }
+            }
+            Ok(())
+        }
+        Commands::Dependents(args) => {
+            let config = open_config(cli, command)?;
+            let client = fetch::build_client(&config);
+            let spinner = download::pb_style::create_spinner("Fetching dependency graph...");
+            let dependency_graph = dependency::DependencyGraph::fetch(&client).await;
+            spinner.finish_and_clear();
+            let dependency_graph = dependency_graph?;
+
+            let name = &args.name;
+            if !dependency_graph.contains_key(manifest::normalize_name(name).as_ref()) {
+                println!("Could not find [{name}] in the dependency graph.");
+                return Ok(());
+            }
+            let reverse = dependency::ReverseDependencyGraph::new(&dependency_graph);
+            let mut dependents = reverse.collect_dependents(name, args.transitive);
+            if args.installed_only {
+                let archive_paths = config.find_installed_mod_archives()?;
+                let installed: HashSet<String> = LocalMod::load_local_mods(&archive_paths)
+                    .iter()
+                    .map(|local_mod| {
+                        manifest::normalize_name(&local_mod.manifest.name).into_owned()
+                    })
+                    .collect();
+                dependents.retain(|dependent, _| installed.contains(dependent));
+            }
 
-    let mut local_mods = LocalMod::load_local_mods(&archive_paths);
+            let scope = if args.installed_only {
+                "installed "
+            } else {
+                ""
+            };
+            if dependents.is_empty() {
+                println!("No {scope}mod requires [{name}].");
+                return Ok(());
+            }
+            println!("{} {scope}mod(s) require [{name}]:", dependents.len());
+            for (dependent, via) in &dependents {
+                match via {
+                    Some(via) => println!("- {dependent} (via {via})"),
+                    None => println!("- {dependent}"),
+                }
+            }
+            Ok(())
+        }
+        Commands::GraphExport(args) => {
+            let config = open_config(cli, command)?;
+            let client = fetch::build_client(&config);
+            let spinner = download::pb_style::create_spinner("Fetching dependency graph...");
+            let dependency_graph = dependency::DependencyGraph::fetch(&client).await;
+            spinner.finish_and_clear();
+            let dependency_graph = dependency_graph?;
+
+            if let Some(name) = &args.name
+                && dependency_graph.get_mod_info_by_name(name).is_none()
+            {
+                return Err(CliError::NotFound(anyhow::anyhow!(
+                    "[{name}] is not in the dependency graph"
+                ))
+                .into());
+            }
+            let archive_paths = config.find_installed_mod_archives()?;
+            let installed: HashSet<String> = LocalMod::load_local_mods(&archive_paths)
+                .iter()
+                .map(|local_mod| manifest::normalize_name(&local_mod.manifest.name).into_owned())
+                .collect();
+            let dot = dependency::to_dot(&dependency_graph, args.name.as_deref(), &installed);
+            match &args.output {
+                Some(path) => {
+                    fs::write(path, dot).with_context(|| {
+                        format!(
+                            "Failed to write the graph to '{}'",
+                            fileutil::replace_home_dir_with_tilde(path)
+                        )
+                    })?;
+                    println!(
+                        "✅ The graph is written to '{}'",
+                        fileutil::replace_home_dir_with_tilde(path)
+                    );
+                }
+                None => print!("{dot}"),
+            }
+            Ok(())
+        }
+        #[cfg(feature = "browse")]
+        Commands::Browse => {
+            let config = open_config(cli, command)?;
+            let client = fetch::build_client(&config);
+            let spinner = download::pb_style::create_spinner("Fetching online database...");
+            let mod_registry = RemoteModRegistry::fetch(&client).await;
+            spinner.finish_and_clear();
+            let names = browse::run(&mod_registry?)?;
+            if names.is_empty() {
+                println!("No mods selected.");
+                return Ok(());
+            }
+            // The selection is installed like `install` does, dependencies included.
+            let args = cli::InstallArgs {
+                names,
+                ..Default::default()
+            };
+            Box::pin(install(cli, config, &args, report)).await
+        }
+        Commands::History(args) => {
+            open_config(cli, command)?;
+            let path = history::history_path().context("Could not determine home directory")?;
+            let entries = history::read(&path)?;
+            if entries.is_empty() {
+                println!("No history recorded yet.");
+                return Ok(());
+            }
+            let limit = args.limit.unwrap_or(entries.len());
+            for entry in entries.iter().take(limit) {
+                println!("{entry}");
+            }
+            Ok(())
+        }
 
-    match &cli.command {
         // Show mod name and file name of installed mods.
-        Commands::List => {
+        Commands::List(args) => {
+            let config = open_config(cli, command)?;
+            let (archive_paths, mut local_mods) = load_installed_mods(cli, &config)?;
             if archive_paths.is_empty() {
                 println!("No mods are currently installed.");
                 return Ok(());
@@ -100,37 +654,204 @@ async fn run() -> Result<()> {
             tracing::info!("Sorting the installed mods by name.");
             local_mods.sort_by(|a, b| a.manifest.name.cmp(&b.manifest.name));
 
+            let pins = match pin::pins_path() {
+                Some(path) => pin::read(&path)?,
+                None => pin::Pins::new(),
+            };
+
+            let dependencies = local_mod::find_installed_dependencies(&local_mods);
+            let is_dependency = |local_mod: &LocalMod| {
+                dependencies.contains(manifest::normalize_name(&local_mod.manifest.name).as_ref())
+            };
+            let mut shown: Vec<&LocalMod> = local_mods
+                .iter()
+                .filter(|local_mod| {
+                    if args.top_level {
+                        !is_dependency(local_mod)
+                    } else if args.deps_only {
+                        is_dependency(local_mod)
+                    } else {
+                        true
+                    }
+                })
+                .collect();
+
+            // The registry is only fetched on request, so that `list` works offline.
+            let registry = if args.outdated_first {
+                let client = fetch::build_client(&config);
+                let spinner = download::pb_style::create_spinner("Fetching online database...");
+                let mod_registry = RemoteModRegistry::fetch(&client).await;
+                spinner.finish_and_clear();
+                match mod_registry {
+                    Ok(mod_registry) => Some(Arc::new(mod_registry)),
+                    Err(err) => {
+                        tracing::warn!("Failed to fetch the mod registry: {:#}", err);
+                        eprintln!(
+                            "⚠️  Could not fetch the mod registry, listing the mods by name: {err}"
+                        );
+                        None
+                    }
+                }
+            } else {
+                None
+            };
+            let updates: HashMap<String, String> = match &registry {
+                Some(registry) => {
+                    let last_checks_path = update_check::last_checks_path();
+                    let mut last_checks = last_checks_path
+                        .as_ref()
+                        .and_then(|path| update_check::read(path).ok())
+                        .unwrap_or_default();
+                    let updates = registry
+                        .clone()
+                        .check_updates(&local_mods, &pins, &mut last_checks)
+                        .into_iter()
+                        .map(|(name, remote_mod)| (name, remote_mod.version))
+                        .collect();
+                    // Saved like `update` does, so that the next check can skip unchanged mods.
+                    if let Some(path) = &last_checks_path {
+                        last_checks.retain(|name, _| {
+                            local_mods.iter().any(|local_mod| {
+                                manifest::normalize_name(&local_mod.manifest.name) == name.as_str()
+                            })
+                        });
+                        if let Err(err) = update_check::write(path, &last_checks) {
+                            tracing::warn!("Failed to save the last update checks: {:#}", err);
+                        }
+                    }
+                    updates
+                }
+                None => HashMap::new(),
+            };
+            if let Some(registry) = &registry {
+                // Stable, so mods updated at the same time stay sorted by name.
+                shown.sort_by_key(|local_mod| {
+                    let updated_at = registry
+                        .get(manifest::normalize_name(&local_mod.manifest.name).as_ref())
+                        .and_then(|remote_mod| remote_mod.updated_at);
+                    (
+                        !updates.contains_key(&local_mod.manifest.name),
+                        std::cmp::Reverse(updated_at),
+                    )
+                });
+            }
+
             tracing::info!("Listing installed mods.");
-            local_mods.iter().for_each(|local_mod| {
-                if let Some(os_str) = local_mod.location.file_name() {
-                    println!(
-                        "- {} ({})",
+            let mut lines: Vec<String> = shown
+                .iter()
+                .filter_map(|local_mod| {
+                    let os_str = local_mod.location.file_name()?;
+                    let pinned = pin::pinned_version(&pins, &local_mod.manifest.name)
+                        .map(|version| format!(" 📌 {version}"))
+                        .unwrap_or_default();
+                    let update = updates
+                        .get(&local_mod.manifest.name)
+                        .map(|version| format!(" ⬆️  {version} available"))
+                        .unwrap_or_default();
+                    Some(format!(
+                        "- {} ({}){}{}",
                         local_mod.manifest.name,
-                        os_str.to_string_lossy()
-                    );
-                }
-            });
+                        os_str.to_string_lossy(),
+                        pinned,
+                        update
+                    ))
+                })
+                .collect();
 
-            println!();
-            println!("✅ {} mods found.", &local_mods.len());
-            if archive_paths.len() != local_mods.len() {
-                println!(
-                    "⚠️  {} mod archive(s) could not be read. Check the log file for details.",
+            lines.push(String::new());
+            lines.push(format!("✅ {} mods found.", shown.len()));
+            let outdated = shown
+                .iter()
+                .filter(|local_mod| updates.contains_key(&local_mod.manifest.name))
+                .count();
+            if outdated > 0 {
+                lines.push(format!(
+                    "⬆️  {outdated} update(s) available, run `update --install` to install them."
+                ));
+            }
+            if archive_paths.len() != local_mods.len() && !cli.keep_going {
+                lines.push(format!(
+                    "⚠️  {} mod archive(s) could not be read. Check the log file or run with --keep-going for details.",
                     archive_paths.len() - local_mods.len()
-                );
+                ));
             }
+            pager::print_paged(&lines, !args.no_pager);
+            Ok(())
         }
 
-        // Show details of a specific mod if it is installed.
+        // Show details of the installed mods matching the names.
         Commands::Show(args) => {
+            let config = open_config(cli, command)?;
+            let (_, local_mods) = load_installed_mods(cli, &config)?;
             tracing::info!("Checking installed mod information...");
-            if let Some(local_mod) = local_mods.iter().find(|m| m.manifest.name == args.name) {
+            if args.index.is_some() && args.names.len() > 1 {
+                return Err(CliError::Usage(anyhow::anyhow!(
+                    "--index can only be used with a single name"
+                ))
+                .into());
+            }
+            let (mut found, unmatched) = local_mod::find_by_patterns(&local_mods, &args.names);
+            // Names without an exact match fall back to partial matches and near misses.
+            for pattern in unmatched {
+                let candidates = if pattern.contains(['*', '?']) {
+                    Vec::new()
+                } else {
+                    local_mod::find_candidates(&local_mods, pattern)
+                };
+                if candidates.is_empty() {
+                    println!("The mod '{pattern}' is not currently installed.");
+                    continue;
+                }
+                let selected = args
+                    .index
+                    .and_then(|index| candidates.get(usize::try_from(index).ok()? - 1));
+                if let Some(selected) = selected {
+                    if !found.iter().any(|other| std::ptr::eq(*other, *selected)) {
+                        found.push(selected);
+                    }
+                    continue;
+                }
+                if let Some(index) = args.index {
+                    println!("There is no mod #{index} for '{pattern}'.");
+                }
+                println!("No installed mod is named '{pattern}'. Did you mean:");
+                for (i, candidate) in candidates.iter().enumerate() {
+                    println!("  {}. {}", i + 1, candidate.manifest.name);
+                }
+                println!("Run again with --index <N> to show one of them.");
+            }
+            for (i, local_mod) in found.into_iter().enumerate() {
+                if i > 0 {
+                    println!();
+                }
                 println!(
                     "📂 {}",
                     fileutil::replace_home_dir_with_tilde(&local_mod.location)
                 );
+                if args.deps_tree {
+                    for line in local_mod::render_dependency_tree(local_mod, &local_mods) {
+                        println!("{line}");
+                    }
+                    continue;
+                }
+                if let Some(algorithm) = args.checksum {
+                    let mut algorithms = vec![fileutil::ChecksumAlgorithm::Xxh64];
+                    if algorithm != fileutil::ChecksumAlgorithm::Xxh64 {
+                        algorithms.push(algorithm);
+                    }
+                    let hashes = fileutil::hash_file_with(&local_mod.location, &algorithms)?;
+                    for (algorithm, hash) in algorithms.iter().zip(hashes) {
+                        println!("🔑 {}: {}", algorithm.label(), hash);
+                    }
+                }
+                if args.provenance {
+                    match zip::read_comment(&local_mod.location)? {
+                        Some(comment) => println!("📜 Provenance: {comment}"),
+                        None => println!("📜 Provenance: none"),
+                    }
+                }
                 println!("- Name: {}", local_mod.manifest.name);
-                println!("  Version: {}", local_mod.manifest.version);
+                println!("  Version: {}", local_mod.manifest.display_version());
                 if let Some(deps) = &local_mod.manifest.dependencies {
                     println!("  Dependencies:");
                     for dep in deps {
@@ -149,124 +870,1236 @@ async fn run() -> Result<()> {
                         }
                     }
                 }
-            } else {
-                println!("The mod '{}' is not currently installed.", args.name);
             }
+            Ok(())
         }
 
-        Commands::Install(_) | Commands::Update(_) => {
-            let semaphore = Arc::new(tokio::sync::Semaphore::new(6));
-            let client = reqwest::ClientBuilder::new()
-                .use_rustls_tls()
-                .https_only(true)
-                .gzip(true)
-                .build()
-                .unwrap_or_default();
+        // Compare the dependencies of an installed mod with the dependency graph.
+        Commands::DepCheck(args) => {
+            let config = open_config(cli, command)?;
+            let (_, local_mods) = load_installed_mods(cli, &config)?;
+            let name = &args.name;
+            let (found, _) = local_mod::find_by_patterns(&local_mods, std::slice::from_ref(name));
+            let Some(local_mod) = found.first() else {
+                println!("The mod '{name}' is not currently installed.");
+                let candidates = local_mod::find_candidates(&local_mods, name);
+                if !candidates.is_empty() {
+                    println!("Did you mean:");
+                    for candidate in candidates {
+                        println!("  - {}", candidate.manifest.name);
+                    }
+                }
+                return Ok(());
+            };
 
-            match &cli.command {
-                // Install a mod by fetching its information from the mod registry.
-                Commands::Install(args) => {
-                    let id_str = cli::extract_id(&args.mod_page_url)?;
-                    let mod_id = cli::parse_id(id_str)?;
-
-                    // Fetching online database
-                    let (mod_registry, dependency_graph) =
-                        fetch::fetch_online_database(&client).await?;
-
-                    // Gets the mod name by using the ID from the Remote Mod Registry.
-                    let mod_names = mod_registry.get_mod_name_by_id(mod_id);
-                    if mod_names.is_empty() {
-                        println!("Could not find the mod matches [{mod_id}].");
-                        return Ok(());
-                    };
-                    tracing::info!("Mod names found for ID [{mod_id}]: {:#?}", &mod_names);
+            let client = fetch::build_client(&config);
+            let spinner = download::pb_style::create_spinner("Fetching dependency graph...");
+            let dependency_graph = dependency::DependencyGraph::fetch(&client).await;
+            spinner.finish_and_clear();
+            let dependency_graph = dependency_graph?;
 
-                    tracing::info!("Collecting installed mods names.");
-                    let mut installed_mod_names: HashSet<String> = local_mods
-                        .into_iter()
-                        .map(|installed| installed.manifest.name)
-                        .collect();
+            let manifest = &local_mod.manifest;
+            let (Some(required), Some(optional)) = (
+                dependency_graph.required_deps(&manifest.name),
+                dependency_graph.optional_deps(&manifest.name),
+            ) else {
+                println!(
+                    "Could not find [{}] in the dependency graph.",
+                    manifest.name
+                );
+                return Ok(());
+            };
+            println!(
+                "[{}] {} installed, compared with the dependency graph:",
+                manifest.name,
+                manifest.display_version()
+            );
+            let mut consistent = true;
+            for (label, installed, registry) in [
+                (
+                    "Dependencies",
+                    manifest.dependencies.as_deref().unwrap_or_default(),
+                    required,
+                ),
+                (
+                    "Optional Dependencies",
+                    manifest
+                        .optional_dependencies
+                        .as_deref()
+                        .unwrap_or_default(),
+                    optional,
+                ),
+            ] {
+                let diff = dependency::diff_dependencies(installed, registry);
+                if diff.is_empty() {
+                    continue;
+                }
+                consistent = false;
+                println!("  {label}:");
+                for added in &diff.added {
+                    println!("    + {added} (only in the dependency graph)");
+                }
+                for removed in &diff.removed {
+                    println!("    - {removed} (only in the installed everest.yaml)");
+                }
+            }
+            if consistent {
+                println!("✅ Both declare the same dependencies.");
+            }
+            Ok(())
+        }
 
-                    tracing::info!("Starting installation process.");
-                    for mod_name in mod_names {
-                        if installed_mod_names.contains(mod_name) {
-                            println!("You already have [{mod_name}] installed.");
-                            continue;
-                        }
+        Commands::Install(args) => install(cli, open_config(cli, command)?, args, report).await,
+        Commands::InstallPack(args) => {
+            install_pack(cli, open_config(cli, command)?, args, report).await
+        }
+        Commands::Update(args) => update(cli, open_config(cli, command)?, args, report).await,
+    }
+}
 
-                        let downloadable_mods = dependency_graph.check_dependencies(
-                            mod_name,
-                            &mod_registry,
-                            &installed_mod_names,
-                        );
+/// Loads the configuration for the command, and reminds of an interrupted install.
+///
+/// # Errors
+///
+/// Returns an error if the configuration is invalid or the mods directory can't be determined.
+fn open_config(cli: &Cli, command: &Commands) -> Result<Arc<Config>> {
+    let config = Config::new(cli)?;
 
-                        if downloadable_mods.is_empty() {
-                            println!("All dependencies for mod [{mod_name}] are already installed");
-                            continue;
-                        }
+    // Determine the mods directory.
+    let mods_directory = config.directory();
+    tracing::info!(
+        "Using mods directory: '{}'",
+        fileutil::replace_home_dir_with_tilde(mods_directory)
+    );
+    tracing::info!("Mirror preference: {}", config.mirror_preferences());
 
-                        println!("Downloading mod [{mod_name}] and its dependencies...");
-                        download::download_mods_concurrently(
-                            &client,
-                            &downloadable_mods,
-                            config.clone(),
-                            &semaphore,
-                        )
-                        .await?;
+    if !matches!(command, Commands::Install(_) | Commands::InstallPack(_)) {
+        offer_resume(&config);
+    }
+    Ok(config)
+}
+
+/// Finds and reads the installed mods, returns the paths of the archives and the mods that
+/// could be read.
+///
+/// # Errors
+///
+/// Returns an error if the mods directory can't be read, or if an archive can't be read with
+/// `--strict`.
+fn load_installed_mods(
+    cli: &Cli,
+    config: &Config,
+) -> Result<(Vec<std::path::PathBuf>, Vec<LocalMod>)> {
+    // Gathering mod paths
+    let phase = Instant::now();
+    let archive_paths = config.find_installed_mod_archives()?;
+    bench::record("scan", phase);
+
+    let phase = Instant::now();
+    let (local_mods, failures) = LocalMod::load_local_mods_with_failures(&archive_paths);
+    bench::record("parse", phase);
+    if !failures.is_empty() && (cli.keep_going || cli.strict) {
+        let failures: Vec<String> = failures
+            .iter()
+            .map(|failure| format!("  - {failure}"))
+            .collect();
+        if cli.strict {
+            return Err(CliError::Parse(anyhow::anyhow!(
+                "{} mod archive(s) could not be read (--strict):\n{}",
+                failures.len(),
+                failures.join("\n")
+            ))
+            .into());
+        }
+        eprintln!("⚠️  {} mod archive(s) could not be read:", failures.len());
+        for failure in failures {
+            eprintln!("{failure}");
+        }
+    }
+    Ok((archive_paths, local_mods))
+}
+
+/// What the downloads of an `install`, `install-pack` or `update` run share.
+struct DownloadSession {
+    config: Arc<Config>,
+    client: reqwest::Client,
+    limiter: Arc<download::ConnectionLimiter>,
+    fail_fast: Arc<download::FailFast>,
+}
+
+impl DownloadSession {
+    /// Prepares the downloads of a run, after checking that the mods directory is writable and
+    /// that the game isn't running if anything is downloaded.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the mods directory isn't writable or the game is running.
+    async fn start(cli: &Cli, config: Arc<Config>, downloads: bool) -> Result<Self> {
+        if downloads {
+            config.ensure_directory_writable()?;
+            ensure_game_not_running(cli.force)?;
+        }
+        if config.verification_policy() == download::VerificationPolicy::WarnOnly {
+            eprintln!(
+                "{}",
+                console::style(
+                    "⚠️  --no-verify: files that don't match the registry checksums will be installed anyway."
+                )
+                .red()
+                .bold()
+            );
+        }
+
+        let limiter = Arc::new(download::ConnectionLimiter::new(
+            config.jobs(),
+            config.max_connections_per_host(),
+        ));
+        // Shared by every download of the run, so that a batch stops as a whole.
+        let fail_fast = Arc::new(download::FailFast::new(config.fail_fast_threshold()));
+        let client = fetch::build_client(&config);
+
+        // The mirrors are pinged once, and the order is used for every download of the run.
+        let config = if downloads && cli.mirror_strategy == MirrorStrategy::Fastest {
+            let spinner = download::pb_style::create_spinner("Pinging mirrors...");
+            let results = ping::ping_mirrors(&client).await;
+            spinner.finish_and_clear();
+            let mirror_preferences = ping::order_by_latency(&results, config.mirror_preferences());
+            tracing::info!("Mirror preference by latency: {}", mirror_preferences);
+            println!(
+                "🏁 Fastest mirrors first: {}",
+                mirror_preferences.replace(',', " > ")
+            );
+            config.with_mirror_preferences(mirror_preferences)
+        } else {
+            config
+        };
+        Ok(Self {
+            config,
+            client,
+            limiter,
+            fail_fast,
+        })
+    }
+}
+
+/// Mods requested by `install` or `install-pack`, and how they are installed.
+struct InstallRequest<'a> {
+    /// The mods in the order given, by page URL or by name
+    mods: Vec<pack::PackMod>,
+    /// The interrupted batch finished with `--resume`
+    resumed: Option<InstallState>,
+    /// Whether the mods are installed as a batch, which carries on after a failed mod
+    is_batch: bool,
+    explain: bool,
+    assume_installed: &'a [String],
+    no_deps_on_error: bool,
+    dry_run: bool,
+    show_mirror: bool,
+    /// Asks before downloading more dependencies than this, never if `None`
+    confirm_deps_over: Option<usize>,
+    on_version_conflict: VersionConflictPolicy,
+    /// Whether a requested version older than the registry's is downloaded from GameBanana
+    installs_older_versions: bool,
+}
 
-                        // Prevent duplicate downloads
-                        for (mod_name, _) in downloadable_mods {
-                            installed_mod_names.insert(mod_name);
+/// Installs the mods given by page URL, by name or in the list of `--from-list`, or finishes
+/// the interrupted batch with `--resume`.
+///
+/// # Errors
+///
+/// Returns an error if the mod fails to install, or if any mod of a batch does.
+async fn install(
+    cli: &Cli,
+    config: Arc<Config>,
+    args: &cli::InstallArgs,
+    report: &mut Report,
+) -> Result<()> {
+    let (_, local_mods) = load_installed_mods(cli, &config)?;
+    let session = DownloadSession::start(cli, config, !args.dry_run).await?;
+    let resumed = if args.resume {
+        Some(read_resumable_state(&session.config)?)
+    } else {
+        None
+    };
+    let page_urls = match &args.from_list {
+        Some(path) => {
+            let contents = fs::read_to_string(path).with_context(|| {
+                format!(
+                    "Failed to read the list of mods '{}'",
+                    fileutil::replace_home_dir_with_tilde(path)
+                )
+            })?;
+            cli::parse_url_list(&contents)
+        }
+        None => args.mod_page_url.iter().cloned().collect(),
+    };
+    let mods: Vec<_> = match &resumed {
+        Some(state) => state.pending.clone(),
+        None => page_urls
+            .into_iter()
+            .map(|url| pack::PackMod {
+                source: PackSource::Url(url),
+                version: args.mod_version.clone(),
+            })
+            .chain(args.names.iter().cloned().map(|name| pack::PackMod {
+                source: PackSource::Name(name),
+                version: None,
+            }))
+            .collect(),
+    };
+    let request = InstallRequest {
+        mods,
+        is_batch: resumed.is_some() || args.from_list.is_some() || !args.names.is_empty(),
+        resumed,
+        explain: args.explain,
+        assume_installed: &args.assume_installed,
+        no_deps_on_error: args.no_deps_on_error,
+        dry_run: args.dry_run,
+        show_mirror: args.show_mirror,
+        confirm_deps_over: Some(args.confirm_deps_over),
+        on_version_conflict: args.on_version_conflict,
+        installs_older_versions: args.installs_older_versions(),
+    };
+    install_mods(cli, session, request, &local_mods, report).await
+}
+
+/// Installs the mods listed in a pack file, as a batch.
+///
+/// # Errors
+///
+/// Returns an error if the pack can't be read, or if any of its mods fails to install.
+async fn install_pack(
+    cli: &Cli,
+    config: Arc<Config>,
+    args: &cli::InstallPackArgs,
+    report: &mut Report,
+) -> Result<()> {
+    let (_, local_mods) = load_installed_mods(cli, &config)?;
+    let session = DownloadSession::start(cli, config, true).await?;
+    let request = InstallRequest {
+        mods: read_pack(&args.path)?.mods(),
+        resumed: None,
+        is_batch: true,
+        explain: args.explain,
+        assume_installed: &[],
+        no_deps_on_error: args.no_deps_on_error,
+        dry_run: false,
+        show_mirror: false,
+        confirm_deps_over: None,
+        on_version_conflict: VersionConflictPolicy::Warn,
+        installs_older_versions: false,
+    };
+    install_mods(cli, session, request, &local_mods, report).await
+}
+
+/// Installs the requested mods and their dependencies, skipping the ones already installed.
+///
+/// # Errors
+///
+/// Returns an error if a single mod fails to install, or if any mod of a batch does.
+async fn install_mods(
+    cli: &Cli,
+    session: DownloadSession,
+    request: InstallRequest<'_>,
+    local_mods: &[LocalMod],
+    report: &mut Report,
+) -> Result<()> {
+    let DownloadSession {
+        config,
+        client,
+        limiter,
+        fail_fast,
+    } = session;
+    let InstallRequest {
+        mods: requested_mods,
+        resumed,
+        is_batch,
+        explain,
+        assume_installed,
+        no_deps_on_error,
+        dry_run,
+        show_mirror,
+        confirm_deps_over,
+        on_version_conflict,
+        installs_older_versions,
+    } = request;
+    tracing::info!("Mods to install: {:#?}", &requested_mods);
+
+    // Saved after each mod, so that an interrupted batch can be resumed.
+    let state_path = install_state::install_state_path();
+    let mut install_state = match resumed {
+        Some(state) => Some(state),
+        None if is_batch && !dry_run => {
+            if let Some(stale) = state_path
+                .as_deref()
+                .and_then(|path| install_state::read(path).ok().flatten())
+            {
+                println!(
+                    "⚠️  The interrupted install of {} remaining mod(s) is replaced by this one.",
+                    stale.pending.len()
+                );
+            }
+            Some(InstallState::new(config.directory(), &requested_mods))
+        }
+        None => None,
+    };
+    save_install_state(state_path.as_deref(), install_state.as_ref());
+
+    // Outcome of each requested mod, reported at the end of a batch.
+    let mut outcomes: Vec<(String, Result<()>)> = Vec::new();
+
+    // Mods given by page URL are looked up by their GameBanana ID.
+    let mut mod_ids = Vec::new();
+    for requested in requested_mods {
+        let PackSource::Url(url) = &requested.source else {
+            mod_ids.push((requested, None));
+            continue;
+        };
+        let mod_id = cli::parse_mod_page_url(url).map_err(anyhow::Error::from);
+        match mod_id {
+            Ok(mod_id) => mod_ids.push((requested, Some(mod_id))),
+            Err(err) if is_batch => outcomes.push((url.clone(), Err(err))),
+            Err(err) => return Err(err),
+        }
+    }
+    if mod_ids.is_empty() {
+        print_install_summary(&outcomes, dry_run);
+        return Err(CliError::Usage(anyhow::anyhow!("No valid mod page URL to install")).into());
+    }
+
+    // Fetching online database
+    let (mod_registry, dependency_graph) = fetch::fetch_registry_and_graph(&client).await?;
+    // Without the dependency graph, only the requested mods themselves are
+    // installed, as an empty graph resolves each mod to itself.
+    let dependency_graph = match dependency_graph {
+        Ok(dependency_graph) => dependency_graph,
+        Err(err) => {
+            println!("⚠️  {err:#}");
+            if !no_deps_on_error
+                && !confirm("Install only the requested mods, without their dependencies?")
+            {
+                return Err(err.context("Use --no-deps-on-error to install without dependencies"));
+            }
+            tracing::warn!("Dependency resolution skipped: {:#}", err);
+            println!(
+                "{}",
+                console::style(
+                    "⚠️  Dependency resolution was skipped: install the dependencies yourself once the dependency graph is available again."
+                )
+                .red()
+                .bold()
+            );
+            dependency::DependencyGraph::new()
+        }
+    };
+
+    // Gets the mod names by using the IDs from the Remote Mod Registry.
+    let mut pages = Vec::new();
+    // Mods installed at an older version than the registry's, whose
+    // dependencies are still installed.
+    let mut historical_mods: HashSet<String> = HashSet::new();
+    for (requested, mod_id) in mod_ids {
+        let url = requested.label().to_string();
+        let Some(mod_id) = mod_id else {
+            let name = manifest::normalize_name(requested.label());
+            let Some((mod_name, remote_mod)) = mod_registry.get_key_value(name.as_ref()) else {
+                println!("Could not find the mod [{url}] in the registry.");
+                outcomes.push((
+                    url,
+                    Err(anyhow::anyhow!("no mod is named [{name}] in the registry")),
+                ));
+                continue;
+            };
+            if let Some(version) = requested
+                .version
+                .as_ref()
+                .filter(|version| **version != remote_mod.version)
+            {
+                println!(
+                    "⚠️  The pack lists [{mod_name}] {version}, but the registry only provides {}.",
+                    remote_mod.version
+                );
+            }
+            pages.push((url, vec![mod_name]));
+            continue;
+        };
+        let (mod_names, other_submissions): (Vec<_>, Vec<_>) = mod_registry
+            .get_mod_name_by_id(mod_id)
+            .into_iter()
+            .partition(|name| mod_registry[*name].is_mod_submission());
+        for name in other_submissions {
+            let submission_type = mod_registry[name]
+                .gamebanana_type
+                .as_deref()
+                .unwrap_or_default();
+            tracing::warn!(
+                "[{}] matches the ID [{}] but is a '{}' submission",
+                name,
+                mod_id,
+                submission_type
+            );
+            println!(
+                "⚠️  [{name}] matches the ID [{mod_id}] but is a GameBanana '{submission_type}' submission, not a mod. Skipping."
+            );
+        }
+        if mod_names.is_empty() {
+            println!("Could not find the mod matches [{mod_id}].");
+            if is_batch {
+                outcomes.push((
+                    url,
+                    Err(anyhow::anyhow!("no mod matches the ID [{mod_id}]")),
+                ));
+            }
+            continue;
+        };
+        tracing::info!("Mod names found for ID [{mod_id}]: {:#?}", &mod_names);
+        // Only `install --version` installs older files, packs keep a warning.
+        let version = requested
+            .version
+            .as_deref()
+            .filter(|_| installs_older_versions)
+            .filter(|version| {
+                mod_names.iter().all(|name| {
+                    mod_registry[*name].version.trim_start_matches('v')
+                        != version.trim().trim_start_matches('v')
+                })
+            });
+        if let Some(version) = version {
+            let result = install_historical_version(
+                &client,
+                &config,
+                &mod_registry,
+                mod_id,
+                &mod_names,
+                version,
+                dry_run,
+            )
+            .await;
+            match result {
+                Ok(mod_name) => {
+                    historical_mods.insert(mod_name.clone());
+                }
+                Err(err) if is_batch => {
+                    outcomes.push((url, Err(err)));
+                    continue;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        pages.push((url, mod_names));
+    }
+
+    tracing::info!("Collecting installed mods names.");
+    let installed_versions: HashMap<String, String> = local_mods
+        .iter()
+        .map(|installed| {
+            (
+                manifest::normalize_name(&installed.manifest.name).into_owned(),
+                installed.manifest.version.clone(),
+            )
+        })
+        .collect();
+    let mut installed_mod_names: HashSet<String> = installed_versions.keys().cloned().collect();
+
+    // Treat mods provided out-of-band as installed so they are not downloaded.
+    if !assume_installed.is_empty() {
+        let referenced: HashSet<String> = pages
+            .iter()
+            .flat_map(|(_, mod_names)| mod_names)
+            .flat_map(|name| dependency_graph.collect_all_dependencies_bfs(name))
+            .collect();
+        for name in assume_installed {
+            let normalized = manifest::normalize_name(name).into_owned();
+            if referenced.contains(&normalized) {
+                tracing::info!("Assuming installed: {}", normalized);
+                installed_mod_names.insert(normalized);
+            } else {
+                println!(
+                    "⚠️  [{name}] is not a dependency of the requested mod, ignoring --assume-installed for it."
+                );
+            }
+        }
+    }
+
+    // Minimums required by the installed mods and the whole batch can conflict too.
+    let other_mods: Vec<String> = installed_versions
+        .keys()
+        .cloned()
+        .chain(
+            pages
+                .iter()
+                .flat_map(|(_, mod_names)| mod_names.iter().map(|name| name.to_string())),
+        )
+        .collect();
+
+    tracing::info!("Starting installation process.");
+    let everest_version = everest::detect_installed_version(config.directory());
+    tracing::info!("Installed Everest version: {:?}", everest_version);
+    for (url, mod_names) in pages {
+        if fail_fast.is_aborted() {
+            // The interrupted install state keeps the remaining mods.
+            break;
+        }
+        let result = async {
+            for mod_name in mod_names {
+                if installed_mod_names.contains(mod_name) {
+                    println!("You already have [{mod_name}] installed.");
+                    continue;
+                }
+
+                let phase = Instant::now();
+                let plan = resolver::resolve(
+                    mod_name,
+                    &dependency_graph,
+                    &mod_registry,
+                    &installed_mod_names,
+                );
+                bench::record("resolution", phase);
+
+                if let Some(required) =
+                    dependency_graph.required_everest_version(mod_name)
+                {
+                    match &everest_version {
+                        Some(installed)
+                            if manifest::compare_versions(installed, &required)
+                                .is_some_and(|ordering| ordering.is_lt()) =>
+                        {
+                            println!(
+                                "⚠️  [{mod_name}] requires Everest >= {required}, but Everest {installed} is installed. Update Everest or the mod won't load."
+                            );
                         }
+                        Some(_) => {}
+                        None => println!(
+                            "Could not determine the installed Everest version. [{mod_name}] requires Everest >= {required}."
+                        ),
                     }
                 }
-                Commands::Update(args) => {
-                    // Filter installed mods according to the `updaterblacklist.txt`
-                    if let Some(updater_blacklist) = config.read_updater_blacklist()? {
-                        local_mods
-                            .retain(|local_mod| !updater_blacklist.contains(&local_mod.location));
+
+                for name in &plan.unavailable {
+                    println!(
+                        "⚠️  [{name}] is required by [{mod_name}] but is not available in the registry."
+                    );
+                }
+                // A mod may be installed under another name than its key in
+                // the registry, so the files are compared too.
+                let mut downloadable_mods = plan.downloads;
+                downloadable_mods.retain(|(name, remote_mod)| {
+                    if historical_mods.contains(name) {
+                        return false;
                     }
+                    let Some(local_mod) =
+                        local_mod::find_by_checksum(local_mods, remote_mod)
+                    else {
+                        return true;
+                    };
+                    println!(
+                        "[{name}] is already installed as '{}' (matched by checksum).",
+                        fileutil::replace_home_dir_with_tilde(&local_mod.location)
+                    );
+                    installed_mod_names.insert(name.clone());
+                    false
+                });
 
-                    // Update installed mods by checking for available updates in the mod registry.
-                    let spinner = download::pb_style::create_spinner();
-                    let mod_registry = RemoteModRegistry::fetch(&client).await?;
-                    spinner.finish_and_clear();
-                    drop(spinner);
-
-                    let registry = Arc::new(mod_registry);
-
-                    let available_updates = registry.check_updates(&local_mods);
-
-                    if available_updates.is_empty() {
-                        println!("All mods are up to date!");
-                    } else if args.install {
-                        println!();
-                        println!("Installing updates...");
-                        download::download_mods_concurrently(
-                            &client,
-                            &available_updates,
-                            config,
-                            &semaphore,
-                        )
-                        .await?;
-                    } else {
-                        println!();
-                        println!("Run with --install to install these updates");
+                let unsatisfied = dependency_graph.find_unsatisfied_minimums(
+                    mod_name,
+                    &mod_registry,
+                    &installed_versions,
+                );
+                let other_mods: Vec<&str> = other_mods
+                    .iter()
+                    .map(String::as_str)
+                    .filter(|name| name != mod_name)
+                    .collect();
+                let conflicts = dependency_graph.find_version_conflicts(
+                    mod_name,
+                    &other_mods,
+                    &unsatisfied,
+                );
+                for conflict in &conflicts {
+                    println!("⚠️  {conflict}");
+                }
+                // The other unsatisfied minimums have a single requester.
+                for unsatisfied in unsatisfied.iter().filter(|unsatisfied| {
+                    !conflicts.iter().any(|conflict| {
+                        manifest::normalize_name(&conflict.dependency)
+                            == manifest::normalize_name(&unsatisfied.dependency)
+                    })
+                }) {
+                    println!("⚠️  {unsatisfied}");
+                }
+                if !conflicts.is_empty()
+                    && !dry_run
+                    && on_version_conflict == VersionConflictPolicy::Abort
+                {
+                    anyhow::bail!(
+                        "[{mod_name}] was not installed because of conflicting dependency versions (--on-version-conflict abort)"
+                    );
+                }
+
+                if downloadable_mods.is_empty() {
+                    println!(
+                        "All dependencies for mod [{mod_name}] are already installed"
+                    );
+                    continue;
+                }
+
+                if dry_run {
+                    println!(
+                        "Would download mod [{mod_name}] and its dependencies:"
+                    );
+                    print_dry_run(
+                        &downloadable_mods,
+                        config.mirror_preferences(),
+                        show_mirror,
+                    );
+                    // Later mods of the run are planned as if these were installed.
+                    installed_mod_names.extend(
+                        downloadable_mods.into_iter().map(|(name, _)| name),
+                    );
+                    continue;
+                }
+
+                let dependency_count = downloadable_mods
+                    .iter()
+                    .filter(|(name, _)| name != mod_name)
+                    .count();
+                if let Some(threshold) = confirm_deps_over
+                    && dependency_count > threshold
+                {
+                    println!(
+                        "[{mod_name}] requires {dependency_count} dependencies that aren't installed yet:"
+                    );
+                    print_dry_run(
+                        &downloadable_mods,
+                        config.mirror_preferences(),
+                        false,
+                    );
+                    if !confirm("Download them all?") {
+                        return Err(CliError::Usage(anyhow::anyhow!(
+                            "Installation of [{mod_name}] was cancelled, pass --yes or a higher --confirm-deps-over to install it"
+                        ))
+                        .into());
+                    }
+                }
+
+                println!("Downloading mod [{mod_name}] and its dependencies...");
+                if explain {
+                    let predecessors = dependency_graph
+                        .collect_dependency_predecessors(mod_name);
+                    for (dependency, _) in &downloadable_mods {
+                        let path =
+                            dependency::dependency_path(&predecessors, dependency);
+                        println!("  {}", path.join(" -> "));
                     }
                 }
-                _ => unreachable!(),
+                let phase = Instant::now();
+                let summary = download::download_mods_concurrently(
+                    &client,
+                    &downloadable_mods,
+                    config.clone(),
+                    &limiter,
+                    &fail_fast,
+                )
+                .await;
+                bench::record("download", phase);
+                report.record_installs(&summary);
+                write_checksum_mismatch_report(cli, &summary);
+
+                // Prevent duplicate downloads
+                for (mod_name, _) in &summary.succeeded {
+                    installed_mod_names.insert(mod_name.clone());
+                }
+                summary.ensure_success()?;
+                if cli.verify_after {
+                    verify_after(&summary)?;
+                }
             }
+            Ok(())
+        }
+        .await;
+
+        if let (Ok(()), Some(state)) = (&result, &mut install_state) {
+            state.complete(&url);
+            save_install_state(state_path.as_deref(), Some(state));
+        }
+        match result {
+            Err(err) if !is_batch => return Err(err),
+            result => outcomes.push((url, result)),
         }
     }
 
+    if let (Some(state), Some(path)) = (&install_state, &state_path) {
+        if state.pending.is_empty() {
+            if let Err(err) = install_state::remove(path) {
+                tracing::warn!("Failed to remove the install state: {}", err);
+            }
+        } else {
+            println!(
+                "📝 Run `everest-mod-cli install --resume` to retry the {} mod(s) that weren't installed.",
+                state.pending.len()
+            );
+        }
+    }
+    if is_batch {
+        print_install_summary(&outcomes, dry_run);
+        let failed = outcomes.iter().filter(|(_, r)| r.is_err()).count();
+        if failed > 0 {
+            anyhow::bail!("{failed} of {} mods failed to install", outcomes.len());
+        }
+    }
     Ok(())
 }
 
+/// Checks the installed mods for updates, and installs them with `--install`.
+///
+/// # Errors
+///
+/// Returns an error if the registry can't be fetched, or if any update fails to install.
+async fn update(
+    cli: &Cli,
+    config: Arc<Config>,
+    args: &cli::UpdateArgs,
+    report: &mut Report,
+) -> Result<()> {
+    let (_, mut local_mods) = load_installed_mods(cli, &config)?;
+    let DownloadSession {
+        config,
+        client,
+        limiter,
+        fail_fast,
+    } = DownloadSession::start(cli, config, args.install).await?;
+
+    // Mods excluded from the update still require their dependencies.
+    let installed_mods = local_mods.clone();
+
+    // Restrict installed mods to the `whitelist.txt`, then subtract the
+    // `updaterblacklist.txt`
+    if let Some(updater_whitelist) = config.read_updater_whitelist()? {
+        local_mods.retain(|local_mod| updater_whitelist.contains(&local_mod.location));
+    }
+    if let Some(updater_blacklist) = config.read_updater_blacklist()? {
+        local_mods.retain(|local_mod| !updater_blacklist.contains(&local_mod.location));
+    }
+
+    // Update installed mods by checking for available updates in the mod registry.
+    let phase = Instant::now();
+    let mod_registry = if let Some(path) = &args.registry_file {
+        let mod_registry = mod_registry::read_snapshot(path)?;
+        println!(
+            "📝 Using the registry snapshot '{}' ({} mods)",
+            fileutil::replace_home_dir_with_tilde(path),
+            mod_registry.len()
+        );
+        mod_registry
+    } else {
+        let spinner = download::pb_style::create_spinner("Fetching online database...");
+        let mod_registry = fetch::retry_with_backoff(
+            REGISTRY_FETCH_ATTEMPTS,
+            Duration::from_secs(REGISTRY_RETRY_DELAY_SECS),
+            || RemoteModRegistry::fetch(&client),
+            |attempt, err| {
+                spinner
+                    .suspend(|| println!("⚠️  {err}, retrying registry fetch (attempt {attempt})"))
+            },
+        )
+        .await?;
+        spinner.finish_and_clear();
+        mod_registry
+    };
+    bench::record("registry fetch", phase);
+
+    let registry = Arc::new(mod_registry);
+
+    let phase = Instant::now();
+    let pins = match pin::pins_path() {
+        Some(path) => pin::read(&path)?,
+        None => pin::Pins::new(),
+    };
+    let last_checks_path = update_check::last_checks_path();
+    let mut last_checks = match &last_checks_path {
+        Some(path) if !args.full => update_check::read(path).unwrap_or_else(|err| {
+            tracing::warn!("{:#}", err);
+            update_check::LastChecks::new()
+        }),
+        _ => update_check::LastChecks::new(),
+    };
+    let mut available_updates = registry.check_updates(&local_mods, &pins, &mut last_checks);
+    bench::record("verify", phase);
+    if let Some(path) = &last_checks_path {
+        // Mods that are no longer installed are forgotten.
+        last_checks.retain(|name, _| {
+            installed_mods.iter().any(|local_mod| {
+                manifest::normalize_name(&local_mod.manifest.name) == name.as_str()
+            })
+        });
+        if let Err(err) = update_check::write(path, &last_checks) {
+            tracing::warn!("Failed to save the last update checks: {:#}", err);
+        }
+    }
+
+    if let Some(newest) = args.newest
+        && available_updates.len() > newest
+    {
+        println!(
+            "Installing the {} most recently updated of {} updates (--newest).",
+            newest,
+            available_updates.len()
+        );
+        mod_registry::sort_by_newest(&mut available_updates);
+        available_updates.truncate(newest);
+    }
+
+    if !available_updates.is_empty() {
+        warn_orphaned_dependencies(&client, &installed_mods, &available_updates).await;
+    }
+
+    if available_updates.is_empty() {
+        println!("All mods are up to date!");
+    } else if args.install {
+        println!();
+        println!("Installing updates...");
+        let phase = Instant::now();
+        let summary = download::download_mods_concurrently(
+            &client,
+            &available_updates,
+            config,
+            &limiter,
+            &fail_fast,
+        )
+        .await;
+        bench::record("download", phase);
+        write_checksum_mismatch_report(cli, &summary);
+        report.record_updates(&summary, |name| {
+            local_mods
+                .iter()
+                .find(|local_mod| manifest::normalize_name(&local_mod.manifest.name) == name)
+                .map(|local_mod| local_mod.manifest.version.clone())
+        });
+
+        // Nothing to log when every update failed.
+        if let Some(log_path) = &args.log_updates
+            && !report.updated().is_empty()
+        {
+            let written = match log_path {
+                Some(path) => Ok(path.clone()),
+                None => report::default_update_log_path(),
+            }
+            .and_then(|path| report::append_update_log(&path, report.updated()).map(|_| path));
+            match written {
+                Ok(path) => println!(
+                    "📝 Update log written to {}",
+                    fileutil::replace_home_dir_with_tilde(&path)
+                ),
+                Err(err) => {
+                    tracing::error!("{:#}", err);
+                    eprintln!("Failed to write the update log: {err:#}");
+                }
+            }
+        }
+
+        summary.ensure_success()?;
+        if cli.verify_after {
+            verify_after(&summary)?;
+        }
+    } else {
+        println!();
+        println!("Run with --install to install these updates");
+    }
+    Ok(())
+}
+
+/// Installs an older version of a mod than the registry has, from the files of its GameBanana
+/// page, for `install --version`.
+///
+/// Returns the name of the mod, whose dependencies are then installed as usual.
+///
+/// # Errors
+///
+/// Returns an error if the page has several mods, if no file of the version is found, or if
+/// the download fails or isn't that version of the mod.
+async fn install_historical_version(
+    client: &reqwest::Client,
+    config: &Config,
+    mod_registry: &RemoteModRegistry,
+    mod_id: u32,
+    mod_names: &[&String],
+    version: &str,
+    dry_run: bool,
+) -> Result<String> {
+    let [mod_name] = mod_names else {
+        return Err(CliError::Usage(anyhow::anyhow!(
+            "the page [{mod_id}] has several mods ({}), --version can't tell which one to install",
+            mod_names
+                .iter()
+                .map(|name| name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ))
+        .into());
+    };
+    let latest = &mod_registry[*mod_name].version;
+    println!("The registry has [{mod_name}] {latest}, looking for {version} on GameBanana...");
+    let files = gamebanana::fetch_files(client, mod_id)
+        .await
+        .context("Failed to fetch the files of the mod page")?;
+    let file = gamebanana::find_file(&files, version).map_err(CliError::NotFound)?;
+
+    if dry_run {
+        println!(
+            "Would download [{mod_name}] {version} from '{}' (no checksum available)",
+            file.download_url
+        );
+        return Ok(mod_name.to_string());
+    }
+    let path = download::download_historical_version(
+        client,
+        config,
+        mod_name,
+        version,
+        &file.download_url,
+        file.size,
+    )
+    .await
+    .with_context(|| format!("Failed to install [{mod_name}] {version}"))?;
+    println!(
+        "{}",
+        console::style(format!(
+            "⚠️  [{mod_name}] {version} was installed as '{}' without checksum verification, the registry only has the checksums of {latest}.",
+            fileutil::replace_home_dir_with_tilde(&path)
+        ))
+        .yellow()
+    );
+    println!("📌 Run `pin {mod_name} {version}` so that update keeps this version.");
+    Ok(mod_name.to_string())
+}
+
+/// Prints the installed mods that nothing would require anymore after the given updates.
+///
+/// The dependencies of the new versions are looked up in the dependency graph. If it can't be
+/// fetched, the check is skipped.
+async fn warn_orphaned_dependencies(
+    client: &reqwest::Client,
+    local_mods: &[LocalMod],
+    available_updates: &[(String, mod_registry::RemoteModInfo)],
+) {
+    let spinner = download::pb_style::create_spinner("Fetching the dependency graph...");
+    let dependency_graph = dependency::DependencyGraph::fetch(client).await;
+    spinner.finish_and_clear();
+    let dependency_graph = match dependency_graph {
+        Ok(dependency_graph) => dependency_graph,
+        Err(err) => {
+            tracing::warn!("Skipping the orphaned dependency check: {:#}", err);
+            return;
+        }
+    };
+
+    let updated_dependencies: HashMap<String, Vec<String>> = available_updates
+        .iter()
+        .filter_map(|(name, _)| {
+            let dependencies = dependency_graph.required_deps(name)?;
+            let dependencies = dependencies
+                .iter()
+                .map(|dep| manifest::normalize_name(&dep.name).into_owned())
+                .collect();
+            Some((name.clone(), dependencies))
+        })
+        .collect();
+    let orphaned = local_mod::find_orphaned_after_update(local_mods, &updated_dependencies);
+    if orphaned.is_empty() {
+        return;
+    }
+    tracing::info!("Orphaned after the update: {:?}", orphaned);
+    println!("⚠️  After this update, no installed mod would require these mods anymore:");
+    for name in orphaned {
+        println!("  - {name}");
+    }
+}
+
+/// Checks a mod archive outside of the mods directory against the checksums in the registry.
+///
+/// # Errors
+///
+/// Returns an error if the archive has no valid manifest, if the mod is not in the registry,
+/// or if the file doesn't match any of its checksums.
+fn verify_file(
+    path: &std::path::Path,
+    mod_registry: &HashMap<String, mod_registry::RemoteModInfo>,
+) -> Result<()> {
+    let debug_filename = fileutil::replace_home_dir_with_tilde(path);
+    let local_mod = LocalMod::from_path(path)
+        .with_context(|| format!("Failed to read the manifest of '{debug_filename}'"))?;
+    let name = &local_mod.manifest.name;
+    let hash = local_mod
+        .checksum()
+        .with_context(|| format!("Failed to hash '{debug_filename}'"))?;
+
+    println!("File:     {debug_filename}");
+    println!("Name:     {name}");
+    println!("Version:  {}", local_mod.manifest.display_version());
+    println!("Checksum: {hash}");
+
+    let remote_mod = mod_registry
+        .get(manifest::normalize_name(name).as_ref())
+        .ok_or_else(|| {
+            CliError::NotFound(anyhow::anyhow!("[{name}] is not in the mod registry"))
+        })?;
+    if remote_mod.has_matching_hash(hash) {
+        println!("✅ Matches the registry (version {})", remote_mod.version);
+        Ok(())
+    } else {
+        println!(
+            "❌ Does not match the registry (version {}, expected one of {})",
+            remote_mod.version,
+            remote_mod.checksums.join(", ")
+        );
+        Err(CliError::Checksum(anyhow::anyhow!(
+            "'{}' does not match the registry checksums",
+            debug_filename
+        ))
+        .into())
+    }
+}
+
+/// Parses an `everest.yaml` file, or the one inside a mod archive, and prints its contents
+/// along with the warnings of [`manifest::ModManifest::lint`].
+///
+/// # Errors
+///
+/// Returns an error if the manifest can't be read or parsed.
+fn validate_manifest(path: &std::path::Path) -> Result<()> {
+    let debug_filename = fileutil::replace_home_dir_with_tilde(path);
+    let is_archive = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("zip"));
+    let mut bytes = if is_archive {
+        zip::find_manifest(path)?
+    } else {
+        fs::read(path).with_context(|| format!("Failed to read '{debug_filename}'"))?
+    };
+    // Check for UTF-8 BOM and remove if present
+    if bytes.starts_with(&[0xEF, 0xBB, 0xBF]) {
+        bytes.drain(0..3);
+    }
+
+    let manifest = manifest::ModManifest::from_slice(&bytes).map_err(|err| {
+        CliError::Parse(anyhow::anyhow!(
+            "'{}' is not a valid manifest: {}",
+            debug_filename,
+            err
+        ))
+    })?;
+
+    println!("✅ {debug_filename} parses");
+    println!("Name:    {}", manifest.name);
+    println!("Version: {}", manifest.display_version());
+    for (title, dependencies) in [
+        ("Dependencies", &manifest.dependencies),
+        ("Optional dependencies", &manifest.optional_dependencies),
+    ] {
+        let Some(dependencies) = dependencies.as_deref().filter(|deps| !deps.is_empty()) else {
+            continue;
+        };
+        println!("{title}:");
+        for dependency in dependencies {
+            match &dependency.version {
+                Some(version) => println!("  - {} >= {}", dependency.name, version),
+                None => println!("  - {}", dependency.name),
+            }
+        }
+    }
+
+    let warnings = manifest.lint();
+    if !warnings.is_empty() {
+        println!();
+    }
+    for warning in &warnings {
+        println!("⚠️  {warning}");
+    }
+    Ok(())
+}
+
+/// Reads a pack file and prints what it is about.
+///
+/// # Errors
+///
+/// Returns an error if the pack can't be read or is not valid.
+fn read_pack(path: &std::path::Path) -> Result<pack::Pack> {
+    let debug_filename = fileutil::replace_home_dir_with_tilde(path);
+    let bytes = fs::read(path).with_context(|| format!("Failed to read '{debug_filename}'"))?;
+    let pack = pack::Pack::from_slice(&bytes).map_err(|err| {
+        CliError::Parse(anyhow::anyhow!(
+            "'{}' is not a valid pack: {}",
+            debug_filename,
+            err
+        ))
+    })?;
+
+    println!(
+        "📦 Installing the pack {}",
+        pack.name.as_deref().unwrap_or(&debug_filename)
+    );
+    if let Some(description) = &pack.description {
+        println!("{description}");
+    }
+    Ok(pack)
+}
+
+/// Prints the mods planned by `install --dry-run` with their sizes, and with `--show-mirror`
+/// the URLs each of them would be fetched from, in the order they would be tried.
+fn print_dry_run(
+    downloads: &[(String, &mod_registry::RemoteModInfo)],
+    mirror_preferences: &str,
+    show_mirror: bool,
+) {
+    for (name, remote_mod) in downloads {
+        println!(
+            "  {} ({}, {})",
+            name,
+            remote_mod.version,
+            indicatif::HumanBytes(remote_mod.file_size)
+        );
+        if show_mirror {
+            let mirror_urls =
+                mirror_list::get_all_mirror_urls(&remote_mod.download_url, mirror_preferences);
+            for (i, url) in mirror_urls.iter().enumerate() {
+                println!("    {}. {}", i + 1, url);
+            }
+        }
+    }
+    let total: u64 = downloads
+        .iter()
+        .map(|(_, remote_mod)| remote_mod.file_size)
+        .sum();
+    println!(
+        "  Total: {} mod(s), {}",
+        downloads.len(),
+        indicatif::HumanBytes(total)
+    );
+}
+
+/// Prints the outcome of each mod installed with `install --from-list` or `install-pack`.
+///
+/// With `--dry-run`, nothing was installed, so the mods that would be are marked as such.
+fn print_install_summary(outcomes: &[(String, Result<()>)], dry_run: bool) {
+    println!();
+    for (url, result) in outcomes {
+        match result {
+            Ok(()) if dry_run => println!("🔍 {url}: would install"),
+            Ok(()) => println!("✅ {url}"),
+            Err(err) => println!("❌ {url}: {err:#}"),
+        }
+    }
+}
+
 #[tokio::main]
 async fn main() {
     if let Err(err) = run().await {
         tracing::error!("{:#?}", err);
         eprintln!("Failed to run the command: cause {}", err);
+        if let Some(hint) = err.hint() {
+            eprintln!("{hint}");
+        }
+        std::process::exit(err.exit_code());
     } else {
         tracing::info!("Command completed successfully.");
     }
@@ -3,7 +3,9 @@
 //! This module provides functionality to parse and validate mod manifest files,
 //! which are typically written in YAML format. The manifest file contains essential
 //! information about the mod, such as its name, version, dependencies, and optional dependencies.
-use std::collections::VecDeque;
+use std::{borrow::Cow, cmp::Ordering, collections::VecDeque};
+
+use icu_normalizer::ComposingNormalizerBorrowed;
 
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
@@ -28,7 +30,9 @@ pub struct ModManifest {
     #[serde(rename = "Name")]
     pub name: String,
     /// A version string of the mod. Not all mods follow semantic versioning. So this is a string.
-    #[serde(rename = "Version")]
+    ///
+    /// Empty if the manifest doesn't have one, which happens with some minimal mods.
+    #[serde(rename = "Version", default)]
     pub version: String,
     /// A path to the custom code file.
     #[serde(rename = "DLL")]
@@ -53,6 +57,47 @@ pub struct Dependency {
     pub version: Option<String>,
 }
 
+impl Dependency {
+    /// Checks if the given version satisfies this dependency.
+    ///
+    /// The version of a dependency is a minimum: any version equal to or higher than it
+    /// satisfies the dependency. A dependency without a version, or with a version that can't
+    /// be compared, is always satisfied.
+    pub fn is_satisfied_by(&self, version: &str) -> bool {
+        self.version
+            .as_deref()
+            .and_then(|minimum| compare_versions(version, minimum))
+            .is_none_or(|ordering| ordering.is_ge())
+    }
+}
+
+/// Compares two version strings component by component (e.g., `1.10.0` > `1.9.2`).
+///
+/// Missing components count as `0`, and anything after the leading digits of a component
+/// (e.g., `-beta`) is ignored. Returns `None` if either version has no leading number.
+pub fn compare_versions(a: &str, b: &str) -> Option<Ordering> {
+    let parse = |version: &str| -> Option<Vec<u64>> {
+        version
+            .trim()
+            .trim_start_matches(['v', 'V'])
+            .split('.')
+            .map(|component| {
+                let digits: String = component.chars().take_while(char::is_ascii_digit).collect();
+                digits.parse().ok()
+            })
+            .collect()
+    };
+    let (a, b) = (parse(a)?, parse(b)?);
+    let len = a.len().max(b.len());
+    let component = |v: &[u64], i: usize| v.get(i).copied().unwrap_or(0);
+    Some(
+        (0..len)
+            .map(|i| component(&a, i).cmp(&component(&b, i)))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(Ordering::Equal),
+    )
+}
+
 impl ModManifest {
     /// Deserialize an instance of type ModManifest from bytes of YAML text.
     ///
@@ -69,6 +114,207 @@ impl ModManifest {
             None => Err(ManifestParseError::NoModEntries),
         }
     }
+
+    /// Returns the version for display, or `unknown` if the manifest doesn't have one.
+    pub fn display_version(&self) -> &str {
+        if self.version.trim().is_empty() {
+            "unknown"
+        } else {
+            &self.version
+        }
+    }
+
+    /// Checks the manifest for common mistakes which don't prevent it from being parsed.
+    ///
+    /// Returns a warning message for each mistake found.
+    pub fn lint(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if self.name.trim().is_empty() {
+            warnings.push("Name is empty".to_string());
+        }
+        if self.version.trim().is_empty() {
+            warnings.push("Version is empty".to_string());
+        } else if compare_versions(&self.version, &self.version).is_none() {
+            warnings.push(format!(
+                "Version '{}' does not start with a number, so it can't be compared",
+                self.version
+            ));
+        }
+
+        let name = normalize_name(&self.name);
+        let required = self.dependencies.as_deref().unwrap_or_default();
+        let optional = self.optional_dependencies.as_deref().unwrap_or_default();
+        for (field, dependencies) in [
+            ("Dependencies", required),
+            ("OptionalDependencies", optional),
+        ] {
+            let mut seen = Vec::new();
+            for dependency in dependencies {
+                let dependency_name = normalize_name(&dependency.name);
+                if dependency_name.is_empty() {
+                    warnings.push(format!("{field} has an entry without a name"));
+                    continue;
+                }
+                if !name.is_empty() && dependency_name == name {
+                    warnings.push(format!("{field} lists the mod itself"));
+                }
+                if seen.contains(&dependency_name) {
+                    warnings.push(format!(
+                        "{field} lists '{}' more than once",
+                        dependency.name
+                    ));
+                }
+                seen.push(dependency_name);
+            }
+        }
+        for dependency in optional {
+            let dependency_name = normalize_name(&dependency.name);
+            if required
+                .iter()
+                .any(|required| normalize_name(&required.name) == dependency_name)
+            {
+                warnings.push(format!(
+                    "'{}' is listed in both Dependencies and OptionalDependencies",
+                    dependency.name
+                ));
+            }
+        }
+        warnings
+    }
+}
+
+/// Normalizes a mod name for matching: trims surrounding whitespace and applies Unicode NFC.
+///
+/// Registry keys and manifest names occasionally differ only by trailing whitespace or by
+/// normalization form, so every name used as a lookup key should go through this function.
+/// The original name should still be used for display.
+pub fn normalize_name(name: &str) -> Cow<'_, str> {
+    ComposingNormalizerBorrowed::new_nfc().normalize(name.trim())
+}
+
+#[cfg(test)]
+mod tests_lint {
+    use super::*;
+
+    #[test]
+    fn test_lint_valid_manifest() -> anyhow::Result<()> {
+        let manifest = ModManifest::from_slice(
+            b"- Name: MyMod\n  Version: 1.0.0\n  Dependencies:\n    - Name: Everest\n      Version: 1.4000.0\n",
+        )?;
+        assert!(manifest.lint().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_lint_common_mistakes() -> anyhow::Result<()> {
+        let yaml = r#"
+- Name: MyMod
+  Version: ""
+  Dependencies:
+    - Name: MyMod
+    - Name: FrostHelper
+    - Name: FrostHelper
+  OptionalDependencies:
+    - Name: FrostHelper
+    - Name: ""
+"#;
+        let warnings = ModManifest::from_slice(yaml.as_bytes())?.lint();
+        assert_eq!(
+            warnings,
+            [
+                "Version is empty",
+                "Dependencies lists the mod itself",
+                "Dependencies lists 'FrostHelper' more than once",
+                "OptionalDependencies has an entry without a name",
+                "'FrostHelper' is listed in both Dependencies and OptionalDependencies",
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_lint_empty_name_and_odd_version() -> anyhow::Result<()> {
+        let manifest = ModManifest::from_slice(b"- Name: \" \"\n  Version: dev\n")?;
+        assert_eq!(
+            manifest.lint(),
+            [
+                "Name is empty",
+                "Version 'dev' does not start with a number, so it can't be compared"
+            ]
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests_normalize_name {
+    use super::*;
+
+    #[test]
+    fn test_normalize_name_unchanged() {
+        assert!(matches!(
+            normalize_name("SpringCollab2020"),
+            Cow::Borrowed("SpringCollab2020")
+        ));
+    }
+
+    #[test]
+    fn test_normalize_name_trims_whitespace() {
+        assert_eq!(normalize_name("  CommunalHelper \t"), "CommunalHelper");
+    }
+
+    #[test]
+    fn test_normalize_name_nfd_equals_nfc() {
+        let nfc = "Caf\u{e9}Helper";
+        let nfd = "Cafe\u{301}Helper";
+        assert_ne!(nfc, nfd);
+        assert_eq!(normalize_name(nfd), normalize_name(nfc));
+        assert_eq!(normalize_name(nfd), nfc);
+    }
+}
+
+#[cfg(test)]
+mod tests_version {
+    use super::*;
+
+    fn dependency(version: Option<&str>) -> Dependency {
+        Dependency {
+            name: "FrostHelper".to_string(),
+            version: version.map(String::from),
+        }
+    }
+
+    #[test]
+    fn test_compare_versions_across_boundaries() {
+        assert_eq!(compare_versions("1.10.0", "1.9.2"), Some(Ordering::Greater));
+        assert_eq!(
+            compare_versions("2.0.0", "1.99.99"),
+            Some(Ordering::Greater)
+        );
+        assert_eq!(compare_versions("1.2", "1.2.0"), Some(Ordering::Equal));
+        assert_eq!(
+            compare_versions("1.2.3-beta", "1.2.3"),
+            Some(Ordering::Equal)
+        );
+        assert_eq!(compare_versions("0.9.10", "1.0.0"), Some(Ordering::Less));
+        assert_eq!(compare_versions("latest", "1.0.0"), None);
+    }
+
+    #[test]
+    fn test_dependency_version_is_minimum() {
+        let dep = dependency(Some("1.9.0"));
+        assert!(dep.is_satisfied_by("1.9.0"));
+        assert!(dep.is_satisfied_by("1.10.0"));
+        assert!(dep.is_satisfied_by("2.0.0"));
+        assert!(!dep.is_satisfied_by("1.8.12"));
+        assert!(!dep.is_satisfied_by("0.99.0"));
+    }
+
+    #[test]
+    fn test_dependency_without_comparable_version_is_satisfied() {
+        assert!(dependency(None).is_satisfied_by("0.0.1"));
+        assert!(dependency(Some("1.0.0")).is_satisfied_by("dev"));
+    }
 }
 
 #[cfg(test)]
@@ -92,6 +338,21 @@ mod tests_manifest {
         Ok(())
     }
 
+    #[test]
+    fn test_from_slice_manifest_without_version() -> anyhow::Result<()> {
+        let yaml = r#"
+        - Name: MinimalMod
+          DLL: MinimalMod.dll
+        "#;
+
+        let manifest = ModManifest::from_slice(yaml.as_bytes())?;
+        assert_eq!(manifest.name, "MinimalMod");
+        assert_eq!(manifest.version, "");
+        assert_eq!(manifest.display_version(), "unknown");
+        assert_eq!(manifest.lint(), ["Version is empty"]);
+        Ok(())
+    }
+
     #[test]
     fn test_from_slice_invalid_manifest() {
         let yaml = r#"
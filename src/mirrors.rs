@@ -0,0 +1,76 @@
+//! Reference of the known download mirrors for the `mirrors` subcommand.
+use mirror_list::{MIRRORS, Mirror};
+
+/// Returns the known mirrors in the order given by the preferences, and the unknown keys.
+pub fn priority_order(mirror_preferences: &str) -> (Vec<Mirror>, Vec<&str>) {
+    let mut ordered = Vec::new();
+    let mut unknown = Vec::new();
+    for id in mirror_preferences
+        .split(',')
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+    {
+        match MIRRORS.iter().find(|mirror| mirror.id == id) {
+            Some(mirror) if !ordered.contains(mirror) => ordered.push(*mirror),
+            Some(_) => {}
+            None => unknown.push(id),
+        }
+    }
+    (ordered, unknown)
+}
+
+/// Renders the known mirrors as a table, followed by the configured priority order.
+pub fn render_table(mirror_preferences: &str) -> Vec<String> {
+    let (ordered, unknown) = priority_order(mirror_preferences);
+
+    let mut lines = vec![format!(
+        "{:<8} {:<28} {:>8}  {}",
+        "MIRROR", "LOCATION", "PRIORITY", "BASE URL"
+    )];
+    for mirror in MIRRORS {
+        let priority = ordered
+            .iter()
+            .position(|preferred| *preferred == mirror)
+            .map_or("-".to_string(), |index| (index + 1).to_string());
+        lines.push(format!(
+            "{:<8} {:<28} {:>8}  {}",
+            mirror.id, mirror.location, priority, mirror.base_url
+        ));
+    }
+
+    lines.push(String::new());
+    let order = ordered
+        .iter()
+        .map(|mirror| mirror.id)
+        .collect::<Vec<_>>()
+        .join(" > ");
+    lines.push(format!("Priority: {order}"));
+    for id in unknown {
+        lines.push(format!(
+            "⚠️  Unknown mirror '{id}' in --mirror-priority is ignored."
+        ));
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests_mirrors {
+    use super::*;
+
+    #[test]
+    fn test_render_table_follows_preferences() {
+        let lines = render_table("jade, gb,foo,jade");
+
+        assert_eq!(lines.len(), 1 + MIRRORS.len() + 3);
+        assert!(lines[1].starts_with("gb "));
+        assert!(lines[1].contains(" 2  https://gamebanana.com/"));
+        let wegfan = lines
+            .iter()
+            .find(|line| line.starts_with("wegfan"))
+            .unwrap();
+        assert!(wegfan.contains("China"));
+        assert!(wegfan.contains(" -  "));
+        assert_eq!(lines[MIRRORS.len() + 2], "Priority: jade > gb");
+        assert!(lines.last().unwrap().contains("'foo'"));
+    }
+}
@@ -1,13 +1,27 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::HashMap, path::Path, sync::Arc};
 
-use anyhow::Result;
+use anyhow::{Context, Result};
+use indicatif::HumanBytes;
 use reqwest::Client;
-use serde::Deserialize;
+use serde::{Deserialize, Deserializer};
 use tracing::debug;
 
-use crate::{constant::MOD_REGISTRY_URL, fetch, local_mod::LocalMod};
+use crate::{
+    cli::VALID_MOD_PAGE_URL_PREFIX,
+    constant::{GAMEBANANA_BASE_URL, GAMEBANANA_MOD_TYPE, MOD_REGISTRY_URL},
+    download::pb_style,
+    fetch, fileutil,
+    local_mod::LocalMod,
+    manifest::normalize_name,
+    pin::{self, Pins},
+    timeutil::UtcDateTime,
+    update_check::{self, LastCheck, LastChecks},
+};
 
 /// Each entry in `everest_update.yaml` containing information about a mod.
+///
+/// Only the version and the download URL are required. The other fields fall back to their
+/// defaults when missing, so that a single incomplete entry doesn't break the whole registry.
 #[derive(Debug, Deserialize, Clone, Default)]
 pub struct RemoteModInfo {
     /// Version string
@@ -17,38 +31,280 @@ pub struct RemoteModInfo {
     #[serde(rename = "URL")]
     pub download_url: String,
     /// File size
-    #[serde(rename = "Size")]
+    #[serde(rename = "Size", default)]
     pub file_size: u64,
     /// xxHash checksums for the file
-    #[serde(rename = "xxHash")]
+    #[serde(rename = "xxHash", default, deserialize_with = "string_or_list")]
     pub checksums: Vec<String>,
     /// Reference ID of the GameBanana page
-    #[serde(rename = "GameBananaId")]
+    #[serde(rename = "GameBananaId", default)]
     pub gamebanana_id: u32,
+    /// Submission type on GameBanana (e.g., "Mod")
+    #[serde(rename = "GameBananaType", default)]
+    pub gamebanana_type: Option<String>,
+    /// Time of the last update as a Unix timestamp
+    #[serde(rename = "LastUpdate", default)]
+    pub updated_at: Option<u64>,
+}
+
+/// Deserializes either a single string or a list of strings into a list.
+fn string_or_list<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StringOrList {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    Ok(match StringOrList::deserialize(deserializer)? {
+        StringOrList::One(checksum) => vec![checksum],
+        StringOrList::Many(checksums) => checksums,
+    })
 }
 
 impl RemoteModInfo {
     /// Checks if the provided hash matches any of the expected checksums.
     pub fn has_matching_hash(&self, computed_hash: &str) -> bool {
+        self.matching_hash_index(computed_hash).is_some()
+    }
+
+    /// Returns the index of the expected checksum that matches the provided hash.
+    ///
+    /// The registry may list several checksums for a single mod (one per file variant),
+    /// so matching any of them means the file is up to date.
+    pub fn matching_hash_index(&self, computed_hash: &str) -> Option<usize> {
         self.checksums
             .iter()
-            .any(|checksum| checksum.eq_ignore_ascii_case(computed_hash))
+            .position(|checksum| checksum.eq_ignore_ascii_case(computed_hash))
+    }
+
+    /// Checks if the entry is a GameBanana mod submission.
+    ///
+    /// GameBanana IDs are shared across submission types, so a mod page ID may also match e.g.
+    /// a tool. Entries without a type are assumed to be mods.
+    pub fn is_mod_submission(&self) -> bool {
+        self.gamebanana_type
+            .as_deref()
+            .is_none_or(|submission_type| submission_type == GAMEBANANA_MOD_TYPE)
+    }
+
+    /// Describes every field of the entry, one per line, as shown by `registry-show`.
+    ///
+    /// Fields missing from the registry are shown as unknown rather than as their defaults.
+    pub fn describe(&self, mod_name: &str) -> Vec<String> {
+        let unknown = || "unknown".to_string();
+        let mut lines = vec![
+            format!("- Name: {mod_name}"),
+            format!("  Version: {}", self.version),
+            format!(
+                "  Size: {}",
+                if self.file_size == 0 {
+                    unknown()
+                } else {
+                    format!("{} ({} bytes)", HumanBytes(self.file_size), self.file_size)
+                }
+            ),
+            format!(
+                "  Last Update: {}",
+                self.updated_at.map_or_else(unknown, |secs| {
+                    UtcDateTime::from_unix_secs(secs as i64).to_rfc3339()
+                })
+            ),
+            format!("  URL: {}", self.download_url),
+        ];
+        if self.checksums.is_empty() {
+            lines.push(format!("  xxHash: {}", unknown()));
+        } else {
+            lines.push("  xxHash:".to_string());
+            lines.extend(
+                self.checksums
+                    .iter()
+                    .map(|checksum| format!("    - {checksum}")),
+            );
+        }
+        lines.push(format!(
+            "  GameBanana Type: {}",
+            self.gamebanana_type.clone().unwrap_or_else(unknown)
+        ));
+        lines.push(if self.gamebanana_id == 0 {
+            format!("  GameBanana ID: {}", unknown())
+        } else {
+            format!(
+                "  GameBanana ID: {} ({}{})",
+                self.gamebanana_id, VALID_MOD_PAGE_URL_PREFIX, self.gamebanana_id
+            )
+        });
+        lines
+    }
+
+    /// Returns the names of the optional fields that are missing from the entry.
+    fn defaulted_fields(&self) -> Vec<&'static str> {
+        let mut fields = Vec::new();
+        if self.file_size == 0 {
+            fields.push("Size");
+        }
+        if self.checksums.is_empty() {
+            fields.push("xxHash");
+        }
+        if self.gamebanana_id == 0 {
+            fields.push("GameBananaId");
+        }
+        if self.gamebanana_type.is_none() {
+            fields.push("GameBananaType");
+        }
+        if self.updated_at.is_none() {
+            fields.push("LastUpdate");
+        }
+        fields
+    }
+}
+
+/// Sorts the mods by the time of their last update in the registry, most recent first.
+///
+/// Mods without a known update time come last.
+pub fn sort_by_newest(mods: &mut [(String, RemoteModInfo)]) {
+    mods.sort_by_key(|(_, info)| std::cmp::Reverse(info.updated_at));
+}
+
+/// Parses the raw entries of `everest_update.yaml` one by one.
+///
+/// Entries that can't be parsed (e.g., missing the version or the download URL) are skipped
+/// with a warning instead of failing the whole registry. The mod names used as keys are
+/// normalized with [`normalize_name`].
+pub fn parse_registry_entries(entries: HashMap<String, serde_yaml_ng::Value>) -> RemoteModRegistry {
+    entries
+        .into_iter()
+        .filter_map(|(name, value)| {
+            match serde_yaml_ng::from_value::<RemoteModInfo>(value)
+                .map_err(anyhow::Error::from)
+                .and_then(|mut info| {
+                    info.download_url = normalize_download_url(&info.download_url)?;
+                    Ok(info)
+                }) {
+                Ok(info) => {
+                    let defaulted = info.defaulted_fields();
+                    if !defaulted.is_empty() {
+                        tracing::debug!("[{}] uses defaults for {:?}", name, defaulted);
+                    }
+                    Some((normalize_name(&name).into_owned(), info))
+                }
+                Err(e) => {
+                    tracing::warn!("Skipping malformed registry entry [{}]: {}", name, e);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// Parses the raw entries, warning if the result doesn't look like the expected registry.
+fn registry_from_entries(entries: HashMap<String, serde_yaml_ng::Value>) -> RemoteModRegistry {
+    let raw_count = entries.len();
+    let registry = parse_registry_entries(entries);
+    if let Some(warning) = check_registry_shape(raw_count, &registry) {
+        tracing::warn!("{}", warning);
+        eprintln!("⚠️  {warning}");
+    }
+    registry
+}
+
+/// Reads a saved copy of `everest_update.yaml` for `update --registry-file`.
+///
+/// # Errors
+///
+/// Returns an error if the file can't be read, isn't a YAML mapping of mods, or has no valid
+/// entry.
+pub fn read_snapshot(path: &Path) -> Result<RemoteModRegistry> {
+    let display_path = fileutil::replace_home_dir_with_tilde(path);
+    let bytes = std::fs::read(path)
+        .with_context(|| format!("Failed to read the registry snapshot '{display_path}'"))?;
+    let body = bytes.strip_prefix(b"\xef\xbb\xbf").unwrap_or(&bytes);
+    let entries = serde_yaml_ng::from_slice::<HashMap<String, serde_yaml_ng::Value>>(body)
+        .with_context(|| {
+            format!("'{display_path}' is not a registry snapshot (everest_update.yaml)")
+        })?;
+    let registry = registry_from_entries(entries);
+    if registry.is_empty() {
+        anyhow::bail!("the registry snapshot '{display_path}' has no valid mod entry");
+    }
+    Ok(registry)
+}
+
+/// Makes a download URL absolute.
+///
+/// Relative (`/mmdl/1`) and protocol-relative (`//gamebanana.com/mmdl/1`) URLs are resolved
+/// against [`GAMEBANANA_BASE_URL`]. Absolute HTTP(S) URLs are returned as is.
+///
+/// # Errors
+///
+/// Returns an error if the URL can't be made into an absolute HTTP(S) URL.
+fn normalize_download_url(url: &str) -> Result<String> {
+    let is_http = |url: &reqwest::Url| matches!(url.scheme(), "http" | "https");
+    match reqwest::Url::parse(url) {
+        Ok(absolute) if is_http(&absolute) => Ok(url.to_string()),
+        Ok(absolute) => anyhow::bail!("unsupported download URL scheme '{}'", absolute.scheme()),
+        Err(_) if !url.trim().is_empty() && !url.contains("://") => {
+            let resolved = reqwest::Url::parse(GAMEBANANA_BASE_URL)?.join(url)?;
+            tracing::debug!("Resolved relative download URL '{}' to '{}'", url, resolved);
+            Ok(resolved.into())
+        }
+        Err(e) => anyhow::bail!("invalid download URL '{}': {}", url, e),
     }
 }
 
+/// Checks that the parsed registry looks like what this tool expects.
+///
+/// Returns a warning if most of the `raw_count` entries couldn't be parsed, or if most of the
+/// parsed entries lack checksums, which suggests that the format of the registry has changed.
+fn check_registry_shape(raw_count: usize, registry: &RemoteModRegistry) -> Option<String> {
+    const HINT: &str = "the registry format may have changed, and this tool may need updating";
+
+    let skipped = raw_count - registry.len();
+    if raw_count > 0 && skipped * 2 > raw_count {
+        return Some(format!(
+            "{skipped} of {raw_count} registry entries could not be parsed; {HINT}"
+        ));
+    }
+    let without_checksums = registry
+        .values()
+        .filter(|info| info.checksums.is_empty())
+        .count();
+    if without_checksums * 2 > registry.len() {
+        return Some(format!(
+            "{without_checksums} of {} registry entries have no checksum; {HINT}",
+            registry.len()
+        ));
+    }
+    None
+}
+
 /// Represents the complete `everest_update.yaml` containing all available remote mods.
 pub type RemoteModRegistry = HashMap<String, RemoteModInfo>;
 
 pub trait ModRegistryQuery {
     async fn fetch(client: &Client) -> Result<RemoteModRegistry>;
     fn get_mod_name_by_id(&self, mod_id: u32) -> Vec<&String>;
-    fn check_updates(self: Arc<Self>, local_mods: &[LocalMod]) -> Vec<(String, RemoteModInfo)>;
+    fn search(&self, term: &str) -> Vec<(&String, &RemoteModInfo)>;
+    fn check_updates(
+        self: Arc<Self>,
+        local_mods: &[LocalMod],
+        pins: &Pins,
+        last_checks: &mut LastChecks,
+    ) -> Vec<(String, RemoteModInfo)>;
 }
 
 impl ModRegistryQuery for RemoteModRegistry {
     /// Fetches the Remote Mod Registry from the maddie480's server.
     async fn fetch(client: &Client) -> Result<Self> {
-        fetch::fetch_remote_data::<Self>(MOD_REGISTRY_URL, client).await
+        let entries = fetch::fetch_remote_data::<HashMap<String, serde_yaml_ng::Value>>(
+            MOD_REGISTRY_URL,
+            client,
+        )
+        .await?;
+        Ok(registry_from_entries(entries))
     }
 
     /// Gets mod names that matches the given mod ID.
@@ -65,38 +321,152 @@ impl ModRegistryQuery for RemoteModRegistry {
             .collect()
     }
 
+    /// Finds mods whose name contains the given term, ignoring case.
+    ///
+    /// Returns the matches sorted by name.
+    fn search(&self, term: &str) -> Vec<(&String, &RemoteModInfo)> {
+        let term = term.trim().to_lowercase();
+        let mut matches: Vec<_> = self
+            .iter()
+            .filter(|(mod_name, _)| mod_name.to_lowercase().contains(&term))
+            .collect();
+        matches.sort_by_key(|(mod_name, _)| *mod_name);
+        matches
+    }
+
     /// Checks for updates of local mods.
     ///
+    /// Mods pinned to a version are only updated to exactly that version, see
+    /// [`pin::allows_update`].
+    ///
+    /// Mods unchanged since their last check in `last_checks` are not hashed again, see
+    /// [`update_check::is_unchanged`]. Mods found up to date are recorded in it.
+    ///
     /// Returns a vector of tuples containing the mod name and its updated remote information.
-    fn check_updates(self: Arc<Self>, local_mods: &[LocalMod]) -> Vec<(String, RemoteModInfo)> {
+    fn check_updates(
+        self: Arc<Self>,
+        local_mods: &[LocalMod],
+        pins: &Pins,
+        last_checks: &mut LastChecks,
+    ) -> Vec<(String, RemoteModInfo)> {
         use rayon::prelude::*;
+
+        /// Outcome of the update check of a single mod.
+        enum Outcome {
+            Update(String, RemoteModInfo),
+            UpToDate(Option<LastCheck>),
+            Unchanged,
+            Skipped,
+        }
+
         tracing::info!("Checking for updates for {} local mods", local_mods.len());
-        local_mods
-            .par_iter()
-            .filter_map(|local_mod| {
-                let name = &local_mod.manifest.name;
-                let remote_mod = self.get(name)?;
-
-                let local_hash = match local_mod.checksum() {
-                    Ok(hash) => hash,
-                    Err(e) => {
-                        tracing::warn!("Failed to compute checksum for {}: {}", name, e);
-                        return None;
-                    }
-                };
+        // NOTE: `ProgressBar` is thread-safe, so every rayon worker can advance it.
+        let pb = pb_style::create_counter(local_mods.len() as u64, "Checking for updates");
+        let previous_checks = &*last_checks;
+        let check_update = |local_mod: &LocalMod| {
+            let name = &local_mod.manifest.name;
+            let Some(remote_mod) = self.get(normalize_name(name).as_ref()) else {
+                return Outcome::Skipped;
+            };
 
-                if remote_mod.has_matching_hash(local_hash) {
-                    None
-                } else {
-                    tracing::debug!("Mods with available updates: {:?}", name);
-                    println!(
-                        "Update available for '{}': {} -> {}",
-                        name, local_mod.manifest.version, remote_mod.version
+            if update_check::is_unchanged(previous_checks, local_mod, remote_mod) {
+                tracing::debug!(
+                    "'{}' is unchanged since the last check, not hashing it",
+                    name
+                );
+                return Outcome::Unchanged;
+            }
+
+            let local_hash = match local_mod.checksum() {
+                Ok(hash) => hash,
+                Err(e) => {
+                    tracing::warn!("Failed to compute checksum for {}: {}", name, e);
+                    return Outcome::Skipped;
+                }
+            };
+
+            if remote_mod.has_matching_hash(local_hash) {
+                tracing::debug!(
+                    "'{}' is up to date: local hash '{}' matches checksum #{:?} of {:?}",
+                    name,
+                    local_hash,
+                    remote_mod.matching_hash_index(local_hash),
+                    remote_mod.checksums
+                );
+                Outcome::UpToDate(update_check::record(local_mod, remote_mod))
+            } else {
+                tracing::debug!(
+                    "'{}' has no matching checksum: local hash '{}', expected one of {:?}",
+                    name,
+                    local_hash,
+                    remote_mod.checksums
+                );
+                if let Some(pinned) = pin::pinned_version(pins, name)
+                    && !pin::allows_update(pinned, &remote_mod.version)
+                {
+                    tracing::info!(
+                        "'{}' is pinned to {}, skipping {}",
+                        name,
+                        pinned,
+                        remote_mod.version
+                    );
+                    pb.suspend(|| {
+                        println!(
+                            "📌 '{}' is pinned to {}, skipping {}",
+                            name, pinned, remote_mod.version
+                        )
+                    });
+                    return Outcome::Skipped;
+                }
+                if local_mod.manifest.version.trim().is_empty() {
+                    tracing::warn!(
+                        "'{}' has no version in its manifest, relying on the checksum only",
+                        name
                     );
-                    Some((name.clone(), remote_mod.clone()))
                 }
+                tracing::debug!("Mods with available updates: {:?}", name);
+                pb.suspend(|| {
+                    println!(
+                        "Update available for '{}': {} -> {}",
+                        name,
+                        local_mod.manifest.display_version(),
+                        remote_mod.version
+                    )
+                });
+                Outcome::Update(name.clone(), remote_mod.clone())
+            }
+        };
+        let outcomes: Vec<_> = local_mods
+            .par_iter()
+            .map(|local_mod| {
+                let outcome = check_update(local_mod);
+                pb.inc(1);
+                (local_mod, outcome)
             })
-            .collect()
+            .collect();
+        pb.finish_and_clear();
+
+        let mut updates = Vec::new();
+        let mut unchanged = 0;
+        for (local_mod, outcome) in outcomes {
+            match outcome {
+                Outcome::Update(name, remote_mod) => updates.push((name, remote_mod)),
+                Outcome::UpToDate(Some(last_check)) => {
+                    let key = normalize_name(&local_mod.manifest.name).into_owned();
+                    last_checks.insert(key, last_check);
+                }
+                Outcome::Unchanged => unchanged += 1,
+                Outcome::UpToDate(None) | Outcome::Skipped => {}
+            }
+        }
+        if unchanged > 0 {
+            tracing::info!("{} mods are unchanged since the last check", unchanged);
+            println!(
+                "{unchanged} mods unchanged since the last check were not hashed (use --full to check them all)."
+            );
+        }
+
+        updates
     }
 }
 
@@ -124,6 +494,220 @@ mod tests {
         registry
     }
 
+    #[test]
+    fn test_sort_by_newest() {
+        let updated_at = |name: &str, updated_at| {
+            (
+                name.to_string(),
+                RemoteModInfo {
+                    updated_at,
+                    ..Default::default()
+                },
+            )
+        };
+        let mut mods = vec![
+            updated_at("Old", Some(100)),
+            updated_at("Unknown", None),
+            updated_at("New", Some(300)),
+        ];
+        sort_by_newest(&mut mods);
+        let names: Vec<_> = mods.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["New", "Old", "Unknown"]);
+    }
+
+    #[test]
+    fn test_describe_shows_missing_fields_as_unknown() {
+        let remote_mod = RemoteModInfo {
+            version: "3.24.0".to_string(),
+            download_url: "https://gamebanana.com/mmdl/1".to_string(),
+            file_size: 2048,
+            checksums: vec!["abcd1234".to_string()],
+            gamebanana_id: 42,
+            updated_at: Some(0),
+            ..Default::default()
+        };
+        assert_eq!(
+            remote_mod.describe("SpeedrunTool"),
+            [
+                "- Name: SpeedrunTool",
+                "  Version: 3.24.0",
+                "  Size: 2.00 KiB (2048 bytes)",
+                "  Last Update: 1970-01-01T00:00:00Z",
+                "  URL: https://gamebanana.com/mmdl/1",
+                "  xxHash:",
+                "    - abcd1234",
+                "  GameBanana Type: unknown",
+                "  GameBanana ID: 42 (https://gamebanana.com/mods/42)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_registry_entries_tolerates_missing_optional_fields() -> anyhow::Result<()> {
+        let yaml = r#"
+        Complete:
+          Version: 1.0.0
+          URL: https://gamebanana.com/mmdl/1
+          Size: 1024
+          xxHash:
+          - 0123456789abcdef
+          GameBananaId: 42
+          GameBananaType: Mod
+          LastUpdate: 1700000000
+        Minimal:
+          Version: 2.0.0
+          URL: https://gamebanana.com/mmdl/2
+        "#;
+        let entries = serde_yaml_ng::from_str(yaml)?;
+        let registry = parse_registry_entries(entries);
+
+        let complete = &registry["Complete"];
+        assert_eq!(complete.gamebanana_type.as_deref(), Some("Mod"));
+        assert_eq!(complete.updated_at, Some(1700000000));
+        assert!(complete.defaulted_fields().is_empty());
+
+        let minimal = &registry["Minimal"];
+        assert_eq!(minimal.version, "2.0.0");
+        assert_eq!(minimal.file_size, 0);
+        assert!(minimal.checksums.is_empty());
+        assert_eq!(minimal.gamebanana_type, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_checksums_accept_scalar_and_list() -> anyhow::Result<()> {
+        let yaml = r#"
+        Scalar:
+          Version: 1.0.0
+          URL: https://gamebanana.com/mmdl/1
+          xxHash: 0123456789abcdef
+        List:
+          Version: 1.0.0
+          URL: https://gamebanana.com/mmdl/2
+          xxHash:
+          - 0123456789abcdef
+          - fedcba9876543210
+        "#;
+        let entries = serde_yaml_ng::from_str(yaml)?;
+        let registry = parse_registry_entries(entries);
+
+        assert_eq!(registry["Scalar"].checksums, vec!["0123456789abcdef"]);
+        assert_eq!(
+            registry["List"].checksums,
+            vec!["0123456789abcdef", "fedcba9876543210"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_registry_entries_resolves_relative_urls() -> anyhow::Result<()> {
+        let yaml = r#"
+        ProtocolRelative:
+          Version: 1.0.0
+          URL: //gamebanana.com/mmdl/1
+        Relative:
+          Version: 1.0.0
+          URL: /mmdl/2
+        Absolute:
+          Version: 1.0.0
+          URL: https://gamebanana.com/mmdl/3
+        Unsupported:
+          Version: 1.0.0
+          URL: ftp://gamebanana.com/mmdl/4
+        Empty:
+          Version: 1.0.0
+          URL: ""
+        "#;
+        let entries = serde_yaml_ng::from_str(yaml)?;
+        let registry = parse_registry_entries(entries);
+
+        assert_eq!(
+            registry["ProtocolRelative"].download_url,
+            "https://gamebanana.com/mmdl/1"
+        );
+        assert_eq!(
+            registry["Relative"].download_url,
+            "https://gamebanana.com/mmdl/2"
+        );
+        assert_eq!(
+            registry["Absolute"].download_url,
+            "https://gamebanana.com/mmdl/3"
+        );
+        assert!(!registry.contains_key("Unsupported"));
+        assert!(!registry.contains_key("Empty"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_registry_shape() -> anyhow::Result<()> {
+        assert_eq!(check_registry_shape(0, &HashMap::new()), None);
+        assert_eq!(check_registry_shape(2, &dummy_registry()), None);
+
+        let warning = check_registry_shape(3, &HashMap::new()).unwrap();
+        assert!(warning.starts_with("3 of 3 registry entries could not be parsed"));
+
+        let yaml = r#"
+        A:
+          Version: 1.0.0
+          URL: https://gamebanana.com/mmdl/1
+          Hash: 0123456789abcdef
+        B:
+          Version: 1.0.0
+          URL: https://gamebanana.com/mmdl/2
+          Hash: fedcba9876543210
+        "#;
+        let entries = serde_yaml_ng::from_str(yaml)?;
+        let warning = check_registry_shape(2, &parse_registry_entries(entries)).unwrap();
+        assert!(warning.starts_with("2 of 2 registry entries have no checksum"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_registry_entries_skips_malformed_entry() -> anyhow::Result<()> {
+        let yaml = r#"
+        Valid:
+          Version: 1.0.0
+          URL: https://gamebanana.com/mmdl/1
+        NoUrl:
+          Version: 1.0.0
+        "#;
+        let entries = serde_yaml_ng::from_str(yaml)?;
+        let registry = parse_registry_entries(entries);
+
+        assert_eq!(registry.len(), 1);
+        assert!(registry.contains_key("Valid"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_snapshot() -> anyhow::Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let path = temp_dir.path().join("everest_update.yaml");
+        std::fs::write(
+            &path,
+            "SpeedrunTool:\n  Version: 3.24.0\n  URL: https://gamebanana.com/mmdl/1\n  xxHash: [abcd1234]\n",
+        )?;
+        let registry = read_snapshot(&path)?;
+        assert_eq!(registry["SpeedrunTool"].version, "3.24.0");
+
+        for contents in ["just some text", "- a list\n", "NoUrl:\n  Version: 1.0.0\n"] {
+            std::fs::write(&path, contents)?;
+            assert!(read_snapshot(&path).is_err(), "{contents}");
+        }
+        assert!(read_snapshot(&temp_dir.path().join("missing.yaml")).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_registry_entries_normalizes_names() -> anyhow::Result<()> {
+        let yaml =
+            "\"Cafe\u{301}Helper \":\n  Version: 1.0.0\n  URL: https://gamebanana.com/mmdl/1\n";
+        let entries = serde_yaml_ng::from_str(yaml)?;
+        let registry = parse_registry_entries(entries);
+        assert!(registry.contains_key("Caf\u{e9}Helper"));
+        Ok(())
+    }
+
     #[test]
     fn test_has_matching_hash() {
         let mod_info = dummy_mod_info(0, vec!["abcd1234", "efgh5678"]);
@@ -132,6 +716,138 @@ mod tests {
         assert!(!mod_info.has_matching_hash("notfound"));
     }
 
+    #[test]
+    fn test_matching_hash_index() {
+        let mod_info = dummy_mod_info(0, vec!["abcd1234", "efgh5678"]);
+        assert_eq!(mod_info.matching_hash_index("abcd1234"), Some(0));
+        assert_eq!(mod_info.matching_hash_index("EFGH5678"), Some(1));
+        assert_eq!(mod_info.matching_hash_index("notfound"), None);
+    }
+
+    #[test]
+    fn test_check_updates_matches_second_checksum() -> anyhow::Result<()> {
+        let local_mod = LocalMod::from_path(std::path::Path::new("./test/test-mod.zip"))?;
+        let local_hash = local_mod.checksum()?.to_string();
+
+        let mut registry = HashMap::new();
+        registry.insert(
+            local_mod.manifest.name.clone(),
+            dummy_mod_info(1, vec!["0000000000000000", &local_hash]),
+        );
+
+        let updates =
+            Arc::new(registry).check_updates(&[local_mod], &Pins::new(), &mut LastChecks::new());
+        assert!(updates.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_updates_no_matching_checksum() -> anyhow::Result<()> {
+        let local_mod = LocalMod::from_path(std::path::Path::new("./test/test-mod.zip"))?;
+
+        let mut registry = HashMap::new();
+        registry.insert(
+            local_mod.manifest.name.clone(),
+            dummy_mod_info(1, vec!["0000000000000000", "1111111111111111"]),
+        );
+
+        let updates =
+            Arc::new(registry).check_updates(&[local_mod], &Pins::new(), &mut LastChecks::new());
+        assert_eq!(updates.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_updates_skips_mods_unchanged_since_last_check() -> anyhow::Result<()> {
+        let local_mod = LocalMod::from_path(std::path::Path::new("./test/test-mod.zip"))?;
+        let local_hash = local_mod.checksum()?.to_string();
+        let registry = |checksum: &str| {
+            let mut remote_mod = dummy_mod_info(1, vec![checksum]);
+            remote_mod.updated_at = Some(1_700_000_000);
+            Arc::new(HashMap::from([(
+                local_mod.manifest.name.clone(),
+                remote_mod,
+            )]))
+        };
+        let local_mods = std::slice::from_ref(&local_mod);
+
+        let mut last_checks = LastChecks::new();
+        assert!(
+            registry(&local_hash)
+                .check_updates(local_mods, &Pins::new(), &mut last_checks)
+                .is_empty()
+        );
+        assert_eq!(last_checks.len(), 1);
+
+        // The registry entry wasn't updated since, so the archive is not hashed again.
+        assert!(
+            registry("0000000000000000")
+                .check_updates(local_mods, &Pins::new(), &mut last_checks)
+                .is_empty()
+        );
+        assert_eq!(
+            registry("0000000000000000")
+                .check_updates(local_mods, &Pins::new(), &mut LastChecks::new())
+                .len(),
+            1
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_updates_skips_versions_past_the_pin() -> anyhow::Result<()> {
+        let local_mod = LocalMod::from_path(std::path::Path::new("./test/test-mod.zip"))?;
+        let name = local_mod.manifest.name.clone();
+        let registry = |version: &str| {
+            let mut remote_mod = dummy_mod_info(1, vec!["0000000000000000"]);
+            remote_mod.version = version.to_string();
+            Arc::new(HashMap::from([(name.clone(), remote_mod)]))
+        };
+        let pins = Pins::from([(normalize_name(&name).into_owned(), "1.0.0".to_string())]);
+
+        let local_mods = std::slice::from_ref(&local_mod);
+        assert!(
+            registry("1.1.0")
+                .check_updates(local_mods, &pins, &mut LastChecks::new())
+                .is_empty()
+        );
+        assert_eq!(
+            registry("1.0.0")
+                .check_updates(local_mods, &pins, &mut LastChecks::new())
+                .len(),
+            1
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_search_ignores_case() {
+        let mod_registry = dummy_registry();
+
+        let result = mod_registry.search("recorder");
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].0, "TASRecorder");
+
+        let names: Vec<_> = mod_registry
+            .search("R")
+            .into_iter()
+            .map(|(n, _)| n)
+            .collect();
+        assert_eq!(names, ["SpeedrunTool", "TASRecorder"]);
+    }
+
+    #[test]
+    fn test_is_mod_submission() {
+        let with_type = |gamebanana_type: Option<&str>| RemoteModInfo {
+            gamebanana_type: gamebanana_type.map(String::from),
+            ..Default::default()
+        };
+        assert!(with_type(Some("Mod")).is_mod_submission());
+        assert!(with_type(None).is_mod_submission());
+        assert!(!with_type(Some("Tool")).is_mod_submission());
+        assert!(!with_type(Some("Wip")).is_mod_submission());
+    }
+
     #[test]
     fn test_find_mod_registry_by_id() {
         let mod_registry = dummy_registry();
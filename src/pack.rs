@@ -0,0 +1,193 @@
+//! Mod packs: named YAML files listing a set of mods, installed together by `install-pack`.
+//!
+//! A pack can be shared between players to reproduce a specific setup:
+//!
+//! ```yaml
+//! Format: 1
+//! Name: Competitive ruleset
+//! Description: Mods allowed in the tournament
+//! Mods:
+//!   - Name: SpeedrunTool
+//!     Version: 3.24.0
+//!   - Url: https://gamebanana.com/mods/150813
+//! ```
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::manifest::normalize_name;
+
+/// The version of the pack format this application understands.
+pub const PACK_FORMAT_VERSION: u32 = 1;
+
+/// Errors that can occur while parsing a pack file.
+#[derive(Debug, Error)]
+pub enum PackError {
+    /// Failed to parse the pack file. Invalid YAML syntax or missing fields.
+    #[error(transparent)]
+    Parse(#[from] serde_yaml_ng::Error),
+    /// The pack was written for another version of the format.
+    #[error("unsupported pack format {0}, only format {PACK_FORMAT_VERSION} is supported")]
+    UnsupportedFormat(u32),
+    /// The pack does not list any mods.
+    #[error("the pack does not list any mods")]
+    Empty,
+    /// An entry has neither or both of `Name` and `Url`.
+    #[error("entry #{0} must have exactly one of 'Name' or 'Url'")]
+    InvalidEntry(usize),
+}
+
+/// A pack file.
+#[derive(Debug, Deserialize)]
+pub struct Pack {
+    /// The version of the pack format.
+    #[serde(rename = "Format")]
+    pub format: u32,
+    /// A display name of the pack.
+    #[serde(rename = "Name", default)]
+    pub name: Option<String>,
+    /// A short description of the pack.
+    #[serde(rename = "Description", default)]
+    pub description: Option<String>,
+    /// The mods of the pack.
+    #[serde(rename = "Mods", default)]
+    mods: Vec<PackEntry>,
+}
+
+/// A mod listed in a pack, as written in the file.
+#[derive(Debug, Deserialize)]
+struct PackEntry {
+    #[serde(rename = "Name")]
+    name: Option<String>,
+    #[serde(rename = "Url")]
+    url: Option<String>,
+    #[serde(rename = "Version")]
+    version: Option<String>,
+}
+
+/// How a mod of a pack is identified.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PackSource {
+    /// The name of the mod in the registry.
+    Name(String),
+    /// The URL of the page of the mod on GameBanana.
+    Url(String),
+}
+
+/// A validated mod of a pack.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PackMod {
+    pub source: PackSource,
+    /// The version the pack was made with, if given.
+    pub version: Option<String>,
+}
+
+impl PackMod {
+    /// Returns the name or the URL of the mod, for display.
+    pub fn label(&self) -> &str {
+        match &self.source {
+            PackSource::Name(name) | PackSource::Url(name) => name,
+        }
+    }
+}
+
+impl Pack {
+    /// Parses and validates a pack file.
+    ///
+    /// # Errors
+    ///
+    /// - `PackError::Parse`: Failed to parse YAML format.
+    /// - `PackError::UnsupportedFormat`: The pack was written for another format version.
+    /// - `PackError::Empty`: The pack does not list any mods.
+    /// - `PackError::InvalidEntry`: An entry has neither or both of `Name` and `Url`.
+    pub fn from_slice(bytes: &[u8]) -> Result<Self, PackError> {
+        let pack: Pack = serde_yaml_ng::from_slice(bytes)?;
+        if pack.format != PACK_FORMAT_VERSION {
+            return Err(PackError::UnsupportedFormat(pack.format));
+        }
+        if pack.mods.is_empty() {
+            return Err(PackError::Empty);
+        }
+        for (i, entry) in pack.mods.iter().enumerate() {
+            if entry.name.is_some() == entry.url.is_some() {
+                return Err(PackError::InvalidEntry(i + 1));
+            }
+        }
+        Ok(pack)
+    }
+
+    /// Returns the mods of the pack, skipping entries listed more than once.
+    pub fn mods(&self) -> Vec<PackMod> {
+        let mut seen = HashSet::new();
+        self.mods
+            .iter()
+            .filter_map(|entry| {
+                let source = match (&entry.name, &entry.url) {
+                    (Some(name), _) => PackSource::Name(name.trim().to_string()),
+                    (None, Some(url)) => PackSource::Url(url.trim().to_string()),
+                    (None, None) => return None,
+                };
+                let key = match &source {
+                    PackSource::Name(name) => normalize_name(name).into_owned(),
+                    PackSource::Url(url) => url.trim_end_matches('/').to_string(),
+                };
+                if !seen.insert(key) {
+                    tracing::warn!("Skipping duplicate pack entry: {:?}", source);
+                    return None;
+                }
+                Some(PackMod {
+                    source,
+                    version: entry.version.clone(),
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests_pack {
+    use super::*;
+
+    #[test]
+    fn test_from_slice_valid_pack() -> anyhow::Result<()> {
+        let pack = Pack::from_slice(
+            b"Format: 1\nName: Ruleset\nMods:\n  - Name: SpeedrunTool\n    Version: 3.24.0\n  - Url: https://gamebanana.com/mods/150813\n  - Name: 'SpeedrunTool '\n",
+        )?;
+        assert_eq!(pack.name.as_deref(), Some("Ruleset"));
+        assert_eq!(
+            pack.mods(),
+            [
+                PackMod {
+                    source: PackSource::Name("SpeedrunTool".to_string()),
+                    version: Some("3.24.0".to_string()),
+                },
+                PackMod {
+                    source: PackSource::Url("https://gamebanana.com/mods/150813".to_string()),
+                    version: None,
+                },
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_slice_rejects_invalid_packs() {
+        assert!(matches!(
+            Pack::from_slice(b"Format: 2\nMods:\n  - Name: A\n"),
+            Err(PackError::UnsupportedFormat(2))
+        ));
+        assert!(matches!(
+            Pack::from_slice(b"Format: 1\nMods: []\n"),
+            Err(PackError::Empty)
+        ));
+        assert!(matches!(
+            Pack::from_slice(b"Format: 1\nMods:\n  - Name: A\n  - Version: 1.0.0\n"),
+            Err(PackError::InvalidEntry(2))
+        ));
+        assert!(matches!(
+            Pack::from_slice(b"Mods:\n  - Name: A\n"),
+            Err(PackError::Parse(_))
+        ));
+    }
+}
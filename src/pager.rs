@@ -0,0 +1,93 @@
+//! Paging of long outputs such as the list of installed mods.
+use std::{
+    env,
+    io::{self, BufRead, IsTerminal, Write},
+    process::{Command, Stdio},
+};
+
+/// Prints the lines, paging them when stdout is a terminal and they don't fit on the screen.
+///
+/// The pager from the `PAGER` environment variable is used if set, otherwise the lines are
+/// printed one screen at a time with a prompt.
+pub fn print_paged(lines: &[String], enabled: bool) {
+    let height = console::Term::stdout()
+        .size_checked()
+        .map(|(rows, _)| rows as usize);
+    if !enabled || !io::stdout().is_terminal() || !exceeds_height(lines.len(), height) {
+        lines.iter().for_each(|line| println!("{line}"));
+        return;
+    }
+
+    if let Ok(pager) = env::var("PAGER")
+        && !pager.trim().is_empty()
+    {
+        match run_external_pager(&pager, lines) {
+            Ok(()) => return,
+            Err(e) => tracing::warn!("Failed to run the pager '{}': {}", pager, e),
+        }
+    }
+    print_in_pages(lines, height.unwrap_or_default());
+}
+
+/// Returns `true` if the lines don't fit on a terminal of the given height.
+fn exceeds_height(line_count: usize, height: Option<usize>) -> bool {
+    height.is_some_and(|height| height > 1 && line_count >= height)
+}
+
+/// Pipes the lines to the given pager command.
+fn run_external_pager(pager: &str, lines: &[String]) -> io::Result<()> {
+    let mut args = pager.split_whitespace();
+    let program = args.next().unwrap_or_default();
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .spawn()?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        for line in lines {
+            // NOTE: The pager may be closed before everything was written, which is fine.
+            if writeln!(stdin, "{line}").is_err() {
+                break;
+            }
+        }
+    }
+    child.wait()?;
+    Ok(())
+}
+
+/// Prints the lines one screen at a time, waiting for Enter between screens.
+fn print_in_pages(lines: &[String], height: usize) {
+    let page_size = height.saturating_sub(1).max(1);
+    let mut stdin = io::stdin().lock();
+    let mut pages = lines.chunks(page_size).peekable();
+
+    while let Some(page) = pages.next() {
+        page.iter().for_each(|line| println!("{line}"));
+        if pages.peek().is_none() {
+            break;
+        }
+
+        print!("-- More -- (Enter: next page, q: quit) ");
+        let _ = io::stdout().flush();
+        let mut answer = String::new();
+        match stdin.read_line(&mut answer) {
+            Ok(0) | Err(_) => break,
+            Ok(_) if answer.trim().eq_ignore_ascii_case("q") => break,
+            Ok(_) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_pager {
+    use super::*;
+
+    #[test]
+    fn test_exceeds_height() {
+        assert!(exceeds_height(100, Some(40)));
+        assert!(exceeds_height(40, Some(40)));
+        assert!(!exceeds_height(39, Some(40)));
+        // Not a terminal
+        assert!(!exceeds_height(100, None));
+    }
+}
@@ -0,0 +1,109 @@
+//! Versions pinned by the `pin` subcommand, which `update` keeps the mods at.
+use std::{
+    cmp::Ordering,
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+
+use crate::{
+    config, fileutil,
+    manifest::{compare_versions, normalize_name},
+};
+
+/// The name of the pin file in the state directory.
+const PINS_FILE: &str = "pins.json";
+
+/// Pinned versions by mod name.
+///
+/// The names are normalized with [`normalize_name`].
+pub type Pins = BTreeMap<String, String>;
+
+/// Returns the path of the pin file in the state directory.
+pub fn pins_path() -> Option<PathBuf> {
+    config::get_state_directory().map(|state_dir| state_dir.join(PINS_FILE))
+}
+
+/// Reads the pinned versions. Returns no pins if the file does not exist.
+///
+/// # Errors
+///
+/// Returns an error if the file exists but cannot be read or parsed.
+pub fn read(path: &Path) -> Result<Pins> {
+    if !path.exists() {
+        return Ok(Pins::new());
+    }
+    let contents = fs::read_to_string(path)?;
+    serde_json::from_str(&contents).with_context(|| {
+        format!(
+            "Failed to parse the pin file '{}'",
+            fileutil::replace_home_dir_with_tilde(path)
+        )
+    })
+}
+
+/// Writes the pinned versions atomically, creating the state directory if needed.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be written.
+pub fn write(path: &Path, pins: &Pins) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fileutil::write_atomic(path, serde_json::to_string_pretty(pins)?)?;
+    Ok(())
+}
+
+/// Returns the version the mod is pinned to, if any.
+pub fn pinned_version<'a>(pins: &'a Pins, mod_name: &str) -> Option<&'a str> {
+    pins.get(normalize_name(mod_name).as_ref())
+        .map(String::as_str)
+}
+
+/// Checks if a pinned mod may be updated to the version in the registry.
+///
+/// Only the pinned version itself is installed, so that a mod which is not at the pinned
+/// version yet can be brought to it, while newer versions are skipped.
+pub fn allows_update(pinned: &str, remote_version: &str) -> bool {
+    match compare_versions(remote_version, pinned) {
+        Some(ordering) => ordering == Ordering::Equal,
+        None => remote_version == pinned,
+    }
+}
+
+#[cfg(test)]
+mod tests_pin {
+    use super::*;
+
+    #[test]
+    fn test_write_and_read() -> Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let path = temp_dir.path().join("state").join(PINS_FILE);
+        assert!(read(&path)?.is_empty());
+
+        let mut pins = Pins::new();
+        pins.insert(normalize_name("SpeedrunTool").into_owned(), "3.24.0".into());
+        write(&path, &pins)?;
+
+        let pins = read(&path)?;
+        assert_eq!(pinned_version(&pins, "SpeedrunTool"), Some("3.24.0"));
+        assert_eq!(pinned_version(&pins, "FrostHelper"), None);
+
+        fs::write(&path, "not json")?;
+        assert!(read(&path).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_allows_update() {
+        assert!(allows_update("3.24.0", "3.24.0"));
+        assert!(allows_update("3.24", "v3.24.0"));
+        assert!(!allows_update("3.24.0", "3.25.0"));
+        assert!(!allows_update("3.24.0", "3.23.9"));
+        assert!(allows_update("beta", "beta"));
+        assert!(!allows_update("beta", "gamma"));
+    }
+}
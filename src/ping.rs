@@ -0,0 +1,141 @@
+//! Reachability check of the download mirrors for the `ping` subcommand.
+use std::time::{Duration, Instant};
+
+use mirror_list::{MIRRORS, Mirror};
+use reqwest::Client;
+
+use crate::constant::PING_TIMEOUT_SECS;
+
+/// Result of pinging a single mirror.
+#[derive(Debug)]
+pub struct PingResult {
+    pub mirror: Mirror,
+    /// Round-trip time, or the reason the mirror could not be reached
+    pub latency: Result<Duration, String>,
+}
+
+/// Sends a HEAD request to the base URL of every known mirror concurrently.
+///
+/// Any HTTP response counts as reachable, since only the round-trip time matters here.
+pub async fn ping_mirrors(client: &Client) -> Vec<PingResult> {
+    let pings = MIRRORS.into_iter().map(|mirror| async move {
+        let started = Instant::now();
+        let latency = client
+            .head(mirror.base_url)
+            .timeout(Duration::from_secs(PING_TIMEOUT_SECS))
+            .send()
+            .await
+            .map(|response| {
+                tracing::debug!("'{}' -> Status: {}", mirror.base_url, response.status());
+                started.elapsed()
+            })
+            .map_err(|e| {
+                tracing::warn!("Failed to reach '{}': {}", mirror.base_url, e);
+                if e.is_timeout() {
+                    "timed out".to_string()
+                } else if e.is_connect() {
+                    "connection failed".to_string()
+                } else {
+                    "request failed".to_string()
+                }
+            });
+        PingResult { mirror, latency }
+    });
+    let mut results = futures_util::future::join_all(pings).await;
+    sort_fastest_first(&mut results);
+    results
+}
+
+/// Sorts the results by latency, with unreachable mirrors last.
+fn sort_fastest_first(results: &mut [PingResult]) {
+    results.sort_by_key(|result| result.latency.as_ref().map_or(Duration::MAX, |d| *d));
+}
+
+/// Orders the mirror preferences by the latency of the results, fastest first.
+///
+/// Only the mirrors in `mirror_preferences` are kept. The ones that could not be reached come
+/// last, in their original order.
+pub fn order_by_latency(results: &[PingResult], mirror_preferences: &str) -> String {
+    let preferred: Vec<&str> = mirror_preferences
+        .split(',')
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .collect();
+    let mut ordered: Vec<&str> = results
+        .iter()
+        .filter(|result| result.latency.is_ok() && preferred.contains(&result.mirror.id))
+        .map(|result| result.mirror.id)
+        .collect();
+    for id in preferred {
+        if !ordered.contains(&id) {
+            ordered.push(id);
+        }
+    }
+    ordered.join(",")
+}
+
+/// Prints the results as a table.
+pub fn print_table(results: &[PingResult]) {
+    println!("{:<8} {:<40} {:>10}", "MIRROR", "LOCATION", "LATENCY");
+    for result in results {
+        let latency = match &result.latency {
+            Ok(duration) => format!("{} ms", duration.as_millis()),
+            Err(reason) => reason.clone(),
+        };
+        println!(
+            "{:<8} {:<40} {:>10}",
+            result.mirror.id, result.mirror.location, latency
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests_ping {
+    use super::*;
+
+    #[test]
+    fn test_sort_fastest_first() {
+        let mut results = vec![
+            PingResult {
+                mirror: MIRRORS[0],
+                latency: Err("timed out".to_string()),
+            },
+            PingResult {
+                mirror: MIRRORS[1],
+                latency: Ok(Duration::from_millis(300)),
+            },
+            PingResult {
+                mirror: MIRRORS[2],
+                latency: Ok(Duration::from_millis(50)),
+            },
+        ];
+        sort_fastest_first(&mut results);
+
+        let ids: Vec<_> = results.iter().map(|result| result.mirror.id).collect();
+        assert_eq!(ids, vec!["wegfan", "jade", "gb"]);
+    }
+
+    #[test]
+    fn test_order_by_latency() {
+        let results = vec![
+            PingResult {
+                mirror: MIRRORS[2],
+                latency: Ok(Duration::from_millis(50)),
+            },
+            PingResult {
+                mirror: MIRRORS[1],
+                latency: Ok(Duration::from_millis(300)),
+            },
+            PingResult {
+                mirror: MIRRORS[0],
+                latency: Err("timed out".to_string()),
+            },
+        ];
+        assert_eq!(
+            order_by_latency(&results, "gb,jade,wegfan"),
+            "wegfan,jade,gb"
+        );
+        // Mirrors missing from the preferences are not added.
+        assert_eq!(order_by_latency(&results, "gb, jade"), "jade,gb");
+    }
+}
@@ -0,0 +1,143 @@
+//! Best-effort detection of a running Celeste process, checked before modifying the mods.
+//!
+//! Detection works on Linux (including Proton and Wine) by reading `/proc`, on macOS with `ps`
+//! and on Windows with `tasklist`. On other platforms nothing is ever detected.
+use std::io;
+
+/// A running process which looks like the game.
+#[derive(Debug, PartialEq, Eq)]
+pub struct GameProcess {
+    pub pid: u32,
+    pub name: String,
+}
+
+/// Checks if a process name is the one of the game, with or without Everest.
+///
+/// Linux truncates process names to 15 characters, so `Celeste.bin.x86_64` shows up as
+/// `Celeste.bin.x86`.
+fn is_game_process(name: &str) -> bool {
+    let name = name.trim().to_ascii_lowercase();
+    let name = name.strip_suffix(".exe").unwrap_or(&name);
+    name == "celeste" || name.starts_with("celeste.bin.")
+}
+
+/// Returns the first running process that looks like the game.
+///
+/// # Errors
+/// Returns an error if the process list can't be read.
+pub fn find_running_game() -> io::Result<Option<GameProcess>> {
+    Ok(list_processes()?
+        .into_iter()
+        .find(|process| is_game_process(&process.name)))
+}
+
+#[cfg(target_os = "linux")]
+fn list_processes() -> io::Result<Vec<GameProcess>> {
+    let mut processes = Vec::new();
+    for entry in std::fs::read_dir("/proc")?.filter_map(Result::ok) {
+        let Some(pid) = entry.file_name().to_str().and_then(|s| s.parse().ok()) else {
+            continue;
+        };
+        // The process may have exited since the directory was listed.
+        if let Ok(name) = std::fs::read_to_string(entry.path().join("comm")) {
+            processes.push(GameProcess {
+                pid,
+                name: name.trim_end().to_string(),
+            });
+        }
+    }
+    Ok(processes)
+}
+
+#[cfg(target_os = "macos")]
+fn list_processes() -> io::Result<Vec<GameProcess>> {
+    let output = std::process::Command::new("ps")
+        .args(["-axo", "pid=,comm="])
+        .output()?;
+    Ok(parse_ps(&String::from_utf8_lossy(&output.stdout)))
+}
+
+#[cfg(target_os = "windows")]
+fn list_processes() -> io::Result<Vec<GameProcess>> {
+    let output = std::process::Command::new("tasklist")
+        .args(["/FO", "CSV", "/NH"])
+        .output()?;
+    Ok(parse_tasklist(&String::from_utf8_lossy(&output.stdout)))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn list_processes() -> io::Result<Vec<GameProcess>> {
+    Ok(Vec::new())
+}
+
+/// Parses the output of `ps -axo pid=,comm=`, where the command is the full path.
+#[cfg(any(target_os = "macos", test))]
+fn parse_ps(output: &str) -> Vec<GameProcess> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (pid, command) = line.trim().split_once(char::is_whitespace)?;
+            Some(GameProcess {
+                pid: pid.parse().ok()?,
+                name: command.trim().rsplit('/').next()?.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Parses the CSV output of `tasklist /FO CSV /NH`, e.g. `"Celeste.exe","1234",...`.
+#[cfg(any(target_os = "windows", test))]
+fn parse_tasklist(output: &str) -> Vec<GameProcess> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split(',').map(|field| field.trim().trim_matches('"'));
+            let name = fields.next()?.to_string();
+            let pid = fields.next()?.parse().ok()?;
+            Some(GameProcess { pid, name })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests_process {
+    use super::*;
+
+    #[test]
+    fn test_is_game_process() {
+        for name in [
+            "Celeste",
+            "Celeste.exe",
+            "Celeste.bin.x86",
+            "celeste.bin.osx",
+        ] {
+            assert!(is_game_process(name), "{name}");
+        }
+        for name in ["CelesteTAS", "Olympus", "steam", "celeste-mod-cli"] {
+            assert!(!is_game_process(name), "{name}");
+        }
+    }
+
+    #[test]
+    fn test_parse_process_lists() {
+        let ps = "  1 /sbin/launchd\n 4242 /Applications/Celeste.app/Contents/MacOS/Celeste\n";
+        assert_eq!(
+            parse_ps(ps).last(),
+            Some(&GameProcess {
+                pid: 4242,
+                name: "Celeste".to_string()
+            })
+        );
+
+        let tasklist = "\"System\",\"4\",\"Services\",\"0\",\"144 K\"\r\n\"Celeste.exe\",\"1234\",\"Console\",\"1\",\"512,000 K\"\r\n";
+        let processes = parse_tasklist(tasklist);
+        assert_eq!(processes.len(), 2);
+        assert_eq!(
+            processes[1],
+            GameProcess {
+                pid: 1234,
+                name: "Celeste.exe".to_string()
+            }
+        );
+    }
+}
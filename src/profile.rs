@@ -0,0 +1,225 @@
+//! Named profiles of the config file, selected with `--profile`.
+//!
+//! The config file is read only when a profile is selected. Each profile is a
+//! `[profile.<name>]` table:
+//!
+//! ```toml
+//! [profile.deck]
+//! mods_dir = "~/.local/share/Steam/steamapps/common/Celeste/Mods"
+//! mirror_priority = "jade,gb"
+//! jobs = 2
+//! proxy = "http://192.168.1.2:3128"
+//! ```
+use std::{
+    collections::BTreeMap,
+    env,
+    ffi::OsString,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use crate::{
+    constant::{CONFIG_DIRECTORY_NAME, CONFIG_DIRECTORY_PATH, CONFIG_FILE_NAME},
+    fileutil,
+};
+
+/// Settings of a profile. Every one of them can be overridden by its command-line option.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    /// Mods directory, overridden by `--mods-dir`
+    pub mods_dir: Option<PathBuf>,
+    /// Mirror priority, overridden by `--mirror-priority`
+    pub mirror_priority: Option<String>,
+    /// Number of mods downloaded at once, overridden by `--jobs`
+    pub jobs: Option<usize>,
+    /// Proxy every request is sent through
+    pub proxy: Option<String>,
+}
+
+/// Profiles by name.
+pub type Profiles = BTreeMap<String, Profile>;
+
+/// Returns the path of the config file.
+///
+/// `XDG_CONFIG_HOME` (on Linux) takes precedence over `~/.config/everest-mod-cli`.
+pub fn config_path() -> Option<PathBuf> {
+    let xdg_config_home = if cfg!(target_os = "linux") {
+        env::var_os("XDG_CONFIG_HOME")
+    } else {
+        None
+    };
+    resolve_config_path(xdg_config_home, env::home_dir())
+}
+
+fn resolve_config_path(
+    xdg_config_home: Option<OsString>,
+    home_dir: Option<PathBuf>,
+) -> Option<PathBuf> {
+    // The XDG spec says that relative paths are invalid and must be ignored.
+    let directory = match xdg_config_home.map(PathBuf::from) {
+        Some(xdg_config_home) if xdg_config_home.is_absolute() => {
+            xdg_config_home.join(CONFIG_DIRECTORY_NAME)
+        }
+        _ => home_dir?.join(CONFIG_DIRECTORY_PATH),
+    };
+    Some(directory.join(CONFIG_FILE_NAME))
+}
+
+/// Reads the profile with the given name from the config file.
+///
+/// # Errors
+///
+/// Returns an error if the config file can't be read or parsed, or if it has no such profile,
+/// in which case the available profiles are listed.
+pub fn load(path: &Path, name: &str) -> Result<Profile> {
+    let display_path = fileutil::replace_home_dir_with_tilde(path);
+    let contents = fs::read_to_string(path).with_context(|| {
+        format!("Failed to read the config file '{display_path}' for --profile {name}")
+    })?;
+    let mut profiles = parse(&contents)
+        .with_context(|| format!("Failed to parse the config file '{display_path}'"))?;
+
+    match profiles.remove(name) {
+        Some(profile) => Ok(profile),
+        None if profiles.is_empty() => {
+            anyhow::bail!("profile '{name}' is not found: '{display_path}' defines no profiles")
+        }
+        None => anyhow::bail!(
+            "profile '{name}' is not found in '{display_path}', available profiles: {}",
+            profiles.keys().cloned().collect::<Vec<_>>().join(", ")
+        ),
+    }
+}
+
+/// Layout of the config file, where every profile is a `[profile.<name>]` table.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    #[serde(default)]
+    profile: Profiles,
+}
+
+/// Parses the profiles of the config file.
+///
+/// # Errors
+///
+/// Returns an error if the file is not valid TOML, has a key outside a profile or an unknown
+/// key, or if `jobs` is not a positive integer.
+pub fn parse(contents: &str) -> Result<Profiles> {
+    let ConfigFile { mut profile } = toml::from_str(contents)?;
+    for (name, profile) in &mut profile {
+        if profile.jobs == Some(0) {
+            anyhow::bail!("profile '{name}': `jobs` must be a positive integer");
+        }
+        // Config files are not expanded by a shell.
+        profile.mods_dir = profile.mods_dir.take().map(|path| expand_tilde(&path));
+    }
+    Ok(profile)
+}
+
+/// Expands a leading `~` to the home directory.
+fn expand_tilde(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), env::home_dir()) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+#[cfg(test)]
+mod tests_profile {
+    use super::*;
+
+    const CONFIG: &str = r#"
+# Profiles of everest-mod-cli
+[profile.deck]
+mods_dir = "/run/media/deck/Celeste/Mods"
+mirror_priority = "jade,gb" # closest first
+jobs = 2
+proxy = "http://192.168.1.2:3128"
+
+[profile."pc"]
+jobs = 8
+"#;
+
+    #[test]
+    fn test_parse() -> Result<()> {
+        let profiles = parse(CONFIG)?;
+        assert_eq!(profiles.keys().collect::<Vec<_>>(), ["deck", "pc"]);
+        assert_eq!(
+            profiles["deck"],
+            Profile {
+                mods_dir: Some(PathBuf::from("/run/media/deck/Celeste/Mods")),
+                mirror_priority: Some("jade,gb".to_string()),
+                jobs: Some(2),
+                proxy: Some("http://192.168.1.2:3128".to_string()),
+            }
+        );
+        assert_eq!(
+            profiles["pc"],
+            Profile {
+                jobs: Some(8),
+                ..Default::default()
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for (contents, message) in [
+            ("jobs = 2", "unknown field `jobs`"),
+            (
+                "[profile.deck]\njobs = 0",
+                "profile 'deck': `jobs` must be a positive integer",
+            ),
+            ("[profile.deck]\nthreads = 2", "unknown field `threads`"),
+            ("[mirrors]", "unknown field `mirrors`"),
+        ] {
+            let err = parse(contents).unwrap_err().to_string();
+            assert!(err.contains(message), "{err}");
+        }
+        assert!(parse("[profile.deck]\nproxy = http://proxy").is_err());
+    }
+
+    #[test]
+    fn test_parse_expands_tilde() -> Result<()> {
+        let Some(home) = env::home_dir() else {
+            return Ok(());
+        };
+        let profiles = parse("[profile.deck]\nmods_dir = \"~/Celeste/Mods\"")?;
+        assert_eq!(profiles["deck"].mods_dir, Some(home.join("Celeste/Mods")));
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_lists_available_profiles() -> Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let path = temp_dir.path().join(CONFIG_FILE_NAME);
+        fs::write(&path, CONFIG)?;
+        assert_eq!(load(&path, "pc")?.jobs, Some(8));
+
+        let err = load(&path, "laptop").unwrap_err().to_string();
+        assert!(err.ends_with("available profiles: deck, pc"), "{err}");
+        Ok(())
+    }
+
+    #[test]
+    fn test_resolve_config_path() {
+        let home = Some(PathBuf::from("/home/madeline"));
+        assert_eq!(
+            resolve_config_path(None, home.clone()),
+            Some(PathBuf::from(
+                "/home/madeline/.config/everest-mod-cli/config.toml"
+            ))
+        );
+        assert_eq!(
+            resolve_config_path(Some("/xdg".into()), home.clone()),
+            Some(PathBuf::from("/xdg/everest-mod-cli/config.toml"))
+        );
+        assert_eq!(resolve_config_path(Some("relative".into()), None), None);
+    }
+}
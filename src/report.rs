@@ -0,0 +1,400 @@
+//! Post-run report written by the `--report` option.
+//!
+//! The report is a single JSON document summarizing what a command did, meant to be consumed by
+//! scripts and dashboards.
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::{
+    config,
+    download::{DownloadError, DownloadSummary},
+    fileutil,
+    timeutil::UtcDateTime,
+};
+
+/// A mod entry in the report.
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct ModRecord {
+    /// Name of the mod
+    pub name: String,
+    /// Version of the mod that was downloaded
+    pub version: String,
+    /// Version of the mod that was installed before the update
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_version: Option<String>,
+    /// URL the mod was downloaded from
+    pub url: String,
+}
+
+/// Summary of a single run of the application.
+#[derive(Debug, Serialize)]
+pub struct Report {
+    /// Name of the subcommand
+    command: String,
+    /// Time the command started, in RFC 3339 format
+    timestamp: String,
+    /// Wall-clock duration of the command in seconds
+    elapsed_secs: f64,
+    /// Whether the command completed without errors
+    success: bool,
+    /// The error that stopped the command, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    /// Mods newly installed
+    installed: Vec<ModRecord>,
+    /// Mods updated to a newer version
+    updated: Vec<ModRecord>,
+    /// Mods that could not be downloaded
+    failed: Vec<ModRecord>,
+    /// Total size of the downloaded files in bytes
+    total_bytes: u64,
+    #[serde(skip)]
+    started: Instant,
+}
+
+impl Report {
+    /// Starts a new report for the given command.
+    pub fn new(command: &str) -> Self {
+        Self {
+            command: command.to_string(),
+            timestamp: UtcDateTime::now().to_rfc3339(),
+            elapsed_secs: 0.0,
+            success: false,
+            error: None,
+            installed: Vec::new(),
+            updated: Vec::new(),
+            failed: Vec::new(),
+            total_bytes: 0,
+            started: Instant::now(),
+        }
+    }
+
+    /// Records the result of installing new mods.
+    pub fn record_installs(&mut self, summary: &DownloadSummary) {
+        self.record(summary, |_| None, false);
+    }
+
+    /// Records the result of updating mods, looking up the previously installed version by name.
+    pub fn record_updates<F>(&mut self, summary: &DownloadSummary, previous_version: F)
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        self.record(summary, previous_version, true);
+    }
+
+    fn record<F>(&mut self, summary: &DownloadSummary, previous_version: F, is_update: bool)
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        for (name, info) in &summary.succeeded {
+            let record = ModRecord {
+                name: name.clone(),
+                version: info.version.clone(),
+                previous_version: previous_version(name),
+                url: info.download_url.clone(),
+            };
+            if is_update {
+                self.updated.push(record);
+            } else {
+                self.installed.push(record);
+            }
+        }
+        for (name, info, _) in &summary.failed {
+            self.failed.push(ModRecord {
+                name: name.clone(),
+                version: info.version.clone(),
+                previous_version: previous_version(name),
+                url: info.download_url.clone(),
+            });
+        }
+        self.total_bytes += summary.total_bytes();
+    }
+
+    /// Time the command started, in RFC 3339 format.
+    pub fn timestamp(&self) -> &str {
+        &self.timestamp
+    }
+
+    /// Mods successfully installed so far.
+    pub fn installed(&self) -> &[ModRecord] {
+        &self.installed
+    }
+
+    /// Mods successfully updated so far.
+    pub fn updated(&self) -> &[ModRecord] {
+        &self.updated
+    }
+
+    /// Completes the report with the outcome of the command.
+    pub fn finish(&mut self, result: &Result<()>) {
+        self.elapsed_secs = self.started.elapsed().as_secs_f64();
+        self.success = result.is_ok();
+        self.error = result.as_ref().err().map(|err| format!("{err:#}"));
+    }
+
+    /// Writes the report to the given path as pretty-printed JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be created or written.
+    pub fn write_to(&self, path: &Path) -> Result<()> {
+        let file = File::create(path).with_context(|| {
+            format!(
+                "Failed to create report file '{}'",
+                fileutil::replace_home_dir_with_tilde(path)
+            )
+        })?;
+        serde_json::to_writer_pretty(BufWriter::new(file), self)
+            .context("Failed to write the report")?;
+        tracing::info!(
+            "Report written to '{}'",
+            fileutil::replace_home_dir_with_tilde(path)
+        );
+        Ok(())
+    }
+}
+
+/// Returns the default path of the update log, `updates-<date>.txt` in the state directory.
+///
+/// # Errors
+///
+/// Returns an error if the home directory could not be determined.
+pub fn default_update_log_path() -> Result<PathBuf> {
+    let state_dir = config::get_state_directory().context("Could not determine home directory")?;
+    Ok(state_dir.join(format!("updates-{}.txt", UtcDateTime::now().to_date())))
+}
+
+/// Appends the updated mods to a changelog-style text file, one `name: old -> new` per line.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be opened or written.
+pub fn append_update_log(path: &Path, updates: &[ModRecord]) -> Result<()> {
+    if updates.is_empty() {
+        tracing::info!("No updates to write to the update log.");
+        return Ok(());
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let debug_filename = fileutil::replace_home_dir_with_tilde(path);
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open update log '{debug_filename}'"))?;
+    let mut writer = BufWriter::new(file);
+
+    writeln!(writer, "# {}", UtcDateTime::now().to_rfc3339())?;
+    for record in updates {
+        writeln!(
+            writer,
+            "{}: {} -> {}",
+            record.name,
+            record.previous_version.as_deref().unwrap_or("?"),
+            record.version
+        )?;
+    }
+    writeln!(writer)?;
+    writer.flush()?;
+
+    tracing::info!("Update log written to '{}'", debug_filename);
+    Ok(())
+}
+
+/// Appends the details of every download of the summary that failed checksum verification to
+/// a text file, to be attached to a bug report.
+///
+/// Returns the number of mods written to the file.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be opened or written.
+pub fn append_checksum_mismatches(path: &Path, summary: &DownloadSummary) -> Result<usize> {
+    let mut contents = String::new();
+    let mut count = 0;
+    for (name, remote_mod, err) in &summary.failed {
+        let Some(DownloadError::InvalidChecksum {
+            mismatches,
+            expected,
+            ..
+        }) = err.downcast_ref::<DownloadError>()
+        else {
+            continue;
+        };
+        count += 1;
+        contents.push_str(&format!(
+            "Mod: {name}\nVersion: {}\nURL: {}\nExpected size: {} bytes\nExpected checksums: {}\n",
+            remote_mod.version,
+            remote_mod.download_url,
+            remote_mod.file_size,
+            expected.join(", ")
+        ));
+        for mismatch in mismatches {
+            contents.push_str(&format!(
+                "Mirror: {}\n  Computed checksum: {}\n  Downloaded size: {} bytes\n",
+                mismatch.url, mismatch.computed, mismatch.size
+            ));
+        }
+        contents.push('\n');
+    }
+    if count == 0 {
+        return Ok(0);
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let debug_filename = fileutil::replace_home_dir_with_tilde(path);
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open checksum mismatch report '{debug_filename}'"))?;
+    write!(
+        file,
+        "# {} {} checksum mismatch report, {}\n{}",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION"),
+        UtcDateTime::now().to_rfc3339(),
+        contents
+    )?;
+
+    tracing::info!("Checksum mismatch report written to '{}'", debug_filename);
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests_report {
+    use super::*;
+    use crate::mod_registry::RemoteModInfo;
+
+    fn remote_mod(version: &str, size: u64) -> RemoteModInfo {
+        RemoteModInfo {
+            version: version.to_string(),
+            download_url: "https://gamebanana.com/dl/1".to_string(),
+            file_size: size,
+            ..Default::default()
+        }
+    }
+
+    fn sample_summary() -> DownloadSummary {
+        DownloadSummary {
+            succeeded: vec![("A".to_string(), remote_mod("1.1.0", 100))],
+            failed: vec![(
+                "B".to_string(),
+                remote_mod("2.0.0", 50),
+                anyhow::anyhow!("network error"),
+            )],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_append_checksum_mismatches() -> Result<()> {
+        let mut summary = sample_summary();
+        let mismatch = DownloadError::InvalidChecksum {
+            mod_name: "C".to_string(),
+            mismatches: vec![crate::download::MismatchedDownload {
+                url: "https://banana-mirror-mods.celestemods.com/1.zip".to_string(),
+                computed: "1f3c5a7e9b2d4c60".to_string(),
+                size: 42,
+            }],
+            expected: vec!["0000000000000000".to_string()],
+        };
+        summary
+            .failed
+            .push(("C".to_string(), remote_mod("3.0.0", 40), mismatch.into()));
+
+        let temp_dir = tempfile::tempdir()?;
+        let path = temp_dir.path().join("mismatch.txt");
+        assert_eq!(append_checksum_mismatches(&path, &summary)?, 1);
+        let contents = fs::read_to_string(&path)?;
+        for expected in [
+            "Mod: C\n",
+            "URL: https://gamebanana.com/dl/1\n",
+            "Expected size: 40 bytes\n",
+            "Expected checksums: 0000000000000000\n",
+            "Mirror: https://banana-mirror-mods.celestemods.com/1.zip\n",
+            "  Computed checksum: 1f3c5a7e9b2d4c60\n",
+            "  Downloaded size: 42 bytes\n",
+        ] {
+            assert!(contents.contains(expected), "{expected:?} in {contents}");
+        }
+        // The network error of B is not a checksum mismatch.
+        assert!(!contents.contains("Mod: B"));
+
+        assert_eq!(append_checksum_mismatches(&path, &sample_summary())?, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_record_installs() {
+        let mut report = Report::new("install");
+        report.record_installs(&sample_summary());
+
+        assert_eq!(report.installed.len(), 1);
+        assert!(report.updated.is_empty());
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(report.failed[0].name, "B");
+        assert_eq!(report.total_bytes, 100);
+    }
+
+    #[test]
+    fn test_record_updates_with_previous_version() {
+        let mut report = Report::new("update");
+        report.record_updates(&sample_summary(), |name| {
+            (name == "A").then(|| "1.0.0".to_string())
+        });
+
+        assert_eq!(
+            report.updated,
+            vec![ModRecord {
+                name: "A".to_string(),
+                version: "1.1.0".to_string(),
+                previous_version: Some("1.0.0".to_string()),
+                url: "https://gamebanana.com/dl/1".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_append_update_log_only_lists_succeeded() -> anyhow::Result<()> {
+        let mut report = Report::new("update");
+        report.record_updates(&sample_summary(), |_| Some("1.0.0".to_string()));
+
+        let temp_dir = tempfile::tempdir()?;
+        let path = temp_dir.path().join("updates.txt");
+        append_update_log(&path, report.updated())?;
+        append_update_log(&path, report.updated())?;
+
+        let contents = fs::read_to_string(&path)?;
+        assert_eq!(contents.matches("A: 1.0.0 -> 1.1.0").count(), 2);
+        assert!(!contents.contains("B:"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_finish_and_write() -> anyhow::Result<()> {
+        let mut report = Report::new("update");
+        report.finish(&Err(anyhow::anyhow!("registry unavailable")));
+
+        let temp_dir = tempfile::tempdir()?;
+        let path = temp_dir.path().join("report.json");
+        report.write_to(&path)?;
+
+        let json: serde_json::Value = serde_json::from_slice(&std::fs::read(&path)?)?;
+        assert_eq!(json["command"], "update");
+        assert_eq!(json["success"], false);
+        assert_eq!(json["error"], "registry unavailable");
+        Ok(())
+    }
+}
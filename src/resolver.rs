@@ -0,0 +1,208 @@
+//! Dependency resolution of the mods to install.
+//!
+//! The resolution only works on the registry, the dependency graph and the installed mods that
+//! have already been loaded, without any I/O, so it can be tested with in-memory fixtures.
+use std::collections::HashSet;
+
+use crate::{
+    dependency::{DependencyGraph, ModDependencyQuery},
+    mod_registry::{RemoteModInfo, RemoteModRegistry},
+};
+
+/// What needs to be done to install a mod with all of its dependencies.
+///
+/// The remote information is borrowed from the registry, so resolving doesn't copy checksums
+/// and URLs of mods that end up not being downloaded.
+#[derive(Debug, Default)]
+pub struct InstallPlan<'a> {
+    /// Mods to download with their remote information, sorted by name
+    pub downloads: Vec<(String, &'a RemoteModInfo)>,
+    /// Required mods that are already installed, sorted by name
+    pub already_installed: Vec<String>,
+    /// Required mods missing from the registry, which can't be downloaded, sorted by name
+    pub unavailable: Vec<String>,
+}
+
+/// Resolves the mods to download to install the given mod, including the mod itself.
+///
+/// The installed mod names are expected to be normalized with
+/// [`normalize_name`](crate::manifest::normalize_name).
+pub fn resolve<'a>(
+    mod_name: &str,
+    dependency_graph: &DependencyGraph,
+    mod_registry: &'a RemoteModRegistry,
+    installed_mod_names: &HashSet<String>,
+) -> InstallPlan<'a> {
+    tracing::info!("Checking dependencies for mod: {}", mod_name);
+
+    // Collects required dependencies for the mod including the mod itself
+    let mut dependencies: Vec<String> = dependency_graph
+        .collect_all_dependencies_bfs(mod_name)
+        .into_iter()
+        .collect();
+    dependencies.sort();
+    tracing::debug!("Installed mods: {:?}", installed_mod_names);
+    tracing::debug!("Dependencies to check: {:?}", dependencies);
+
+    let mut plan = InstallPlan::default();
+    for name in dependencies {
+        if installed_mod_names.contains(&name) {
+            plan.already_installed.push(name);
+        } else if let Some(remote_mod) = mod_registry.get(&name) {
+            tracing::info!("Mod [{}] is available: {}", name, remote_mod.download_url);
+            plan.downloads.push((name, remote_mod));
+        } else {
+            tracing::warn!("Mod [{}] is not available in the registry", name);
+            plan.unavailable.push(name);
+        }
+    }
+    tracing::info!(
+        "Missing mods: {:?}",
+        plan.downloads
+            .iter()
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>()
+    );
+
+    plan
+}
+
+#[cfg(test)]
+mod tests_resolver {
+    use super::*;
+    use crate::{dependency::DependencyInfo, manifest::Dependency};
+
+    fn dep(name: &str) -> Dependency {
+        Dependency {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn graph(edges: &[(&str, &[&str])]) -> DependencyGraph {
+        edges
+            .iter()
+            .map(|(name, deps)| {
+                (
+                    name.to_string(),
+                    DependencyInfo::new(deps.iter().map(|d| dep(d)).collect()),
+                )
+            })
+            .collect()
+    }
+
+    fn registry(names: &[&str]) -> RemoteModRegistry {
+        names
+            .iter()
+            .map(|name| (name.to_string(), RemoteModInfo::default()))
+            .collect()
+    }
+
+    fn installed(names: &[&str]) -> HashSet<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    fn download_names<'a>(plan: &'a InstallPlan<'_>) -> Vec<&'a str> {
+        plan.downloads
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_resolve_partially_installed() {
+        let graph = graph(&[("A", &["B", "C"]), ("B", &["D"]), ("C", &[]), ("D", &[])]);
+        let registry = registry(&["A", "B", "C", "D"]);
+
+        let plan = resolve("A", &graph, &registry, &installed(&["A", "B"]));
+        assert_eq!(download_names(&plan), ["C", "D"]);
+        assert_eq!(plan.already_installed, ["A", "B"]);
+        assert!(plan.unavailable.is_empty());
+    }
+
+    #[test]
+    fn test_resolve_diamond_downloads_shared_dependency_once() {
+        // A -> B -> D, A -> C -> D
+        let graph = graph(&[("A", &["B", "C"]), ("B", &["D"]), ("C", &["D"]), ("D", &[])]);
+        let registry = registry(&["A", "B", "C", "D"]);
+
+        let plan = resolve("A", &graph, &registry, &installed(&[]));
+        assert_eq!(download_names(&plan), ["A", "B", "C", "D"]);
+    }
+
+    #[test]
+    fn test_resolve_without_dependency_graph_downloads_only_the_mod() {
+        // `install --no-deps-on-error` resolves against an empty graph.
+        let registry = registry(&["A", "B"]);
+
+        let plan = resolve("A", &DependencyGraph::new(), &registry, &installed(&[]));
+        assert_eq!(download_names(&plan), ["A"]);
+        assert!(plan.unavailable.is_empty());
+    }
+
+    #[test]
+    fn test_resolve_missing_from_registry() {
+        let graph = graph(&[("A", &["B", "Gone"]), ("B", &[])]);
+        let registry = registry(&["A", "B"]);
+
+        let plan = resolve("A", &graph, &registry, &installed(&[]));
+        assert_eq!(download_names(&plan), ["A", "B"]);
+        assert_eq!(plan.unavailable, ["Gone"]);
+    }
+
+    #[test]
+    fn test_resolve_already_satisfied() {
+        let graph = graph(&[("A", &["B"]), ("B", &[])]);
+        let registry = registry(&["A", "B"]);
+
+        let plan = resolve("A", &graph, &registry, &installed(&["A", "B"]));
+        assert!(plan.downloads.is_empty());
+        assert_eq!(plan.already_installed, ["A", "B"]);
+    }
+
+    #[test]
+    fn test_resolve_deep_chain() {
+        let names: Vec<String> = (0..50).map(|i| format!("M{i:02}")).collect();
+        let edges: Vec<(String, Vec<&str>)> = names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                let next = names.get(i + 1).map(String::as_str);
+                (name.clone(), next.into_iter().collect())
+            })
+            .collect();
+        let graph: DependencyGraph = edges
+            .iter()
+            .map(|(name, deps)| {
+                (
+                    name.clone(),
+                    DependencyInfo::new(deps.iter().map(|d| dep(d)).collect()),
+                )
+            })
+            .collect();
+        let registry: RemoteModRegistry = names
+            .iter()
+            .map(|name| (name.clone(), RemoteModInfo::default()))
+            .collect();
+
+        let plan = resolve("M00", &graph, &registry, &installed(&["M49"]));
+        assert_eq!(plan.downloads.len(), 49);
+        assert_eq!(plan.already_installed, ["M49"]);
+        // The plan borrows the registry entries instead of copying them.
+        assert!(
+            plan.downloads
+                .iter()
+                .all(|(name, remote_mod)| std::ptr::eq(*remote_mod, &registry[name]))
+        );
+    }
+
+    #[test]
+    fn test_resolve_matches_normalized_names() {
+        // "CaféHelper" is written in NFD with a trailing space in the dependency list.
+        let graph = graph(&[("A", &["Cafe\u{301}Helper "]), ("Caf\u{e9}Helper", &[])]);
+        let registry = registry(&["A", "Caf\u{e9}Helper"]);
+
+        let plan = resolve("A", &graph, &registry, &installed(&["Caf\u{e9}Helper"]));
+        assert_eq!(download_names(&plan), ["A"]);
+    }
+}
@@ -0,0 +1,91 @@
+//! Minimal UTC date and time formatting without pulling in a date library.
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Calendar representation of a UTC instant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UtcDateTime {
+    pub year: i64,
+    pub month: u32,
+    pub day: u32,
+    pub hour: u32,
+    pub minute: u32,
+    pub second: u32,
+}
+
+impl UtcDateTime {
+    /// Converts seconds since the Unix epoch into a calendar date and time.
+    pub fn from_unix_secs(secs: i64) -> Self {
+        let days = secs.div_euclid(86_400);
+        let seconds_of_day = secs.rem_euclid(86_400);
+
+        // NOTE: Howard Hinnant's `civil_from_days` algorithm.
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z.rem_euclid(146_097);
+        let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+        let year = yoe + era * 400 + i64::from(month <= 2);
+
+        Self {
+            year,
+            month,
+            day,
+            hour: (seconds_of_day / 3600) as u32,
+            minute: (seconds_of_day % 3600 / 60) as u32,
+            second: (seconds_of_day % 60) as u32,
+        }
+    }
+
+    /// Returns the current UTC date and time.
+    pub fn now() -> Self {
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or_default();
+        Self::from_unix_secs(secs)
+    }
+
+    /// Formats as RFC 3339, e.g. `2025-01-31T12:34:56Z`.
+    pub fn to_rfc3339(self) -> String {
+        format!(
+            "{}T{:02}:{:02}:{:02}Z",
+            self.to_date(),
+            self.hour,
+            self.minute,
+            self.second
+        )
+    }
+
+    /// Formats the date part only, e.g. `2025-01-31`.
+    pub fn to_date(self) -> String {
+        format!("{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+#[cfg(test)]
+mod tests_timeutil {
+    use super::*;
+
+    #[test]
+    fn test_from_unix_secs_epoch() {
+        let dt = UtcDateTime::from_unix_secs(0);
+        assert_eq!(dt.to_rfc3339(), "1970-01-01T00:00:00Z");
+    }
+
+    #[test]
+    fn test_from_unix_secs_leap_day() {
+        // 2024-02-29T23:59:59Z
+        let dt = UtcDateTime::from_unix_secs(1_709_251_199);
+        assert_eq!(dt.to_rfc3339(), "2024-02-29T23:59:59Z");
+        assert_eq!(dt.to_date(), "2024-02-29");
+    }
+
+    #[test]
+    fn test_from_unix_secs_before_epoch() {
+        let dt = UtcDateTime::from_unix_secs(-1);
+        assert_eq!(dt.to_rfc3339(), "1969-12-31T23:59:59Z");
+    }
+}
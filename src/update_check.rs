@@ -0,0 +1,183 @@
+//! Results of the previous update checks, so that `update` doesn't hash unchanged mods again.
+//!
+//! A mod found up to date is recorded with the `LastUpdate` of its registry entry and the
+//! modification time of its archive. On the next check, it is considered up to date without
+//! hashing it as long as neither of them changed. `--full` hashes every mod regardless.
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config, fileutil, local_mod::LocalMod, manifest::normalize_name, mod_registry::RemoteModInfo,
+};
+
+/// The name of the file of the last update checks in the state directory.
+const LAST_CHECKS_FILE: &str = "last-checks.json";
+
+/// The last check which found a mod up to date.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LastCheck {
+    /// When the check happened, as a Unix timestamp
+    pub checked_at: u64,
+    /// `LastUpdate` of the registry entry the mod matched, as a Unix timestamp
+    pub registry_updated_at: u64,
+    /// Modification time of the archive, as a Unix timestamp
+    pub modified: Option<u64>,
+}
+
+/// Last checks by mod name.
+///
+/// The names are normalized with [`normalize_name`].
+pub type LastChecks = BTreeMap<String, LastCheck>;
+
+/// Returns the path of the file of the last update checks in the state directory.
+pub fn last_checks_path() -> Option<PathBuf> {
+    config::get_state_directory().map(|state_dir| state_dir.join(LAST_CHECKS_FILE))
+}
+
+/// Reads the last update checks. Returns no checks if the file does not exist.
+///
+/// # Errors
+///
+/// Returns an error if the file exists but cannot be read or parsed.
+pub fn read(path: &Path) -> Result<LastChecks> {
+    if !path.exists() {
+        return Ok(LastChecks::new());
+    }
+    let contents = fs::read_to_string(path)?;
+    serde_json::from_str(&contents).with_context(|| {
+        format!(
+            "Failed to parse the last update checks '{}'",
+            fileutil::replace_home_dir_with_tilde(path)
+        )
+    })
+}
+
+/// Writes the last update checks atomically, creating the state directory if needed.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be written.
+pub fn write(path: &Path, last_checks: &LastChecks) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fileutil::write_atomic(path, serde_json::to_string_pretty(last_checks)?)?;
+    Ok(())
+}
+
+/// Returns the modification time of the file as a Unix timestamp.
+fn modified_secs(path: &Path) -> Option<u64> {
+    let modified = fs::metadata(path).and_then(|meta| meta.modified()).ok()?;
+    modified
+        .duration_since(UNIX_EPOCH)
+        .ok()
+        .map(|duration| duration.as_secs())
+}
+
+/// Returns the record of a mod found up to date, unless the registry doesn't tell when the
+/// entry was updated.
+pub fn record(local_mod: &LocalMod, remote_mod: &RemoteModInfo) -> Option<LastCheck> {
+    Some(LastCheck {
+        checked_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default(),
+        registry_updated_at: remote_mod.updated_at?,
+        modified: modified_secs(&local_mod.location),
+    })
+}
+
+/// Checks if the mod is still up to date according to its last check, without hashing it.
+pub fn is_unchanged(
+    last_checks: &LastChecks,
+    local_mod: &LocalMod,
+    remote_mod: &RemoteModInfo,
+) -> bool {
+    let Some(last_check) = last_checks.get(normalize_name(&local_mod.manifest.name).as_ref())
+    else {
+        return false;
+    };
+    remote_mod
+        .updated_at
+        .is_some_and(|updated_at| updated_at <= last_check.registry_updated_at)
+        && last_check.modified.is_some()
+        && last_check.modified == modified_secs(&local_mod.location)
+}
+
+#[cfg(test)]
+mod tests_update_check {
+    use super::*;
+
+    #[test]
+    fn test_is_unchanged() -> anyhow::Result<()> {
+        let local_mod = LocalMod::from_path(Path::new("./test/test-mod.zip"))?;
+        let remote_mod = |updated_at| RemoteModInfo {
+            updated_at,
+            ..Default::default()
+        };
+        assert_eq!(record(&local_mod, &remote_mod(None)), None);
+
+        let last_check = record(&local_mod, &remote_mod(Some(1_700_000_000))).unwrap();
+        let key = normalize_name(&local_mod.manifest.name).into_owned();
+        let last_checks = LastChecks::from([(key, last_check.clone())]);
+        assert!(is_unchanged(
+            &last_checks,
+            &local_mod,
+            &remote_mod(Some(1_700_000_000))
+        ));
+        assert!(!is_unchanged(
+            &last_checks,
+            &local_mod,
+            &remote_mod(Some(1_700_000_001))
+        ));
+        assert!(!is_unchanged(&last_checks, &local_mod, &remote_mod(None)));
+        assert!(!is_unchanged(
+            &LastChecks::new(),
+            &local_mod,
+            &remote_mod(Some(0))
+        ));
+
+        // The archive was replaced since the check.
+        let modified = last_check.modified.map(|secs| secs - 1);
+        let key = normalize_name(&local_mod.manifest.name).into_owned();
+        let last_checks = LastChecks::from([(
+            key,
+            LastCheck {
+                modified,
+                ..last_check
+            },
+        )]);
+        assert!(!is_unchanged(
+            &last_checks,
+            &local_mod,
+            &remote_mod(Some(0))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_then_read() -> Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let path = temp_dir.path().join("state").join(LAST_CHECKS_FILE);
+        assert!(read(&path)?.is_empty());
+
+        let last_checks = LastChecks::from([(
+            "SpeedrunTool".to_string(),
+            LastCheck {
+                checked_at: 1_700_000_100,
+                registry_updated_at: 1_700_000_000,
+                modified: Some(1_600_000_000),
+            },
+        )]);
+        write(&path, &last_checks)?;
+        assert_eq!(read(&path)?, last_checks);
+        Ok(())
+    }
+}
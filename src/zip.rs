@@ -3,7 +3,7 @@
 //! searching for the manifest, and returning its contents as a byte vector.
 use std::path::Path;
 
-use zip_search::ZipSearcher;
+use zip_search::{ZipSearchError, ZipSearcher};
 
 /// Errors that can occur while finding the manifest file in a ZIP archive.
 #[derive(Debug, thiserror::Error)]
@@ -13,6 +13,12 @@ pub enum ZipError {
         "the manifest file could not be found. It may be misspelled or have the extension `.yml`"
     )]
     NotFound,
+    /// The manifest is compressed with a method other than stored or deflate.
+    #[error(
+        "the archive uses the unsupported compression method {0} ({name}), repack it with deflate",
+        name = compression_method_name(*.0)
+    )]
+    UnsupportedCompression(u16),
     /// Failed to parse the ZIP file. Broken ZIP format.
     #[error(transparent)]
     Parse(#[from] zip_search::ZipSearchError),
@@ -20,18 +26,28 @@ pub enum ZipError {
 
 /// Finds manifest file in the ZIP file and returns the bytes of its contents.
 ///
+/// The manifest may be nested in a subdirectory (e.g., `ModName/everest.yaml`), in which case
+/// the shallowest one is used.
+///
 /// # Errors
 ///
 /// - `ZipError::NotFound`: The manifest file not found in given path.
+/// - `ZipError::UnsupportedCompression`: The manifest is compressed with an unsupported method.
 /// - `ZipError::Parse`: Could not parse ZIP archive. Broken or invalid.
 pub(crate) fn find_manifest(file_path: &Path) -> Result<Vec<u8>, ZipError> {
     const MANIFEST_FILE_NAME: &str = "everest.yaml";
 
     let mut zip_searcher = ZipSearcher::new(file_path)?;
 
-    match zip_searcher.find_file(MANIFEST_FILE_NAME) {
+    match zip_searcher.find_file_nested(MANIFEST_FILE_NAME) {
         Ok(Some(entry)) => {
-            let mut buffer = zip_searcher.read_file(&entry)?;
+            let mut buffer = match zip_searcher.read_file(&entry) {
+                Ok(buffer) => buffer,
+                Err(ZipSearchError::UnsupportedCompression(method)) => {
+                    return Err(ZipError::UnsupportedCompression(method));
+                }
+                Err(err) => return Err(err.into()),
+            };
 
             // Check for UTF-8 BOM and remove if present
             if buffer.starts_with(&[0xEF, 0xBB, 0xBF]) {
@@ -45,6 +61,36 @@ pub(crate) fn find_manifest(file_path: &Path) -> Result<Vec<u8>, ZipError> {
     }
 }
 
+/// Returns the name of a ZIP compression method, as listed in the ZIP specification.
+fn compression_method_name(method: u16) -> &'static str {
+    match method {
+        0 => "stored",
+        8 => "deflate",
+        9 => "deflate64",
+        12 => "bzip2",
+        14 => "LZMA",
+        93 => "Zstandard",
+        95 => "XZ",
+        98 => "PPMd",
+        99 => "AES encryption",
+        _ => "unknown",
+    }
+}
+
+/// Returns the archive comment of the ZIP file, or `None` if it has none.
+///
+/// Invalid UTF-8 is replaced, since the comment is only displayed.
+///
+/// # Errors
+///
+/// - `ZipError::Parse`: Could not parse ZIP archive. Broken or invalid.
+pub(crate) fn read_comment(file_path: &Path) -> Result<Option<String>, ZipError> {
+    let zip_searcher = ZipSearcher::new(file_path)?;
+    let comment = String::from_utf8_lossy(zip_searcher.comment());
+    let comment = comment.trim();
+    Ok((!comment.is_empty()).then(|| comment.to_string()))
+}
+
 // TODO: Implement functions which can find `Dialog/English.txt` file in the ZIP file.
 //       We can modify the `find_manifest` function to accept a filename parameter.
 
@@ -66,6 +112,36 @@ mod tests_zip {
         Ok(())
     }
 
+    #[test]
+    fn test_find_manifest_in_zip_nested() -> anyhow::Result<()> {
+        let mod_path = Path::new("./test/nested-manifest.zip");
+        let manifest_bytes = find_manifest(mod_path)?;
+        assert!(String::from_utf8(manifest_bytes)?.contains("- Name: nested-mod"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_comment() -> anyhow::Result<()> {
+        assert_eq!(read_comment(Path::new("./test/test-mod.zip"))?, None);
+
+        // Append a comment to the end of central directory record, which has none yet.
+        let comment = b"Source: https://gamebanana.com/mods/000000\nBuilt: 2025-01-31";
+        let mut bytes = std::fs::read("./test/test-mod.zip")?;
+        let comment_length = bytes.len() - 2;
+        bytes[comment_length..].copy_from_slice(&(comment.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(comment);
+        let temp_dir = tempfile::tempdir()?;
+        let path = temp_dir.path().join("commented.zip");
+        std::fs::write(&path, bytes)?;
+
+        assert_eq!(
+            read_comment(&path)?.as_deref(),
+            Some("Source: https://gamebanana.com/mods/000000\nBuilt: 2025-01-31")
+        );
+        assert!(find_manifest(&path).is_ok());
+        Ok(())
+    }
+
     #[test]
     fn test_find_manifest_in_zip_invalid() {
         let mod_path = Path::new("./test/missing-manifest.zip");
@@ -77,4 +153,15 @@ mod tests_zip {
                 .is_some_and(|e| matches!(e, ZipError::NotFound))
         );
     }
+
+    #[test]
+    fn test_find_manifest_in_zip_unsupported_compression() {
+        let mod_path = Path::new("./test/bzip2-manifest.zip");
+        let err = find_manifest(mod_path).unwrap_err();
+        assert!(matches!(err, ZipError::UnsupportedCompression(12)));
+        assert_eq!(
+            err.to_string(),
+            "the archive uses the unsupported compression method 12 (bzip2), repack it with deflate"
+        );
+    }
 }
@@ -108,6 +108,7 @@ struct EndOfCentralDirectory {
     total_entries: u16,
     central_directory_offset: u32,
     central_directory_size: u32,
+    comment: Vec<u8>,
 }
 
 /// Represents a single entry in the ZIP file's central directory.
@@ -233,8 +234,12 @@ impl ZipSearcher {
                 {
                     // Additional validation: check if comment length makes sense
                     let comment_len = read_u16_le(&buffer[pos + 20..]) as usize;
-                    if pos + MIN_EOCD_SIZE + comment_len <= buffer.len() {
-                        return Ok(eocd);
+                    let comment_start = pos + MIN_EOCD_SIZE;
+                    if comment_start + comment_len <= buffer.len() {
+                        return Ok(EndOfCentralDirectory {
+                            comment: buffer[comment_start..comment_start + comment_len].to_vec(),
+                            ..eocd
+                        });
                     }
                 }
             }
@@ -275,9 +280,17 @@ impl ZipSearcher {
             total_entries,
             central_directory_offset: cd_offset,
             central_directory_size: cd_size,
+            comment: Vec::new(),
         })
     }
 
+    /// Returns the archive comment, which is empty if the archive has none.
+    ///
+    /// Some tools store provenance information in it, such as the source URL or the build date.
+    pub fn comment(&self) -> &[u8] {
+        &self.eocd.comment
+    }
+
     /// Searches for a file in the ZIP central directory by name.
     ///
     /// This function scans the central directory of the ZIP file to find an entry
@@ -336,12 +349,44 @@ impl ZipSearcher {
         &mut self,
         target_name: &str,
     ) -> ZipSearchResult<Option<CentralDirectoryEntry>> {
+        let target_bytes = target_name.as_bytes();
+        self.find_entry_by(|file_name| (file_name == target_bytes).then_some(0))
+    }
+
+    /// Finds a file by name at the top level or in any subdirectory of the archive.
+    ///
+    /// An entry at the top level is returned as is. Otherwise, the shallowest entry whose
+    /// path ends with `/<target_name>` is returned (e.g., `ModName/everest.yaml`).
+    pub fn find_file_nested(
+        &mut self,
+        target_name: &str,
+    ) -> ZipSearchResult<Option<CentralDirectoryEntry>> {
+        let target_bytes = target_name.as_bytes();
+        self.find_entry_by(|file_name| {
+            let parent = file_name.strip_suffix(target_bytes)?;
+            if parent.is_empty() {
+                return Some(0);
+            }
+            parent
+                .ends_with(b"/")
+                .then(|| parent.iter().filter(|&&b| b == b'/').count())
+        })
+    }
+
+    /// Scans the central directory for the entry with the lowest rank.
+    ///
+    /// `rank` returns `None` for entries that don't match. The scan stops at the first entry
+    /// ranked `0`.
+    fn find_entry_by<F>(&mut self, mut rank: F) -> ZipSearchResult<Option<CentralDirectoryEntry>>
+    where
+        F: FnMut(&[u8]) -> Option<usize>,
+    {
         const CD_ENTRY_SIGNATURE: u32 = 0x02014b50;
         const MIN_CD_ENTRY_SIZE: usize = 46;
         const CHUNK_SIZE: usize = 64 * 1024; // 64KB chunks
 
-        let target_bytes = target_name.as_bytes();
         let mut buffer = ReadBuffer::new(CHUNK_SIZE);
+        let mut best: Option<(usize, CentralDirectoryEntry)> = None;
 
         // Seek to central directory
         self.file
@@ -382,7 +427,16 @@ impl ZipSearcher {
             let filename_start = MIN_CD_ENTRY_SIZE;
             let filename_end = filename_start + filename_len;
 
-            if slice.len() >= filename_end && &slice[filename_start..filename_end] == target_bytes {
+            let entry_rank = if slice.len() >= filename_end {
+                rank(&slice[filename_start..filename_end])
+            } else {
+                None
+            };
+            if let Some(entry_rank) = entry_rank
+                && best
+                    .as_ref()
+                    .is_none_or(|(best_rank, _)| entry_rank < *best_rank)
+            {
                 // Found match! Parse complete entry
                 let compression_method = read_u16_le(&slice[10..]);
                 let compressed_size = read_u32_le(&slice[20..]);
@@ -393,13 +447,17 @@ impl ZipSearcher {
                 let file_name =
                     String::from_utf8_lossy(&slice[filename_start..filename_end]).into_owned();
 
-                return Ok(Some(CentralDirectoryEntry {
+                let entry = CentralDirectoryEntry {
                     file_name,
                     compression_method,
                     uncompressed_size,
                     compressed_size,
                     local_header_offset,
-                }));
+                };
+                if entry_rank == 0 {
+                    return Ok(Some(entry));
+                }
+                best = Some((entry_rank, entry));
             }
 
             // Move to next entry
@@ -412,7 +470,7 @@ impl ZipSearcher {
             }
         }
 
-        Ok(None)
+        Ok(best.map(|(_, entry)| entry))
     }
 
     /// Read file data with optimized decompression